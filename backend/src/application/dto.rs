@@ -1,9 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 
-use crate::domain::entities::{Jurisdiction, Violation};
+use crate::domain::{
+    address::PostalAddress,
+    entities::{
+        DatePrecision, Inspection, Jurisdiction, LocationPrecision, ValidationReason, Violation,
+    },
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SourceFacilityInput {
     pub source_id: String,
     pub name: String,
@@ -13,12 +18,52 @@ pub struct SourceFacilityInput {
     pub postal_code: String,
     pub latitude: f64,
     pub longitude: f64,
+    pub location_precision: LocationPrecision,
     pub jurisdiction: Jurisdiction,
-    pub inspected_at: DateTime<Utc>,
+    pub inspection_id: Option<String>,
+    /// `None` when the source gave no usable date; never substituted.
+    pub inspected_at: Option<DateTime<Utc>>,
+    pub inspected_at_precision: Option<DatePrecision>,
+    pub inspection_type: Option<String>,
     pub raw_score: Option<f32>,
     pub letter_grade: Option<String>,
     pub placard_status: Option<String>,
     pub violations: Vec<Violation>,
+    /// Defaults the connector substituted for missing source values.
+    pub repairs: Vec<ValidationReason>,
+    /// File within a multi-file export (such as a ZIP bundle) the record was read from.
+    pub source_file: Option<String>,
+}
+
+#[cfg(test)]
+impl SourceFacilityInput {
+    /// A scored rooftop-located Pasadena record for tests to override field by field.
+    pub fn sample(source_id: &str, name: &str) -> Self {
+        use chrono::TimeZone;
+
+        Self {
+            source_id: source_id.to_owned(),
+            name: name.to_owned(),
+            address: "1 Main St".to_owned(),
+            city: "Pasadena".to_owned(),
+            state: "CA".to_owned(),
+            postal_code: "91101".to_owned(),
+            latitude: 34.14,
+            longitude: -118.14,
+            location_precision: LocationPrecision::Rooftop,
+            jurisdiction: Jurisdiction::Pasadena,
+            inspection_id: None,
+            inspected_at: Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+            inspected_at_precision: None,
+            inspection_type: None,
+            raw_score: Some(92.0),
+            letter_grade: None,
+            placard_status: None,
+            violations: Vec::new(),
+            repairs: Vec::new(),
+            source_file: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -64,6 +109,7 @@ pub struct FacilitySummary {
     pub postal_code: String,
     pub latitude: f64,
     pub longitude: f64,
+    pub location_precision: LocationPrecision,
     pub jurisdiction: String,
     pub trust_score: u8,
     pub latest_inspection_at: Option<DateTime<Utc>>,
@@ -81,12 +127,15 @@ pub struct FacilityDetail {
     pub city: String,
     pub state: String,
     pub postal_code: String,
+    pub address_components: PostalAddress,
     pub latitude: f64,
     pub longitude: f64,
+    pub location_precision: LocationPrecision,
     pub jurisdiction: String,
     pub trust_score: u8,
     pub inspections_count: usize,
     pub latest_inspection_at: Option<DateTime<Utc>>,
+    pub inspections: Vec<Inspection>,
     pub likes: u64,
     pub dislikes: u64,
     pub vote_score: i64,
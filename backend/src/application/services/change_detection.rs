@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::entities::{Facility, FacilityChange, FacilityEvent, Jurisdiction};
+
+/// The parts of a published facility that change detection compares.
+#[derive(Clone, Debug)]
+pub struct FacilitySnapshot {
+    pub name: String,
+    pub jurisdiction: Jurisdiction,
+    pub trust_score: u8,
+    pub letter_grade: Option<String>,
+    pub placard_status: Option<String>,
+}
+
+impl FacilitySnapshot {
+    pub fn of(facility: &Facility) -> Self {
+        let latest = facility
+            .inspections
+            .iter()
+            .max_by_key(|inspection| inspection.inspected_at);
+
+        Self {
+            name: facility.name.clone(),
+            jurisdiction: facility.jurisdiction.clone(),
+            trust_score: facility.trust_score,
+            letter_grade: latest.and_then(|inspection| inspection.letter_grade.clone()),
+            placard_status: latest.and_then(|inspection| inspection.placard_status.clone()),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.placard_status
+            .as_deref()
+            .is_some_and(is_closure_placard)
+    }
+}
+
+/// Diffs the facilities about to be published against the previous dataset.
+///
+/// Only facilities in `replaced`, the previous IDs this publish replaces, can be
+/// reported removed, since the rest stay published. A jurisdiction with no previous
+/// facilities is a baseline load and emits nothing, so a first run does not report
+/// every facility as added.
+pub fn detect_changes(
+    run_id: &str,
+    previous: &HashMap<String, FacilitySnapshot>,
+    current: &[Facility],
+    replaced: &HashSet<String>,
+    detected_at: DateTime<Utc>,
+) -> Vec<FacilityEvent> {
+    let mut tracked: Vec<Jurisdiction> = Vec::new();
+    for snapshot in previous.values() {
+        if !tracked.contains(&snapshot.jurisdiction) {
+            tracked.push(snapshot.jurisdiction.clone());
+        }
+    }
+    let event =
+        |facility_id: &str, snapshot: &FacilitySnapshot, change: FacilityChange| FacilityEvent {
+            id: Uuid::new_v4().to_string(),
+            run_id: run_id.to_owned(),
+            facility_id: facility_id.to_owned(),
+            facility_name: snapshot.name.clone(),
+            jurisdiction: snapshot.jurisdiction.clone(),
+            change,
+            detected_at,
+        };
+
+    let mut events = Vec::new();
+    let mut seen = HashSet::with_capacity(current.len());
+    for facility in current {
+        seen.insert(facility.id.as_str());
+        if !tracked.contains(&facility.jurisdiction) {
+            continue;
+        }
+
+        let now = FacilitySnapshot::of(facility);
+        let Some(before) = previous.get(&facility.id) else {
+            events.push(event(&facility.id, &now, FacilityChange::FacilityAdded));
+            if now.is_closed() {
+                let placard_status = now.placard_status.clone();
+                events.push(event(
+                    &facility.id,
+                    &now,
+                    FacilityChange::ClosureIssued { placard_status },
+                ));
+            }
+            continue;
+        };
+
+        if let (Some(from), Some(to)) = (&before.letter_grade, &now.letter_grade)
+            && let (Some(from_rank), Some(to_rank)) = (grade_rank(from), grade_rank(to))
+            && to_rank < from_rank
+        {
+            let change = FacilityChange::GradeDropped {
+                from: from.clone(),
+                to: to.clone(),
+            };
+            events.push(event(&facility.id, &now, change));
+        }
+
+        match (before.is_closed(), now.is_closed()) {
+            (false, true) => {
+                let placard_status = now.placard_status.clone();
+                events.push(event(
+                    &facility.id,
+                    &now,
+                    FacilityChange::ClosureIssued { placard_status },
+                ));
+            }
+            (true, false) => {
+                let placard_status = now.placard_status.clone();
+                events.push(event(
+                    &facility.id,
+                    &now,
+                    FacilityChange::Reopened { placard_status },
+                ));
+            }
+            _ => {}
+        }
+
+        if before.trust_score != now.trust_score {
+            let change = FacilityChange::TrustScoreChanged {
+                from: before.trust_score,
+                to: now.trust_score,
+            };
+            events.push(event(&facility.id, &now, change));
+        }
+    }
+
+    let mut removed = previous
+        .iter()
+        .filter(|(id, _)| replaced.contains(id.as_str()) && !seen.contains(id.as_str()))
+        .collect::<Vec<_>>();
+    removed.sort_by(|left, right| left.0.cmp(right.0));
+    for (id, snapshot) in removed {
+        events.push(event(id, snapshot, FacilityChange::FacilityRemoved));
+    }
+
+    events
+}
+
+/// Higher is better; `None` for grades that are not on the A/B/C scale.
+fn grade_rank(grade: &str) -> Option<u8> {
+    match grade.trim().to_ascii_uppercase().as_str() {
+        "A" => Some(3),
+        "B" => Some(2),
+        "C" => Some(1),
+        _ => None,
+    }
+}
+
+/// Long Beach posts red placards for closures; other feeds spell the result out.
+fn is_closure_placard(status: &str) -> bool {
+    let status = status.trim().to_ascii_lowercase();
+    status == "red" || status.contains("closed") || status.contains("closure")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{FacilitySnapshot, detect_changes};
+    use crate::domain::entities::{Facility, FacilityChange, Inspection, Jurisdiction};
+
+    fn facility(id: &str, grade: &str, placard: Option<&str>, trust_score: u8) -> Facility {
+        Facility {
+            city: "Long Beach".to_owned(),
+            postal_code: "90802".to_owned(),
+            latitude: 33.77,
+            longitude: -118.19,
+            jurisdiction: Jurisdiction::LongBeach,
+            trust_score,
+            inspections: vec![Inspection {
+                inspection_id: format!("{id}-1"),
+                inspected_at: Some(Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap()),
+                inspected_at_precision: None,
+                inspection_type: None,
+                raw_score: None,
+                letter_grade: Some(grade.to_owned()),
+                placard_status: placard.map(str::to_owned),
+                violations: Vec::new(),
+            }],
+            updated_at: Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap(),
+            ..Facility::sample(id)
+        }
+    }
+
+    fn snapshots(facilities: &[Facility]) -> HashMap<String, FacilitySnapshot> {
+        facilities
+            .iter()
+            .map(|facility| (facility.id.clone(), FacilitySnapshot::of(facility)))
+            .collect()
+    }
+
+    #[test]
+    fn emits_typed_events_for_changed_facilities() {
+        let previous = snapshots(&[
+            facility("kept", "A", None, 90),
+            facility("closing", "A", None, 80),
+            facility("reopening", "B", Some("Red"), 40),
+            facility("gone", "A", None, 85),
+        ]);
+        let current = vec![
+            facility("kept", "B", None, 90),
+            facility("closing", "A", Some("Closed"), 80),
+            facility("reopening", "B", Some("Yellow"), 55),
+            facility("new", "A", None, 75),
+        ];
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+
+        let replaced = previous.keys().cloned().collect::<HashSet<_>>();
+
+        let events = detect_changes("run-1", &previous, &current, &replaced, now);
+        let changes = events
+            .iter()
+            .map(|event| (event.facility_id.as_str(), event.change.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "kept",
+                    FacilityChange::GradeDropped {
+                        from: "A".to_owned(),
+                        to: "B".to_owned()
+                    }
+                ),
+                (
+                    "closing",
+                    FacilityChange::ClosureIssued {
+                        placard_status: Some("Closed".to_owned())
+                    }
+                ),
+                (
+                    "reopening",
+                    FacilityChange::Reopened {
+                        placard_status: Some("Yellow".to_owned())
+                    }
+                ),
+                (
+                    "reopening",
+                    FacilityChange::TrustScoreChanged { from: 40, to: 55 }
+                ),
+                ("new", FacilityChange::FacilityAdded),
+                ("gone", FacilityChange::FacilityRemoved),
+            ]
+        );
+        assert!(events.iter().all(|event| event.run_id == "run-1"));
+    }
+
+    #[test]
+    fn baseline_and_retained_facilities_emit_nothing() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let current = vec![facility("new", "A", Some("Red"), 75)];
+
+        let baseline = detect_changes("run-1", &HashMap::new(), &current, &HashSet::new(), now);
+        assert!(baseline.is_empty());
+
+        let previous = snapshots(&[facility("gone", "A", None, 85)]);
+        let retained = detect_changes("run-2", &previous, &[], &HashSet::new(), now);
+        assert!(retained.is_empty());
+    }
+}
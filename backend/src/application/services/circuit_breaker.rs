@@ -0,0 +1,125 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::entities::{CircuitState, ConnectorCircuit};
+
+/// When a connector's circuit opens and how long it stays open before a probe.
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitBreakerPolicy {
+    /// Consecutive failed runs that open the circuit.
+    pub failure_threshold: u32,
+    /// How long an open circuit skips its connector before letting one run probe it.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::hours(72),
+        }
+    }
+}
+
+/// What a refresh should do with a connector given its circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitDecision {
+    Call,
+    /// The cooldown has elapsed; call the connector once to see if it recovered.
+    Probe,
+    Skip {
+        retry_after: DateTime<Utc>,
+    },
+}
+
+impl CircuitBreakerPolicy {
+    pub fn decide(&self, circuit: &ConnectorCircuit, now: DateTime<Utc>) -> CircuitDecision {
+        match circuit.state {
+            CircuitState::Closed => CircuitDecision::Call,
+            CircuitState::HalfOpen => CircuitDecision::Probe,
+            CircuitState::Open => {
+                let retry_after = circuit
+                    .opened_at
+                    .unwrap_or(now)
+                    .checked_add_signed(self.cooldown)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+                if now >= retry_after {
+                    CircuitDecision::Probe
+                } else {
+                    CircuitDecision::Skip { retry_after }
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&self, circuit: &mut ConnectorCircuit) {
+        circuit.state = CircuitState::Closed;
+        circuit.consecutive_failures = 0;
+        circuit.opened_at = None;
+    }
+
+    /// Counts a failed run. A failed probe re-opens the circuit for a fresh cooldown.
+    pub fn record_failure(&self, circuit: &mut ConnectorCircuit, error: &str, now: DateTime<Utc>) {
+        circuit.consecutive_failures = circuit.consecutive_failures.saturating_add(1);
+        circuit.last_failure_at = Some(now);
+        circuit.last_error = Some(error.to_owned());
+
+        if circuit.state == CircuitState::HalfOpen
+            || circuit.consecutive_failures >= self.failure_threshold.max(1)
+        {
+            circuit.state = CircuitState::Open;
+            circuit.opened_at = Some(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{CircuitBreakerPolicy, CircuitDecision};
+    use crate::domain::entities::{CircuitState, ConnectorCircuit};
+
+    #[test]
+    fn opens_after_threshold_and_probes_after_cooldown() {
+        let policy = CircuitBreakerPolicy {
+            failure_threshold: 2,
+            cooldown: Duration::hours(6),
+        };
+        let start = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let mut circuit = ConnectorCircuit::closed("cpra_import_orange_pasadena");
+
+        policy.record_failure(&mut circuit, "timeout", start);
+        assert_eq!(circuit.state, CircuitState::Closed);
+        policy.record_failure(&mut circuit, "timeout", start);
+        assert_eq!(circuit.state, CircuitState::Open);
+
+        assert_eq!(
+            policy.decide(&circuit, start + Duration::hours(1)),
+            CircuitDecision::Skip {
+                retry_after: start + Duration::hours(6)
+            }
+        );
+        assert_eq!(
+            policy.decide(&circuit, start + Duration::hours(6)),
+            CircuitDecision::Probe
+        );
+    }
+
+    #[test]
+    fn failed_probe_reopens_and_success_closes() {
+        let policy = CircuitBreakerPolicy::default();
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let mut circuit = ConnectorCircuit::closed("long_beach_closures_page");
+        circuit.state = CircuitState::HalfOpen;
+        circuit.consecutive_failures = 3;
+
+        policy.record_failure(&mut circuit, "HTTP status 503", now);
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.opened_at, Some(now));
+
+        policy.record_success(&mut circuit);
+        assert_eq!(circuit.state, CircuitState::Closed);
+        assert_eq!(circuit.consecutive_failures, 0);
+        assert_eq!(policy.decide(&circuit, now), CircuitDecision::Call);
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
 
 use chrono::{DateTime, Utc};
 
@@ -8,9 +11,11 @@ use crate::{
         ScoreSliceCounts,
     },
     domain::{
-        entities::{Facility, FacilityVoteSummary},
+        address::{PostalAddress, standard_abbreviation},
+        entities::{Facility, FacilityVoteSummary, SourceRecordLink},
         repositories::FacilityRepository,
     },
+    infrastructure::lives_export::{LivesFeedInfo, write_lives_zip},
 };
 
 #[derive(Clone)]
@@ -35,67 +40,85 @@ impl DirectoryService {
             .unwrap_or(false);
 
         let mut search_relevance: HashMap<String, usize> = HashMap::new();
-        if let Some(term) = query.q.as_ref().map(|value| normalize_for_search(value)) {
-            if !term.is_empty() {
-                let search_tokens = term.split_whitespace().collect::<Vec<_>>();
-                facilities.retain(|facility| {
-                    let candidate = normalize_for_search(&format!(
-                        "{} {} {} {}",
-                        facility.name, facility.address, facility.city, facility.postal_code
-                    ));
-                    let candidate_tokens = candidate.split_whitespace().collect::<Vec<_>>();
-
-                    let relevance = search_tokens
-                        .iter()
-                        .map(|query_token| {
-                            candidate_tokens
-                                .iter()
-                                .map(|candidate_token| {
-                                    token_match_score(candidate_token, query_token)
-                                })
-                                .max()
-                                .unwrap_or(0)
-                        })
-                        .sum::<usize>();
-
-                    // Google-like behavior: include all facilities with meaningful token overlap
-                    // and rank stronger matches first.
-                    if relevance > 0 {
-                        search_relevance.insert(facility.id.clone(), relevance);
-                        true
-                    } else {
-                        false
-                    }
-                });
-            }
+        if let Some(term) = query.q.as_ref().map(|value| normalize_for_search(value))
+            && !term.is_empty()
+        {
+            // Addresses are stored in USPS form, so "west" and "street" also try "w" and
+            // "st".
+            let search_tokens = term
+                .split_whitespace()
+                .map(|token| {
+                    let abbreviation = standard_abbreviation(token)
+                        .map(str::to_ascii_lowercase)
+                        .filter(|abbreviation| abbreviation != token);
+                    (token, abbreviation)
+                })
+                .collect::<Vec<_>>();
+            facilities.retain(|facility| {
+                let candidate = normalize_for_search(&format!(
+                    "{} {} {} {}",
+                    facility.name, facility.address, facility.city, facility.postal_code
+                ));
+                let candidate_tokens = candidate.split_whitespace().collect::<Vec<_>>();
+
+                let relevance = search_tokens
+                    .iter()
+                    .map(|(query_token, abbreviation)| {
+                        candidate_tokens
+                            .iter()
+                            .map(|candidate_token| {
+                                let abbreviated =
+                                    abbreviation.as_deref().map_or(0, |abbreviation| {
+                                        token_match_score(candidate_token, abbreviation)
+                                    });
+                                token_match_score(candidate_token, query_token).max(abbreviated)
+                            })
+                            .max()
+                            .unwrap_or(0)
+                    })
+                    .sum::<usize>();
+
+                // Google-like behavior: include all facilities with meaningful token overlap
+                // and rank stronger matches first.
+                if relevance > 0 {
+                    search_relevance.insert(facility.id.clone(), relevance);
+                    true
+                } else {
+                    false
+                }
+            });
         }
 
         if let Some(jurisdiction) = query
             .jurisdiction
             .as_ref()
             .map(|value| value.trim().to_ascii_lowercase())
+            && !jurisdiction.is_empty()
+            && jurisdiction != "all"
         {
-            if !jurisdiction.is_empty() && jurisdiction != "all" {
-                facilities.retain(|facility| {
-                    facility
-                        .jurisdiction
-                        .label()
-                        .eq_ignore_ascii_case(jurisdiction.as_str())
-                });
-            }
+            facilities.retain(|facility| {
+                facility
+                    .jurisdiction
+                    .label()
+                    .eq_ignore_ascii_case(jurisdiction.as_str())
+            });
         }
 
         // Search terms (name/address/ZIP) should not be constrained by the default
         // "near downtown LA" radius used for discovery mode.
-        if !has_search_term {
-            if let (Some(latitude), Some(longitude), Some(radius_miles)) =
+        let mut radius_applied = false;
+        if !has_search_term
+            && let (Some(latitude), Some(longitude), Some(radius_miles)) =
                 (query.latitude, query.longitude, query.radius_miles)
-            {
-                facilities.retain(|facility| {
-                    haversine_miles(latitude, longitude, facility.latitude, facility.longitude)
+        {
+            // City and jurisdiction fallback points say nothing about distance, so
+            // they are left out rather than clustered around a centroid.
+            facilities.retain(|facility| {
+                !facility.location_precision.is_placeholder()
+                    && haversine_miles(latitude, longitude, facility.latitude, facility.longitude)
                         <= radius_miles.max(0.1)
-                });
-            }
+            });
+            radius_applied = true;
         }
 
         if query.recent_only.unwrap_or(false) {
@@ -144,6 +167,8 @@ impl DirectoryService {
             .as_deref()
         {
             Some("recent_desc") => {
+                // `None` orders before any date, so facilities without a dated
+                // inspection sort last.
                 facilities.sort_by(|left, right| {
                     latest_inspection_at(right)
                         .cmp(&latest_inspection_at(left))
@@ -165,10 +190,15 @@ impl DirectoryService {
                             .then(right.updated_at.cmp(&left.updated_at))
                     });
                 } else {
+                    // Within a radius, ZIP centroids rank after facilities that are
+                    // located precisely.
                     facilities.sort_by(|left, right| {
-                        right
-                            .trust_score
-                            .cmp(&left.trust_score)
+                        let precise = |facility: &Facility| {
+                            radius_applied && facility.location_precision.is_precise()
+                        };
+                        precise(right)
+                            .cmp(&precise(left))
+                            .then(right.trust_score.cmp(&left.trust_score))
                             .then(right.updated_at.cmp(&left.updated_at))
                     });
                 }
@@ -226,13 +256,20 @@ impl DirectoryService {
         let inspections_count = facility.inspections.len();
         let vote_summaries = self
             .repository
-            .get_facility_vote_summaries(&[facility.id.clone()])
+            .get_facility_vote_summaries(std::slice::from_ref(&facility.id))
             .await?;
         let vote_summary = vote_summaries
             .get(&facility.id)
             .cloned()
             .unwrap_or_default();
 
+        let address_components = PostalAddress::from_parts(
+            &facility.address,
+            &facility.city,
+            &facility.state,
+            &facility.postal_code,
+        );
+
         Ok(Some(FacilityDetail {
             id: facility.id,
             source_id: facility.source_id,
@@ -241,18 +278,63 @@ impl DirectoryService {
             city: facility.city,
             state: facility.state,
             postal_code: facility.postal_code,
+            address_components,
             latitude: facility.latitude,
             longitude: facility.longitude,
+            location_precision: facility.location_precision,
             jurisdiction: facility.jurisdiction.label().to_string(),
             trust_score: facility.trust_score,
             inspections_count,
             latest_inspection_at,
+            inspections: facility.inspections,
             likes: vote_summary.likes,
             dislikes: vote_summary.dislikes,
             vote_score: vote_summary.score(),
         }))
     }
 
+    /// Source records resolved to the facility, or `None` when it is not published.
+    pub async fn sources(
+        &self,
+        id: &str,
+    ) -> Result<Option<Vec<SourceRecordLink>>, crate::domain::errors::RepositoryError> {
+        if self.repository.get_by_id(id).await?.is_none() {
+            return Ok(None);
+        }
+
+        self.repository.list_source_links(Some(id)).await.map(Some)
+    }
+
+    /// The published facilities as a LIVES batch ZIP, stamped with the last refresh time
+    /// and the sources behind them. The ZIP is built on a blocking thread.
+    pub async fn lives_export(&self) -> anyhow::Result<Vec<u8>> {
+        let facilities = self.repository.list().await?;
+        let snapshot_at = self
+            .repository
+            .get_system_ingestion_status()
+            .await?
+            .map_or_else(Utc::now, |status| status.last_refresh_at);
+        let ids = facilities
+            .iter()
+            .map(|facility| facility.id.as_str())
+            .collect::<HashSet<_>>();
+        let sources = self
+            .repository
+            .list_source_links(None)
+            .await?
+            .into_iter()
+            .filter(|link| ids.contains(link.canonical_id.as_str()))
+            .map(|link| link.source)
+            .collect::<BTreeSet<_>>();
+        let feed = LivesFeedInfo {
+            snapshot_at,
+            sources: sources.into_iter().collect(),
+        };
+
+        // Compressing the whole directory is CPU-bound, so it stays off the runtime.
+        tokio::task::spawn_blocking(move || write_lives_zip(&facilities, &feed)).await?
+    }
+
     pub async fn top_picks(
         &self,
         limit: usize,
@@ -320,6 +402,7 @@ fn to_summary(facility: Facility, vote_summary: FacilityVoteSummary) -> Facility
         postal_code: facility.postal_code,
         latitude: facility.latitude,
         longitude: facility.longitude,
+        location_precision: facility.location_precision,
         jurisdiction: facility.jurisdiction.label().to_string(),
         trust_score: facility.trust_score,
         latest_inspection_at,
@@ -329,11 +412,12 @@ fn to_summary(facility: Facility, vote_summary: FacilityVoteSummary) -> Facility
     }
 }
 
+/// Newest dated inspection; undated inspections never count as recent.
 fn latest_inspection_at(facility: &Facility) -> Option<DateTime<Utc>> {
     facility
         .inspections
         .iter()
-        .map(|inspection| inspection.inspected_at)
+        .filter_map(|inspection| inspection.inspected_at)
         .max()
 }
 
@@ -415,7 +499,121 @@ fn haversine_miles(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{normalize_for_search, token_match_score};
+    use std::sync::Arc;
+
+    use chrono::{Duration, Utc};
+
+    use super::{DirectoryService, normalize_for_search, token_match_score};
+    use crate::{
+        application::dto::FacilitySearchQuery,
+        domain::{
+            entities::{Facility, Inspection, LocationPrecision},
+            repositories::FacilityRepository,
+        },
+        infrastructure::repositories::InMemoryFacilityRepository,
+    };
+
+    fn facility(id: &str, trust_score: u8, location_precision: LocationPrecision) -> Facility {
+        Facility {
+            trust_score,
+            location_precision,
+            ..Facility::sample(id)
+        }
+    }
+
+    #[tokio::test]
+    async fn radius_search_skips_fallback_points_and_ranks_zip_centroids_last() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        repository
+            .replace_facilities(
+                &[],
+                vec![
+                    facility("zip", 95, LocationPrecision::ZipCentroid),
+                    facility("rooftop", 80, LocationPrecision::Rooftop),
+                    facility("fallback", 99, LocationPrecision::JurisdictionDefault),
+                ],
+            )
+            .await
+            .unwrap();
+        let service = DirectoryService::new(repository);
+
+        let result = service
+            .search(FacilitySearchQuery {
+                q: None,
+                latitude: Some(34.0522),
+                longitude: Some(-118.2437),
+                radius_miles: Some(2.0),
+                jurisdiction: None,
+                sort: None,
+                score_slice: None,
+                recent_only: None,
+                page: None,
+                page_size: None,
+                limit: None,
+            })
+            .await
+            .unwrap();
+
+        let ids = result
+            .data
+            .iter()
+            .map(|facility| facility.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["rooftop", "zip"]);
+    }
+
+    #[tokio::test]
+    async fn undated_inspections_are_never_recent_and_sort_last() {
+        let inspection = |days_ago: Option<i64>| Inspection {
+            inspection_id: format!("insp-{days_ago:?}"),
+            inspected_at: days_ago.map(|days| Utc::now() - Duration::days(days)),
+            inspected_at_precision: None,
+            inspection_type: None,
+            raw_score: Some(95.0),
+            letter_grade: Some("A".to_owned()),
+            placard_status: None,
+            violations: Vec::new(),
+        };
+        let mut undated = facility("undated", 99, LocationPrecision::Rooftop);
+        undated.inspections = vec![inspection(None)];
+        let mut stale = facility("stale", 80, LocationPrecision::Rooftop);
+        stale.inspections = vec![inspection(Some(400)), inspection(None)];
+        let mut fresh = facility("fresh", 70, LocationPrecision::Rooftop);
+        fresh.inspections = vec![inspection(Some(10))];
+
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        repository
+            .replace_facilities(&[], vec![undated, stale, fresh])
+            .await
+            .unwrap();
+        let service = DirectoryService::new(repository);
+        let search = |recent_only| FacilitySearchQuery {
+            q: None,
+            latitude: None,
+            longitude: None,
+            radius_miles: None,
+            jurisdiction: None,
+            sort: Some("recent_desc".to_owned()),
+            score_slice: None,
+            recent_only: Some(recent_only),
+            page: None,
+            page_size: None,
+            limit: None,
+        };
+
+        let all = service.search(search(false)).await.unwrap();
+        let ids = all
+            .data
+            .iter()
+            .map(|facility| facility.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["fresh", "stale", "undated"]);
+        assert!(all.data[2].latest_inspection_at.is_none());
+
+        let recent = service.search(search(true)).await.unwrap();
+        assert_eq!(recent.data.len(), 1);
+        assert_eq!(recent.data[0].id, "fresh");
+    }
 
     #[test]
     fn normalizes_apostrophes_and_punctuation() {
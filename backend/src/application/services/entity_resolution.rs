@@ -0,0 +1,530 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::{address::PostalAddress, entities::SourceRecordLink},
+};
+
+/// Weighted similarity two records in the same block need to be merged.
+const MATCH_THRESHOLD: f64 = 0.8;
+const NAME_WEIGHT: f64 = 0.6;
+const ADDRESS_WEIGHT: f64 = 0.4;
+/// Records with real coordinates farther apart than this are never merged.
+const MAX_MATCH_DISTANCE_METERS: f64 = 250.0;
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+const NAME_NOISE: &[&str] = &["the", "inc", "llc", "corp", "co", "ltd"];
+
+/// A fetched record and the connector that produced it.
+#[derive(Clone, Debug)]
+pub struct SourceRecord<'a> {
+    pub source: &'a str,
+    pub record: SourceFacilityInput,
+}
+
+/// Records resolved to one real-world facility, in fetch order.
+#[derive(Clone, Debug)]
+pub struct ResolvedFacility {
+    pub canonical_id: String,
+    pub records: Vec<SourceFacilityInput>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EntityResolution {
+    pub facilities: Vec<ResolvedFacility>,
+    /// Where every resolved source record now points.
+    pub links: Vec<SourceRecordLink>,
+}
+
+/// Groups records that describe the same facility, across sources and jurisdictions.
+///
+/// Records are blocked by ZIP and street number, then merged when their weighted
+/// name and address similarity clears `MATCH_THRESHOLD` and, if both have real
+/// coordinates, they are within `MAX_MATCH_DISTANCE_METERS`. Records lacking a ZIP or
+/// street number only merge on an identical normalized name and address.
+///
+/// `known` maps `(source, source_id)` to a previously assigned canonical ID; a group
+/// keeps the ID most of its records already map to, so facility IDs survive refreshes.
+/// `retained` holds the IDs of stored facilities this run keeps; a group only takes
+/// one by voting for it, so a derived ID never lands on another source's facility.
+pub fn resolve_entities(
+    records: Vec<SourceRecord>,
+    known: &HashMap<(String, String), String>,
+    retained: &HashSet<String>,
+    resolved_at: DateTime<Utc>,
+) -> EntityResolution {
+    let keys = records.iter().map(MatchKey::of).collect::<Vec<_>>();
+
+    let mut blocks: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, key) in keys.iter().enumerate() {
+        blocks.entry(key.block.clone()).or_default().push(index);
+    }
+
+    // Every record starts in its own group, numbered by its index. A merge keeps the
+    // lower number, so groups stay in the order of their first record.
+    let mut group_of = (0..records.len()).collect::<Vec<_>>();
+    let mut members = (0..records.len())
+        .map(|index| vec![index])
+        .collect::<Vec<_>>();
+
+    // Records sharing a source ID are one facility as far as the source is concerned,
+    // e.g. one record per inspection.
+    let mut first_by_source_id: HashMap<(&str, &str), usize> = HashMap::new();
+    for (index, source_record) in records.iter().enumerate() {
+        let key = (
+            source_record.source,
+            source_record.record.source_id.as_str(),
+        );
+        match first_by_source_id.get(&key) {
+            Some(&first) => merge_groups(&mut group_of, &mut members, first, index),
+            None => {
+                first_by_source_id.insert(key, index);
+            }
+        }
+    }
+
+    for block in blocks.values() {
+        for (position, &left) in block.iter().enumerate() {
+            for &right in &block[position + 1..] {
+                let (left_group, right_group) = (group_of[left], group_of[right]);
+                if left_group == right_group
+                    || !keys[left].matches(&keys[right])
+                    || too_far_apart(&keys, &members[left_group], &members[right_group])
+                {
+                    continue;
+                }
+
+                merge_groups(&mut group_of, &mut members, left, right);
+            }
+        }
+    }
+    let groups = members
+        .into_iter()
+        .filter(|group| !group.is_empty())
+        .map(|mut group| {
+            group.sort_unstable();
+            group
+        })
+        .collect::<Vec<_>>();
+
+    let canonical_ids = assign_canonical_ids(&records, &groups, known, retained);
+
+    let mut records = records.into_iter().map(Some).collect::<Vec<_>>();
+    let mut resolution = EntityResolution::default();
+    for (members, canonical_id) in groups.into_iter().zip(canonical_ids) {
+        let mut group_records = Vec::with_capacity(members.len());
+        let mut linked = HashSet::new();
+        for index in members {
+            let SourceRecord { source, record } = records[index]
+                .take()
+                .expect("each record belongs to one group");
+            if linked.insert((source, record.source_id.clone())) {
+                resolution.links.push(SourceRecordLink {
+                    source: source.to_owned(),
+                    source_id: record.source_id.clone(),
+                    canonical_id: canonical_id.clone(),
+                    jurisdiction: record.jurisdiction.clone(),
+                    source_file: record.source_file.clone(),
+                    resolved_at,
+                });
+            }
+            group_records.push(record);
+        }
+        resolution.facilities.push(ResolvedFacility {
+            canonical_id,
+            records: group_records,
+        });
+    }
+
+    resolution
+}
+
+/// Picks each group's ID. Groups first claim the known ID most of their records map
+/// to, largest groups first; groups left without one derive an ID from their newest
+/// record, as facility IDs were derived before resolution existed. Retained IDs no
+/// record votes for are claimed up front, so derivation falls back to a source-scoped
+/// ID rather than reuse one.
+fn assign_canonical_ids(
+    records: &[SourceRecord],
+    groups: &[Vec<usize>],
+    known: &HashMap<(String, String), String>,
+    retained: &HashSet<String>,
+) -> Vec<String> {
+    let mut order = (0..groups.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&group| std::cmp::Reverse(groups[group].len()));
+
+    let voted = records
+        .iter()
+        .filter_map(|record| {
+            known.get(&(record.source.to_owned(), record.record.source_id.clone()))
+        })
+        .collect::<HashSet<_>>();
+    let mut claimed = retained
+        .iter()
+        .filter(|id| !voted.contains(id))
+        .cloned()
+        .collect::<HashSet<_>>();
+    let mut ids: Vec<Option<String>> = vec![None; groups.len()];
+    for &group in &order {
+        let mut votes: Vec<(&String, usize)> = Vec::new();
+        for &index in &groups[group] {
+            let record = &records[index];
+            let key = (record.source.to_owned(), record.record.source_id.clone());
+            if let Some(id) = known.get(&key) {
+                match votes.iter_mut().find(|(candidate, _)| *candidate == id) {
+                    Some((_, count)) => *count += 1,
+                    None => votes.push((id, 1)),
+                }
+            }
+        }
+        // Stable sort: ties go to the ID seen first.
+        votes.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        if let Some(id) = votes
+            .into_iter()
+            .map(|(id, _)| id)
+            .find(|id| !claimed.contains(*id))
+        {
+            claimed.insert(id.clone());
+            ids[group] = Some(id.clone());
+        }
+    }
+
+    for group in order {
+        if ids[group].is_some() {
+            continue;
+        }
+
+        let newest = groups[group]
+            .iter()
+            .map(|&index| &records[index])
+            .reduce(|newest, record| {
+                if record.record.inspected_at > newest.record.inspected_at {
+                    record
+                } else {
+                    newest
+                }
+            })
+            .expect("resolved groups are never empty");
+        let jurisdiction = newest.record.jurisdiction.code();
+        let mut id = format!("{jurisdiction}::{}", newest.record.source_id);
+        if claimed.contains(&id) {
+            id = format!(
+                "{jurisdiction}::{}::{}",
+                newest.source, newest.record.source_id
+            );
+        }
+
+        claimed.insert(id.clone());
+        ids[group] = Some(id);
+    }
+
+    ids.into_iter()
+        .map(|id| id.expect("every group is assigned an ID"))
+        .collect()
+}
+
+/// The normalized fields a record is blocked and compared on.
+struct MatchKey {
+    block: String,
+    name: String,
+    address: String,
+    coordinates: Option<(f64, f64)>,
+}
+
+impl MatchKey {
+    fn of(source_record: &SourceRecord) -> Self {
+        let record = &source_record.record;
+        let name = normalize_name(&record.name);
+        let postal_address = PostalAddress::from_parts(
+            &record.address,
+            &record.city,
+            &record.state,
+            &record.postal_code,
+        );
+        let address = postal_address.street_line().to_lowercase();
+        let street_number = postal_address
+            .number
+            .as_deref()
+            .unwrap_or_default()
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+
+        let block = match postal_address.zip.as_deref() {
+            Some(zip) if !street_number.is_empty() => format!("{zip}|{street_number}"),
+            _ => format!(
+                "exact|{name}|{address}|{}",
+                postal_address.city.as_deref().unwrap_or_default()
+            ),
+        };
+
+        let coordinates_real = record.location_precision.is_precise()
+            && record.latitude.is_finite()
+            && record.longitude.is_finite()
+            && !(record.latitude == 0.0 && record.longitude == 0.0);
+
+        Self {
+            block,
+            name,
+            address,
+            coordinates: coordinates_real.then_some((record.latitude, record.longitude)),
+        }
+    }
+
+    /// Name and address similarity only; distance is checked per group.
+    fn matches(&self, other: &Self) -> bool {
+        let score = NAME_WEIGHT * similarity(&self.name, &other.name)
+            + ADDRESS_WEIGHT * similarity(&self.address, &other.address);
+        score >= MATCH_THRESHOLD
+    }
+}
+
+/// Lowercases, drops apostrophes ("Joe's" -> "joes"), and turns any other
+/// punctuation into word breaks.
+fn normalize_text(value: &str) -> String {
+    value
+        .to_lowercase()
+        .replace(['\'', '\u{2019}'], "")
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize_name(name: &str) -> String {
+    normalize_text(name)
+        .split(' ')
+        .filter(|token| !NAME_NOISE.contains(token))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Sørensen-Dice coefficient over character bigrams, in `0.0..=1.0`.
+fn similarity(left: &str, right: &str) -> f64 {
+    if left == right {
+        return 1.0;
+    }
+
+    let bigrams = |value: &str| {
+        let characters = value.chars().collect::<Vec<_>>();
+        characters
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect::<Vec<_>>()
+    };
+    let left = bigrams(left);
+    let mut right = bigrams(right);
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+
+    let total = left.len() + right.len();
+    let mut shared = 0usize;
+    for bigram in left {
+        if let Some(position) = right.iter().position(|candidate| *candidate == bigram) {
+            right.swap_remove(position);
+            shared += 1;
+        }
+    }
+
+    (2 * shared) as f64 / total as f64
+}
+
+fn distance_meters(left: (f64, f64), right: (f64, f64)) -> f64 {
+    let (left_lat, left_lon) = (left.0.to_radians(), left.1.to_radians());
+    let (right_lat, right_lon) = (right.0.to_radians(), right.1.to_radians());
+    let half_chord = ((right_lat - left_lat) / 2.0).sin().powi(2)
+        + left_lat.cos() * right_lat.cos() * ((right_lon - left_lon) / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_METERS * half_chord.sqrt().asin()
+}
+
+/// Moves the group holding `right` into the group holding `left`, keeping the lower
+/// group number.
+fn merge_groups(group_of: &mut [usize], members: &mut [Vec<usize>], left: usize, right: usize) {
+    let (left_group, right_group) = (group_of[left], group_of[right]);
+    if left_group == right_group {
+        return;
+    }
+
+    let (kept, merged) = (left_group.min(right_group), left_group.max(right_group));
+    let moved = std::mem::take(&mut members[merged]);
+    for &index in &moved {
+        group_of[index] = kept;
+    }
+    members[kept].extend(moved);
+}
+
+/// Whether merging two groups would join records with real coordinates too far
+/// apart, e.g. two branches linked only through a record without coordinates.
+fn too_far_apart(keys: &[MatchKey], left: &[usize], right: &[usize]) -> bool {
+    left.iter().any(|&left| {
+        right.iter().any(|&right| {
+            matches!(
+                (keys[left].coordinates, keys[right].coordinates),
+                (Some(a), Some(b)) if distance_meters(a, b) > MAX_MATCH_DISTANCE_METERS
+            )
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use chrono::Utc;
+
+    use super::{SourceRecord, resolve_entities};
+    use crate::{
+        application::dto::SourceFacilityInput,
+        domain::entities::{Jurisdiction, LocationPrecision},
+    };
+
+    fn record(
+        source: &'static str,
+        source_id: &str,
+        name: &str,
+        address: &str,
+        coordinates: (f64, f64),
+    ) -> SourceRecord<'static> {
+        SourceRecord {
+            source,
+            record: SourceFacilityInput {
+                address: address.to_owned(),
+                city: "Los Angeles".to_owned(),
+                postal_code: "90012-1234".to_owned(),
+                latitude: coordinates.0,
+                longitude: coordinates.1,
+                jurisdiction: Jurisdiction::LosAngelesCounty,
+                ..SourceFacilityInput::sample(source_id, name)
+            },
+        }
+    }
+
+    fn grouped(resolution: &super::EntityResolution) -> Vec<Vec<&str>> {
+        resolution
+            .facilities
+            .iter()
+            .map(|facility| {
+                facility
+                    .records
+                    .iter()
+                    .map(|record| record.source_id.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_spelling_variants_within_a_block() {
+        let here = (34.0500, -118.2400);
+        let mut defaulted = record("lives", "4", "Joes Pizza 2", "123 Main St", (0.0, 0.0));
+        defaulted.record.location_precision = LocationPrecision::JurisdictionDefault;
+        let mut bundled = record(
+            "cpra",
+            "2",
+            "JOES PIZZA 2",
+            "123 Main Street",
+            (34.0501, -118.2401),
+        );
+        bundled.record.source_file = Some("2024/closures.csv".to_owned());
+        let records = vec![
+            record("la", "1", "Joe's Pizza #2", "123 Main St", here),
+            bundled,
+            record("la", "3", "Sushi Go", "123 Main St. Suite B", here),
+            defaulted,
+            record("cpra", "5", "Joe's Pizza #2", "125 Main St", here),
+            record(
+                "cpra",
+                "6",
+                "Joe's Pizza #2",
+                "123 Main St",
+                (34.1000, -118.2400),
+            ),
+        ];
+
+        let resolution = resolve_entities(records, &HashMap::new(), &HashSet::new(), Utc::now());
+
+        assert_eq!(
+            grouped(&resolution),
+            vec![vec!["1", "2", "4"], vec!["3"], vec!["5"], vec!["6"]]
+        );
+        assert_eq!(resolution.facilities[0].canonical_id, "lac::1");
+        assert_eq!(resolution.links.len(), 6);
+        assert_eq!(
+            resolution.links[1].source_file.as_deref(),
+            Some("2024/closures.csv")
+        );
+    }
+
+    #[test]
+    fn keeps_known_canonical_ids_and_resolves_conflicts() {
+        let here = (34.0500, -118.2400);
+        let known = HashMap::from([
+            (("la".to_owned(), "1".to_owned()), "lac::legacy".to_owned()),
+            (
+                ("cpra".to_owned(), "2".to_owned()),
+                "lac::legacy".to_owned(),
+            ),
+            (("la".to_owned(), "3".to_owned()), "lac::legacy".to_owned()),
+        ]);
+        let records = vec![
+            record("la", "3", "Taco Stand", "9 Spring St", here),
+            record("la", "1", "Noodle Bar", "400 Hill St", here),
+            record("cpra", "2", "NOODLE BAR", "400 Hill Street", here),
+        ];
+
+        let resolution = resolve_entities(records, &known, &HashSet::new(), Utc::now());
+
+        let ids = resolution
+            .facilities
+            .iter()
+            .map(|facility| facility.canonical_id.as_str())
+            .collect::<Vec<_>>();
+        // The larger group keeps the shared ID; the split-off record gets its own.
+        assert_eq!(ids, vec!["lac::3", "lac::legacy"]);
+        let link = resolution
+            .links
+            .iter()
+            .find(|link| link.source == "cpra")
+            .unwrap();
+        assert_eq!(link.canonical_id, "lac::legacy");
+    }
+
+    #[test]
+    fn derived_ids_skip_retained_ids_no_record_votes_for() {
+        let records = vec![
+            record(
+                "lb_permits",
+                "7",
+                "Harbor Grill",
+                "300 Ocean Blvd",
+                (33.77, -118.19),
+            ),
+            record(
+                "lb_grades",
+                "8",
+                "Pine Deli",
+                "1 Pine Ave",
+                (33.77, -118.19),
+            ),
+        ];
+        let known = HashMap::from([(
+            ("lb_grades".to_owned(), "8".to_owned()),
+            "lac::8".to_owned(),
+        )]);
+        let retained = HashSet::from(["lac::7".to_owned(), "lac::8".to_owned()]);
+
+        let resolution = resolve_entities(records, &known, &retained, Utc::now());
+
+        let ids = resolution
+            .facilities
+            .iter()
+            .map(|facility| facility.canonical_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["lac::lb_permits::7", "lac::8"]);
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::Utc;
+
+use crate::{
+    domain::{
+        address::PostalAddress,
+        entities::{Facility, GeocodeCacheEntry},
+        errors::RepositoryError,
+        repositories::FacilityRepository,
+    },
+    infrastructure::gazetteer::Gazetteer,
+};
+
+/// Outcome of one geocoding pass.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GeocodeStats {
+    /// Imprecisely located facilities with an address precise enough to look up.
+    pub candidates: usize,
+    /// Distinct addresses answered from the cache.
+    pub cache_hits: usize,
+    /// Distinct addresses looked up in the gazetteer.
+    pub looked_up: usize,
+    /// Facilities whose coordinates were replaced by a more precise point.
+    pub improved: usize,
+}
+
+/// Relocates facilities with fallback coordinates using the local gazetteer, caching
+/// every result by normalized address.
+pub struct GeocodingService {
+    repository: Arc<dyn FacilityRepository>,
+    gazetteer: Arc<Gazetteer>,
+}
+
+impl GeocodingService {
+    pub fn new(repository: Arc<dyn FacilityRepository>, gazetteer: Arc<Gazetteer>) -> Self {
+        Self {
+            repository,
+            gazetteer,
+        }
+    }
+
+    /// Geocodes every facility not already located precisely. A result only replaces
+    /// coordinates it is more precise than.
+    pub async fn geocode(
+        &self,
+        facilities: &mut [Facility],
+    ) -> Result<GeocodeStats, RepositoryError> {
+        let mut stats = GeocodeStats::default();
+        let mut addresses: HashMap<String, (PostalAddress, Vec<usize>)> = HashMap::new();
+        for (index, facility) in facilities.iter().enumerate() {
+            if facility.location_precision.is_precise() {
+                continue;
+            }
+
+            let address = PostalAddress::from_parts(
+                &facility.address,
+                &facility.city,
+                &facility.state,
+                &facility.postal_code,
+            );
+            let Some(key) = geocode_key(&address) else {
+                continue;
+            };
+            stats.candidates += 1;
+            addresses
+                .entry(key)
+                .or_insert_with(|| (address, Vec::new()))
+                .1
+                .push(index);
+        }
+        if addresses.is_empty() {
+            return Ok(stats);
+        }
+
+        let keys = addresses.keys().cloned().collect::<Vec<_>>();
+        let mut cached = self.repository.get_geocodes(&keys).await?;
+        let mut fresh = Vec::new();
+        for (key, (address, _)) in &addresses {
+            if cached
+                .get(key)
+                .is_some_and(|entry| entry.gazetteer == self.gazetteer.fingerprint())
+            {
+                stats.cache_hits += 1;
+                continue;
+            }
+
+            let found = self.gazetteer.lookup(address);
+            let entry = GeocodeCacheEntry {
+                address_key: key.clone(),
+                gazetteer: self.gazetteer.fingerprint().to_owned(),
+                quality: found.quality,
+                latitude: found.latitude,
+                longitude: found.longitude,
+                matched_address: found.matched_address,
+                geocoded_at: Utc::now(),
+            };
+            stats.looked_up += 1;
+            cached.insert(key.clone(), entry.clone());
+            fresh.push(entry);
+        }
+        if !fresh.is_empty() {
+            self.repository.upsert_geocodes(fresh).await?;
+        }
+
+        for (key, (_, indexes)) in addresses {
+            let Some(entry) = cached.get(&key) else {
+                continue;
+            };
+            let (Some(precision), Some(latitude), Some(longitude)) = (
+                entry.quality.location_precision(),
+                entry.latitude,
+                entry.longitude,
+            ) else {
+                continue;
+            };
+
+            for index in indexes {
+                let facility = &mut facilities[index];
+                if precision < facility.location_precision {
+                    facility.latitude = latitude;
+                    facility.longitude = longitude;
+                    facility.location_precision = precision;
+                    stats.improved += 1;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// `<number> <street>|<ZIP or city>`, without the unit; `None` when the address is too
+/// vague to place on a street.
+fn geocode_key(address: &PostalAddress) -> Option<String> {
+    let number = address.number.as_ref()?;
+    let street = address.street_key()?;
+    let locality = address.zip.as_ref().or(address.city.as_ref())?;
+
+    Some(format!("{number} {street}|{locality}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, sync::Arc};
+
+    use super::GeocodingService;
+    use crate::{
+        domain::{
+            entities::{Facility, GeocodeMatch, Jurisdiction, LocationPrecision},
+            repositories::FacilityRepository,
+        },
+        infrastructure::{gazetteer::Gazetteer, repositories::InMemoryFacilityRepository},
+    };
+
+    fn facility(id: &str, address: &str, location_precision: LocationPrecision) -> Facility {
+        Facility {
+            address: address.to_owned(),
+            city: "San Diego".to_owned(),
+            postal_code: "92101".to_owned(),
+            latitude: 32.7157,
+            longitude: -117.1611,
+            location_precision,
+            jurisdiction: Jurisdiction::SanDiegoCounty,
+            ..Facility::sample(id)
+        }
+    }
+
+    #[tokio::test]
+    async fn geocodes_fallback_points_and_caches_results() {
+        let path = std::env::temp_dir().join(format!(
+            "cleanplated-gazetteer-{}.csv",
+            uuid::Uuid::new_v4()
+        ));
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "LON,LAT,NUMBER,STREET,CITY,POSTCODE").unwrap();
+        writeln!(file, "-117.1600,32.7100,500,Broadway,San Diego,92101").unwrap();
+        drop(file);
+        let gazetteer = Arc::new(Gazetteer::load(std::slice::from_ref(&path)).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let service = GeocodingService::new(repository.clone(), gazetteer);
+        let mut facilities = vec![
+            facility("found", "500 Broadway", LocationPrecision::CityCentroid),
+            facility(
+                "zip-only",
+                "9 Nowhere Ln",
+                LocationPrecision::JurisdictionDefault,
+            ),
+            facility("kept", "500 Broadway", LocationPrecision::Rooftop),
+        ];
+
+        let stats = service.geocode(&mut facilities).await.unwrap();
+        assert_eq!(
+            (stats.candidates, stats.looked_up, stats.improved),
+            (2, 2, 2)
+        );
+        assert_eq!(facilities[0].location_precision, LocationPrecision::Rooftop);
+        assert_eq!(facilities[0].longitude, -117.16);
+        assert_eq!(
+            facilities[1].location_precision,
+            LocationPrecision::ZipCentroid
+        );
+        assert_eq!(facilities[2].longitude, -117.1611);
+
+        let cached = repository
+            .get_geocodes(&["500 BROADWAY|92101".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(cached["500 BROADWAY|92101"].quality, GeocodeMatch::Exact);
+
+        let mut again = vec![facility(
+            "found",
+            "500 Broadway",
+            LocationPrecision::CityCentroid,
+        )];
+        let stats = service.geocode(&mut again).await.unwrap();
+        assert_eq!(
+            (stats.cache_hits, stats.looked_up, stats.improved),
+            (1, 0, 1)
+        );
+    }
+}
@@ -1,37 +1,89 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use chrono::Utc;
-use tokio::sync::Mutex;
-use tokio::time::{Duration, sleep};
+use chrono::{DateTime, Utc};
+use tokio::sync::{Mutex, Semaphore};
+use tokio::time::{Duration, timeout};
 use tracing::{info, warn};
+use uuid::Uuid;
 
 use crate::{
     application::{
         dto::SourceFacilityInput,
-        services::{ScoreSignals, TrustScoreService},
+        services::{
+            CircuitBreakerPolicy, CircuitDecision, FacilitySnapshot, GeocodingService,
+            ScoreSignals, SourceRecord, TrustScoreService, ValidationReport, WebhookService,
+            detect_changes, resolve_entities, validate_records,
+        },
     },
     domain::{
-        entities::{ConnectorIngestionStatus, Facility, Inspection, SystemIngestionStatus},
-        repositories::FacilityRepository,
+        address::PostalAddress,
+        entities::{
+            CircuitState, ConnectorCircuit, ConnectorIngestionStatus, ConnectorSyncState,
+            DeadLetterRecord, Facility, FacilityEvent, IngestionRun, IngestionRunOutcome,
+            IngestionTrigger, Inspection, Jurisdiction, RecordDisposition, SystemIngestionStatus,
+        },
+        errors::RepositoryError,
+        repositories::{DeadLetterQuery, FacilityEventQuery, FacilityRepository},
+    },
+    infrastructure::{
+        archive::{ArchiveRun, PayloadArchive, ReplaySnapshot},
+        connectors::{FetchContext, HealthDataConnector, SOURCE_TIME_ZONE},
     },
-    infrastructure::connectors::HealthDataConnector,
 };
 
+/// One connector's fetch result and the sync state to store after it.
+struct ConnectorFetch {
+    result: anyhow::Result<Vec<SourceFacilityInput>>,
+    sync_state: Option<ConnectorSyncState>,
+    /// Jurisdictions a successful fetch could not cover, with their errors.
+    failed_jurisdictions: Vec<(Jurisdiction, String)>,
+}
+
+/// Cap on dead-lettered records kept per source, so a feed that repairs every row
+/// does not flood the store. Validation counts stay exact.
+const MAX_DEAD_LETTERS_PER_SOURCE: usize = 5_000;
+
 #[derive(Clone)]
 pub struct IngestionService {
     repository: Arc<dyn FacilityRepository>,
     trust_score_service: Arc<TrustScoreService>,
     connectors: Vec<Arc<dyn HealthDataConnector>>,
+    archive: Option<Arc<PayloadArchive>>,
+    fetch_limits: ConnectorFetchLimits,
+    circuit_policy: CircuitBreakerPolicy,
+    webhooks: Option<Arc<WebhookService>>,
+    geocoding: Option<Arc<GeocodingService>>,
     refresh_lock: Arc<Mutex<()>>,
 }
 
-const CONNECTOR_MAX_ATTEMPTS: usize = 3;
+/// Bounds on concurrent connector fetching during a refresh.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectorFetchLimits {
+    /// Maximum number of connectors fetching at the same time.
+    pub parallelism: usize,
+    /// Wall-clock budget for one connector, page retries included, counted from the
+    /// moment it starts fetching.
+    pub connector_budget: Duration,
+}
+
+impl Default for ConnectorFetchLimits {
+    fn default() -> Self {
+        Self {
+            parallelism: 4,
+            connector_budget: Duration::from_secs(900),
+        }
+    }
+}
 
 #[derive(Clone, Debug, serde::Serialize, Default)]
 pub struct IngestionStats {
     pub last_refresh_at: Option<chrono::DateTime<Utc>>,
     pub unique_facilities: usize,
     pub connector_stats: Vec<ConnectorIngestionStatus>,
+    pub circuits: Vec<ConnectorCircuit>,
 }
 
 impl IngestionService {
@@ -39,31 +91,177 @@ impl IngestionService {
         repository: Arc<dyn FacilityRepository>,
         trust_score_service: Arc<TrustScoreService>,
         connectors: Vec<Arc<dyn HealthDataConnector>>,
+        archive: Option<Arc<PayloadArchive>>,
+        fetch_limits: ConnectorFetchLimits,
+        circuit_policy: CircuitBreakerPolicy,
+        webhooks: Option<Arc<WebhookService>>,
     ) -> Self {
         Self {
             repository,
             trust_score_service,
             connectors,
+            archive,
+            fetch_limits,
+            circuit_policy,
+            webhooks,
+            geocoding: None,
             refresh_lock: Arc::new(Mutex::new(())),
         }
     }
 
+    /// Geocodes imprecisely located facilities before each publish.
+    pub fn with_geocoding(mut self, geocoding: Arc<GeocodingService>) -> Self {
+        self.geocoding = Some(geocoding);
+        self
+    }
+
     pub async fn stats(&self) -> IngestionStats {
+        let circuits = self
+            .repository
+            .list_connector_circuits()
+            .await
+            .unwrap_or_default();
+
         match self.repository.get_system_ingestion_status().await {
             Ok(Some(status)) => IngestionStats {
                 last_refresh_at: Some(status.last_refresh_at),
                 unique_facilities: status.unique_facilities,
                 connector_stats: status.connector_stats,
+                circuits,
+            },
+            Ok(None) | Err(_) => IngestionStats {
+                circuits,
+                ..IngestionStats::default()
             },
-            Ok(None) | Err(_) => IngestionStats::default(),
         }
     }
 
-    pub async fn refresh(&self) -> anyhow::Result<()> {
+    pub async fn runs(&self, limit: usize) -> Result<Vec<IngestionRun>, RepositoryError> {
+        self.repository.list_ingestion_runs(limit).await
+    }
+
+    /// Marks runs left `running` by a process that exited mid-run as abandoned. Called
+    /// at startup; a run still going in another process records its real outcome over
+    /// this when it finishes.
+    pub async fn abandon_interrupted_runs(&self) {
+        match self
+            .repository
+            .abandon_running_ingestion_runs(Utc::now(), "process exited before the run finished")
+            .await
+        {
+            Ok(0) => {}
+            Ok(abandoned) => warn!(abandoned, "Marked interrupted ingestion runs as abandoned"),
+            Err(error) => warn!(%error, "Unable to mark interrupted ingestion runs as abandoned"),
+        }
+    }
+
+    pub async fn dead_letters(
+        &self,
+        query: &DeadLetterQuery,
+    ) -> Result<Vec<DeadLetterRecord>, RepositoryError> {
+        self.repository.list_dead_letters(query).await
+    }
+
+    pub async fn events(
+        &self,
+        query: &FacilityEventQuery,
+    ) -> Result<Vec<FacilityEvent>, RepositoryError> {
+        self.repository.list_facility_events(query).await
+    }
+
+    pub async fn refresh(&self, trigger: IngestionTrigger) -> anyhow::Result<()> {
+        self.execute(trigger, None).await
+    }
+
+    /// Re-runs normalize, dedupe, and scoring over the payloads an earlier run
+    /// archived. Connectors read archived pages instead of calling their sources.
+    pub async fn replay(&self, run_id: &str) -> anyhow::Result<()> {
+        let archive = self.archive.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("replay requires a payload archive (CLEANPLATED_ARCHIVE_DIR)")
+        })?;
+        let snapshot = Arc::new(archive.load_snapshot(run_id).await?);
+
+        self.execute(IngestionTrigger::Replay, Some(snapshot)).await
+    }
+
+    async fn execute(
+        &self,
+        trigger: IngestionTrigger,
+        replay: Option<Arc<ReplaySnapshot>>,
+    ) -> anyhow::Result<()> {
         let _guard = self.refresh_lock.lock().await;
-        let mut stitched: HashMap<String, SourceFacilityInput> = HashMap::new();
-        let mut connector_stats = Vec::new();
-        let mut successful_connectors = 0usize;
+        let mut run = IngestionRun {
+            id: Uuid::new_v4().to_string(),
+            trigger,
+            started_at: Utc::now(),
+            finished_at: None,
+            outcome: IngestionRunOutcome::Running,
+            unique_facilities: None,
+            connector_stats: Vec::new(),
+            message: None,
+        };
+        self.record_run(&run).await;
+
+        // Replays never archive: their pages already live in the source run.
+        let archive_run = match self.archive.as_ref().filter(|_| replay.is_none()) {
+            Some(archive) => {
+                if let Err(error) = archive.apply_retention().await {
+                    warn!(error = %format!("{error:#}"), "Payload archive retention failed");
+                }
+                Some(archive.start_run(&run.id))
+            }
+            None => None,
+        };
+
+        let result = self
+            .run_refresh(&mut run, archive_run.as_ref(), replay.as_ref())
+            .await;
+        run.finished_at = Some(Utc::now());
+        match &result {
+            Ok(()) => {
+                run.outcome = IngestionRunOutcome::Published;
+                run.message = replay
+                    .as_ref()
+                    .map(|snapshot| format!("replayed archive run {}", snapshot.run_id()));
+            }
+            Err(error) => {
+                if run.outcome == IngestionRunOutcome::Running {
+                    run.outcome = IngestionRunOutcome::Failed;
+                }
+                run.message = Some(format!("{error:#}"));
+            }
+        }
+        self.record_run(&run).await;
+
+        if let Some(archive_run) = archive_run
+            && let Err(error) = archive_run.finish(&run).await
+        {
+            warn!(
+                run_id = %run.id,
+                error = %format!("{error:#}"),
+                "Unable to write payload archive manifest"
+            );
+        }
+
+        result
+    }
+
+    async fn record_run(&self, run: &IngestionRun) {
+        if let Err(error) = self.repository.record_ingestion_run(run.clone()).await {
+            warn!(run_id = %run.id, %error, "Unable to record ingestion run");
+        }
+    }
+
+    async fn run_refresh(
+        &self,
+        run: &mut IngestionRun,
+        archive_run: Option<&ArchiveRun>,
+        replay: Option<&Arc<ReplaySnapshot>>,
+    ) -> anyhow::Result<()> {
+        let mut stitched: Vec<SourceRecord> = Vec::new();
+        let connector_stats = &mut run.connector_stats;
+        // Each succeeded source with the jurisdictions its fetch could not cover.
+        let mut succeeded_sources: Vec<(&str, Vec<Jurisdiction>)> = Vec::new();
         let previous_status = self
             .repository
             .get_system_ingestion_status()
@@ -71,32 +269,159 @@ impl IngestionService {
             .ok()
             .flatten();
 
+        // Replays never touch the network, so they neither consult nor update circuits.
+        let mut circuits = match replay {
+            Some(_) => None,
+            None => Some(self.load_circuits().await),
+        };
+        // Connectors with a refresh interval wait that long after their last success;
+        // replays fetch whatever the archived run fetched.
+        let now = Utc::now();
+        let mut skipped = Vec::with_capacity(self.connectors.len());
+        let mut deferred = Vec::with_capacity(self.connectors.len());
         for connector in &self.connectors {
-            match self.fetch_with_retry(connector.as_ref()).await {
+            let due_at = connector
+                .refresh_interval()
+                .filter(|_| replay.is_none())
+                .and_then(|interval| chrono::Duration::from_std(interval).ok())
+                .zip(previous_success_at(
+                    previous_status.as_ref(),
+                    connector.source_name(),
+                ))
+                .map(|(interval, last_success_at)| last_success_at + interval)
+                .filter(|due_at| *due_at > now);
+            let reason = match (due_at, circuits.as_mut()) {
+                (Some(due_at), _) => Some(format!("not due until {}", due_at.to_rfc3339())),
+                (None, Some(circuits)) => self.admit(connector.source_name(), circuits).await,
+                (None, None) => None,
+            };
+            skipped.push(reason);
+            deferred.push(due_at.is_some());
+        }
+
+        let fetched = self.fetch_all(archive_run, replay, &skipped).await;
+        for (
+            (
+                (
+                    connector,
+                    ConnectorFetch {
+                        result,
+                        sync_state,
+                        failed_jurisdictions,
+                    },
+                ),
+                skip_reason,
+            ),
+            is_deferred,
+        ) in self
+            .connectors
+            .iter()
+            .zip(fetched)
+            .zip(&skipped)
+            .zip(&deferred)
+        {
+            // A connector that is not due keeps its status. It did not succeed this run,
+            // so its facilities are retained even beside a connector that refreshed
+            // the same jurisdiction.
+            if *is_deferred {
+                if let Some(entry) = previous_status.as_ref().and_then(|status| {
+                    status
+                        .connector_stats
+                        .iter()
+                        .find(|entry| entry.source == connector.source_name())
+                }) {
+                    connector_stats.push(entry.clone());
+                }
+                info!(
+                    source = connector.source_name(),
+                    reason = skip_reason.as_deref().unwrap_or_default(),
+                    "Connector not due; keeping its previously published facilities"
+                );
+                continue;
+            }
+
+            if let Some(circuit) = circuits
+                .as_mut()
+                .and_then(|circuits| circuits.get_mut(connector.source_name()))
+                .filter(|_| skip_reason.is_none())
+            {
+                match &result {
+                    Ok(_) => self.circuit_policy.record_success(circuit),
+                    Err(error) => {
+                        self.circuit_policy.record_failure(
+                            circuit,
+                            &format!("{error:#}"),
+                            Utc::now(),
+                        );
+                        if circuit.state == CircuitState::Open {
+                            warn!(
+                                source = connector.source_name(),
+                                consecutive_failures = circuit.consecutive_failures,
+                                "Connector circuit opened; skipping it until the cooldown elapses"
+                            );
+                        }
+                    }
+                }
+                self.save_circuit(circuit).await;
+            }
+
+            // The sync state mirrors the source, so it stays valid even if this run
+            // is not published.
+            if let Some(state) = sync_state
+                && let Err(error) = self.repository.put_sync_state(state).await
+            {
+                warn!(source = connector.source_name(), %error, "Unable to store connector sync state");
+            }
+
+            match result {
                 Ok(records) => {
-                    successful_connectors += 1;
+                    for (jurisdiction, error) in &failed_jurisdictions {
+                        warn!(
+                            source = connector.source_name(),
+                            jurisdiction = jurisdiction.code(),
+                            %error,
+                            "Connector could not fetch a jurisdiction; keeping its previously published facilities"
+                        );
+                    }
+                    let partial_error = (!failed_jurisdictions.is_empty()).then(|| {
+                        failed_jurisdictions
+                            .iter()
+                            .map(|(jurisdiction, error)| {
+                                format!("{}: {error}", jurisdiction.code())
+                            })
+                            .collect::<Vec<_>>()
+                            .join("; ")
+                    });
+                    succeeded_sources.push((
+                        connector.source_name(),
+                        failed_jurisdictions
+                            .into_iter()
+                            .map(|(jurisdiction, _)| jurisdiction)
+                            .collect(),
+                    ));
+                    let fetched_records = records.len();
+                    let report = validate_records(records);
                     info!(
                         source = connector.source_name(),
-                        records = records.len(),
+                        records = fetched_records,
+                        repaired = report.counts.repaired,
+                        rejected = report.counts.rejected,
                         "Fetched inspection records"
                     );
+                    self.store_dead_letters(&run.id, connector.source_name(), &report)
+                        .await;
                     connector_stats.push(ConnectorIngestionStatus {
                         source: connector.source_name().to_owned(),
-                        fetched_records: records.len(),
-                        error: None,
+                        fetched_records,
+                        stale: partial_error.is_some(),
+                        error: partial_error,
+                        last_success_at: Some(Utc::now()),
+                        validation: report.counts,
                     });
-
-                    for record in records {
-                        let key = dedupe_key(&record);
-                        stitched
-                            .entry(key)
-                            .and_modify(|current| {
-                                if current.inspected_at < record.inspected_at {
-                                    *current = record.clone();
-                                }
-                            })
-                            .or_insert(record);
-                    }
+                    stitched.extend(report.accepted.into_iter().map(|record| SourceRecord {
+                        source: connector.source_name(),
+                        record,
+                    }));
                 }
                 Err(error) => {
                     let error_chain = format!("{error:#}");
@@ -104,17 +429,26 @@ impl IngestionService {
                         source: connector.source_name().to_owned(),
                         fetched_records: 0,
                         error: Some(error_chain.clone()),
+                        stale: true,
+                        last_success_at: previous_success_at(
+                            previous_status.as_ref(),
+                            connector.source_name(),
+                        ),
+                        validation: Default::default(),
                     });
                     warn!(
                         source = connector.source_name(),
                         error = %error_chain,
-                        "Connector fetch failed"
+                        "Connector fetch failed; keeping its previously published facilities"
                     );
                 }
             }
         }
 
-        if successful_connectors == 0 {
+        if succeeded_sources.is_empty() {
+            if deferred.iter().all(|is_deferred| *is_deferred) {
+                anyhow::bail!("no connectors are due yet; keeping previous dataset untouched");
+            }
             anyhow::bail!("no connectors succeeded; keeping previous dataset untouched");
         }
 
@@ -122,32 +456,134 @@ impl IngestionService {
             anyhow::bail!("ingestion produced zero facilities; keeping previous dataset untouched");
         }
 
+        let existing = self
+            .repository
+            .list()
+            .await
+            .map_err(|error| anyhow::anyhow!(error.to_string()))?;
+
+        let links = match self.repository.list_source_links(None).await {
+            Ok(links) => links,
+            Err(error) => {
+                warn!(%error, "Unable to load source record links; deriving facility IDs afresh");
+                Vec::new()
+            }
+        };
+        let mut known_links = HashMap::with_capacity(links.len());
+        let mut linked_sources: HashMap<String, Vec<String>> = HashMap::new();
+        for link in links {
+            let sources = linked_sources.entry(link.canonical_id.clone()).or_default();
+            if !sources.contains(&link.source) {
+                sources.push(link.source.clone());
+            }
+            known_links.insert((link.source, link.source_id), link.canonical_id);
+        }
+
+        // Only facilities whose every connector succeeded are replaced, so a failed,
+        // skipped or deferred connector's last good data stays published even when
+        // another connector serves the same jurisdiction.
+        let served = self
+            .connectors
+            .iter()
+            .map(|connector| (connector.source_name(), connector.jurisdictions()))
+            .collect::<Vec<_>>();
+        let mut replaced_ids = HashSet::new();
+        let mut retained_ids = HashSet::new();
+        // Carry stored inspection history forward so each facility accumulates a
+        // timeline across refreshes instead of only holding the latest visit.
+        let mut history = HashMap::with_capacity(existing.len());
+        let mut previous_facilities = HashMap::with_capacity(existing.len());
+        for facility in existing {
+            let sources = linked_sources
+                .get(&facility.id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if is_replaced(&facility, sources, &served, &succeeded_sources) {
+                replaced_ids.insert(facility.id.clone());
+            } else {
+                retained_ids.insert(facility.id.clone());
+            }
+            previous_facilities.insert(facility.id.clone(), FacilitySnapshot::of(&facility));
+            history.insert(facility.id, facility.inspections);
+        }
+        let resolution = resolve_entities(stitched, &known_links, &retained_ids, Utc::now());
+        let mut facilities = resolution
+            .facilities
+            .into_iter()
+            .map(|resolved| self.normalize(resolved.canonical_id, resolved.records, &mut history))
+            .collect::<Vec<_>>();
+
+        // Geocoding only refines coordinates, so a failure publishes the fallbacks.
+        if let Some(geocoding) = self.geocoding.as_ref() {
+            match geocoding.geocode(&mut facilities).await {
+                Ok(stats) => info!(
+                    candidates = stats.candidates,
+                    cache_hits = stats.cache_hits,
+                    looked_up = stats.looked_up,
+                    improved = stats.improved,
+                    "Geocoded imprecisely located facilities"
+                ),
+                Err(error) => warn!(run_id = %run.id, %error, "Geocoding failed"),
+            }
+        }
+
+        // A resolved facility only reuses a retained ID its records voted for, and then
+        // replaces that facility.
+        for facility in &facilities {
+            if retained_ids.remove(&facility.id) {
+                replaced_ids.insert(facility.id.clone());
+            }
+        }
+        let unique_facilities = facilities.len() + retained_ids.len();
+        run.unique_facilities = Some(unique_facilities);
+
         if let Some(previous) = previous_status {
             let minimum_safe_count = (previous.unique_facilities / 2).max(1);
-            if stitched.len() < minimum_safe_count {
+            if unique_facilities < minimum_safe_count {
+                run.outcome = IngestionRunOutcome::RejectedByShrinkGuard;
                 anyhow::bail!(
                     "ingestion result too small ({} < {}), keeping previous dataset untouched",
-                    stitched.len(),
+                    unique_facilities,
                     minimum_safe_count
                 );
             }
         }
 
-        let facilities = stitched
-            .into_values()
-            .map(|record| self.normalize(record))
-            .collect::<Vec<_>>();
-        let unique_facilities = facilities.len();
+        let events = detect_changes(
+            &run.id,
+            &previous_facilities,
+            &facilities,
+            &replaced_ids,
+            Utc::now(),
+        );
 
+        let removed_ids = replaced_ids.into_iter().collect::<Vec<_>>();
         self.repository
-            .replace_all(facilities)
+            .replace_facilities(&removed_ids, facilities)
             .await
             .map_err(|error| anyhow::anyhow!(error.to_string()))?;
 
+        // A missing link only costs ID stability on the next run, so it is not fatal.
+        if let Err(error) = self.repository.upsert_source_links(resolution.links).await {
+            warn!(run_id = %run.id, %error, "Unable to store source record links");
+        }
+
+        // Events describe what was published, so they are stored only once the
+        // dataset they were diffed from has replaced the previous one.
+        if !events.is_empty() {
+            info!(events = events.len(), "Detected facility changes");
+            match self.repository.append_facility_events(events.clone()).await {
+                Ok(()) => self.queue_webhooks(&run.id, &events).await,
+                Err(error) => {
+                    warn!(run_id = %run.id, %error, "Unable to store facility events");
+                }
+            }
+        }
+
         let snapshot = SystemIngestionStatus {
             last_refresh_at: Utc::now(),
             unique_facilities,
-            connector_stats,
+            connector_stats: run.connector_stats.clone(),
         };
 
         self.repository
@@ -163,74 +599,1412 @@ impl IngestionService {
         Ok(())
     }
 
-    async fn fetch_with_retry(
+    async fn queue_webhooks(&self, run_id: &str, events: &[FacilityEvent]) {
+        let Some(webhooks) = self.webhooks.as_ref() else {
+            return;
+        };
+
+        match webhooks.enqueue(events).await {
+            Ok(0) => {}
+            Ok(queued) => info!(run_id, deliveries = queued, "Queued webhook deliveries"),
+            Err(error) => warn!(run_id, %error, "Unable to queue webhook deliveries"),
+        }
+    }
+
+    /// Keeps the repaired and rejected records of a connector's latest successful
+    /// fetch, rejected first, up to `MAX_DEAD_LETTERS_PER_SOURCE`.
+    async fn store_dead_letters(&self, run_id: &str, source: &str, report: &ValidationReport) {
+        let recorded_at = Utc::now();
+        let mut flagged = report.flagged.iter().collect::<Vec<_>>();
+        flagged.sort_by_key(|flagged| flagged.disposition != RecordDisposition::Rejected);
+
+        let records = flagged
+            .into_iter()
+            .take(MAX_DEAD_LETTERS_PER_SOURCE)
+            .map(|flagged| DeadLetterRecord {
+                id: Uuid::new_v4().to_string(),
+                run_id: run_id.to_owned(),
+                source: source.to_owned(),
+                source_id: Some(flagged.record.source_id.clone())
+                    .filter(|source_id| !source_id.trim().is_empty()),
+                disposition: flagged.disposition,
+                reasons: flagged.reasons.clone(),
+                payload: serde_json::to_value(&flagged.record).unwrap_or(serde_json::Value::Null),
+                recorded_at,
+            })
+            .collect::<Vec<_>>();
+
+        if let Err(error) = self.repository.replace_dead_letters(source, records).await {
+            warn!(source, %error, "Unable to store dead-lettered records");
+        }
+    }
+
+    async fn load_circuits(&self) -> HashMap<String, ConnectorCircuit> {
+        match self.repository.list_connector_circuits().await {
+            Ok(circuits) => circuits
+                .into_iter()
+                .map(|circuit| (circuit.source.clone(), circuit))
+                .collect(),
+            Err(error) => {
+                warn!(%error, "Unable to load connector circuits; treating all as closed");
+                HashMap::new()
+            }
+        }
+    }
+
+    async fn save_circuit(&self, circuit: &ConnectorCircuit) {
+        if let Err(error) = self
+            .repository
+            .upsert_connector_circuit(circuit.clone())
+            .await
+        {
+            warn!(source = %circuit.source, %error, "Unable to save connector circuit");
+        }
+    }
+
+    /// Returns why `source` is skipped this run, or `None` when it should be called.
+    /// A connector whose cooldown has elapsed moves to half-open before its probe.
+    async fn admit(
         &self,
-        connector: &dyn HealthDataConnector,
-    ) -> anyhow::Result<Vec<SourceFacilityInput>> {
-        let mut attempt = 0usize;
-        loop {
-            attempt += 1;
-
-            match connector.fetch_facilities().await {
-                Ok(records) => return Ok(records),
-                Err(error) if attempt < CONNECTOR_MAX_ATTEMPTS => {
-                    let backoff_seconds = (attempt as u64) * 2;
-                    let error_chain = format!("{error:#}");
-                    warn!(
-                        source = connector.source_name(),
-                        attempt,
-                        error = %error_chain,
-                        "Connector fetch failed, retrying"
-                    );
-                    sleep(Duration::from_secs(backoff_seconds)).await;
+        source: &str,
+        circuits: &mut HashMap<String, ConnectorCircuit>,
+    ) -> Option<String> {
+        let circuit = circuits
+            .entry(source.to_owned())
+            .or_insert_with(|| ConnectorCircuit::closed(source));
+
+        match self.circuit_policy.decide(circuit, Utc::now()) {
+            CircuitDecision::Call => None,
+            CircuitDecision::Probe => {
+                info!(source, "Connector circuit cooldown elapsed; probing source");
+                if circuit.state != CircuitState::HalfOpen {
+                    circuit.state = CircuitState::HalfOpen;
+                    self.save_circuit(circuit).await;
                 }
-                Err(error) => {
-                    return Err(error);
+                None
+            }
+            CircuitDecision::Skip { retry_after } => Some(format!(
+                "circuit open after {} consecutive failures; next probe after {}",
+                circuit.consecutive_failures,
+                retry_after.to_rfc3339()
+            )),
+        }
+    }
+
+    /// Fetches connectors concurrently, at most `parallelism` at a time, and returns
+    /// their results in connector order so stitching never depends on which
+    /// connector finished first. Connectors with a `skipped` reason are not called.
+    async fn fetch_all(
+        &self,
+        archive_run: Option<&ArchiveRun>,
+        replay: Option<&Arc<ReplaySnapshot>>,
+        skipped: &[Option<String>],
+    ) -> Vec<ConnectorFetch> {
+        let permits = Arc::new(Semaphore::new(self.fetch_limits.parallelism.max(1)));
+        let budget = self.fetch_limits.connector_budget;
+
+        // Replays resume from the sync state archived with the run instead.
+        let mut stored_sync_states = Vec::with_capacity(self.connectors.len());
+        for (connector, skip_reason) in self.connectors.iter().zip(skipped) {
+            let stored = match (replay, skip_reason) {
+                (None, None) => self.load_sync_state(connector.source_name()).await,
+                _ => None,
+            };
+            stored_sync_states.push(stored);
+        }
+
+        let tasks = self
+            .connectors
+            .iter()
+            .zip(skipped)
+            .zip(stored_sync_states)
+            .map(|((connector, skip_reason), stored_sync_state)| {
+                if let Some(reason) = skip_reason {
+                    return Err(reason.clone());
                 }
+
+                let connector = connector.clone();
+                let permits = permits.clone();
+                let context = match replay {
+                    Some(snapshot) => {
+                        FetchContext::replay(connector.source_name(), snapshot.clone())
+                    }
+                    None => FetchContext::live(
+                        connector.source_name(),
+                        connector.retry_policy(),
+                        archive_run.cloned(),
+                    )
+                    .with_sync_state(stored_sync_state),
+                };
+
+                Ok(tokio::spawn(async move {
+                    let _permit = permits
+                        .acquire_owned()
+                        .await
+                        .expect("connector semaphore is never closed");
+                    let result = timeout(budget, connector.fetch_facilities(&context))
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(anyhow::anyhow!(
+                                "connector exceeded its {}s fetch budget",
+                                budget.as_secs()
+                            ))
+                        });
+                    let sync_state = match &result {
+                        Ok(_) => context.take_sync_state(),
+                        Err(_) => context.take_committed_sync_state(),
+                    };
+                    let failed_jurisdictions = context.take_failed_jurisdictions();
+                    ConnectorFetch {
+                        result,
+                        sync_state,
+                        failed_jurisdictions,
+                    }
+                }))
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let fetch = match task {
+                Ok(task) => task.await.unwrap_or_else(|error| ConnectorFetch {
+                    result: Err(anyhow::anyhow!("connector fetch task failed: {error}")),
+                    sync_state: None,
+                    failed_jurisdictions: Vec::new(),
+                }),
+                Err(reason) => ConnectorFetch {
+                    result: Err(anyhow::anyhow!(reason)),
+                    sync_state: None,
+                    failed_jurisdictions: Vec::new(),
+                },
+            };
+            results.push(fetch);
+        }
+
+        results
+    }
+
+    async fn load_sync_state(&self, source: &str) -> Option<ConnectorSyncState> {
+        match self.repository.get_sync_state(source).await {
+            Ok(state) => state,
+            Err(error) => {
+                warn!(source, %error, "Unable to load connector sync state; pulling in full");
+                None
             }
         }
     }
 
-    fn normalize(&self, record: SourceFacilityInput) -> Facility {
-        let trust_score = self.trust_score_service.score(&ScoreSignals {
-            raw_score: record.raw_score,
-            letter_grade: record.letter_grade.clone(),
-            placard_status: record.placard_status.clone(),
-        });
+    /// Builds one facility from every record resolved to `id`. The newest record
+    /// supplies identity and the newest record with a score, grade or placard supplies
+    /// scoring, so an unscored follow-up visit does not reset the facility's score.
+    /// Every record contributes an inspection that is merged into the stored history by
+    /// `inspection_id`. Coordinates come from the most precise record, so a fallback
+    /// point never hides another source's real one.
+    fn normalize(
+        &self,
+        id: String,
+        mut records: Vec<SourceFacilityInput>,
+        history: &mut HashMap<String, Vec<Inspection>>,
+    ) -> Facility {
+        records.sort_by_key(|record| std::cmp::Reverse(record.inspected_at));
+        let (latitude, longitude, location_precision) = records
+            .iter()
+            .min_by_key(|record| record.location_precision)
+            .map(|record| (record.latitude, record.longitude, record.location_precision))
+            .expect("stitched facility groups are never empty");
+        let signals = records
+            .iter()
+            .find(|record| {
+                record.raw_score.is_some()
+                    || record.letter_grade.is_some()
+                    || record.placard_status.is_some()
+            })
+            .map(|record| ScoreSignals {
+                raw_score: record.raw_score,
+                letter_grade: record.letter_grade.clone(),
+                placard_status: record.placard_status.clone(),
+            })
+            .unwrap_or_default();
+        let trust_score = self.trust_score_service.score(&signals);
 
-        let inspection = Inspection {
-            inspection_id: format!("{}-{}", record.jurisdiction.code(), record.source_id),
-            inspected_at: record.inspected_at,
-            raw_score: record.raw_score,
-            letter_grade: record.letter_grade,
-            placard_status: record.placard_status,
-            violations: record.violations,
-        };
+        let mut records = records.into_iter();
+        let record = records
+            .next()
+            .expect("stitched facility groups are never empty");
+
+        let fetched = std::iter::once(to_inspection(&record))
+            .chain(records.map(|older| to_inspection(&older)))
+            .collect::<Vec<_>>();
+        let inspections = merge_inspections(history.remove(&id).unwrap_or_default(), fetched);
+        let address = PostalAddress::from_parts(
+            &record.address,
+            &record.city,
+            &record.state,
+            &record.postal_code,
+        );
 
         Facility {
-            id: format!("{}::{}", record.jurisdiction.code(), record.source_id),
+            id,
             source_id: record.source_id,
             name: record.name,
-            address: record.address,
-            city: record.city,
-            state: record.state,
-            postal_code: record.postal_code,
-            latitude: record.latitude,
-            longitude: record.longitude,
+            address: address.display_street_line(),
+            city: address.display_city().unwrap_or(record.city),
+            state: address.state.clone().unwrap_or(record.state),
+            postal_code: address.postal_code().unwrap_or(record.postal_code),
+            latitude,
+            longitude,
+            location_precision,
             jurisdiction: record.jurisdiction,
             trust_score,
-            inspections: vec![inspection],
+            inspections,
             updated_at: Utc::now(),
         }
     }
 }
 
-fn dedupe_key(record: &SourceFacilityInput) -> String {
-    format!(
-        "{}|{}|{}|{}",
-        record.name.trim().to_ascii_lowercase(),
-        record.address.trim().to_ascii_lowercase(),
-        record.city.trim().to_ascii_lowercase(),
-        record.postal_code.trim().to_ascii_lowercase()
-    )
+fn previous_success_at(
+    previous_status: Option<&SystemIngestionStatus>,
+    source: &str,
+) -> Option<DateTime<Utc>> {
+    let status = previous_status?;
+    let entry = status
+        .connector_stats
+        .iter()
+        .find(|entry| entry.source == source)?;
+
+    entry
+        .last_success_at
+        .or_else(|| entry.error.is_none().then_some(status.last_refresh_at))
+}
+
+/// Whether this run replaces a published facility: every configured connector among
+/// its linked `sources` succeeded without failing the facility's jurisdiction. A facility with no link to a configured connector is
+/// attributed to every connector serving its jurisdiction.
+fn is_replaced(
+    facility: &Facility,
+    sources: &[String],
+    served: &[(&str, Vec<Jurisdiction>)],
+    succeeded: &[(&str, Vec<Jurisdiction>)],
+) -> bool {
+    let covered = |source: &str| {
+        succeeded.iter().any(|(succeeded, failed)| {
+            *succeeded == source && !failed.contains(&facility.jurisdiction)
+        })
+    };
+    let mut owners = served
+        .iter()
+        .filter(|(source, _)| sources.iter().any(|linked| linked == source))
+        .peekable();
+    if owners.peek().is_some() {
+        return owners.all(|(source, _)| covered(source));
+    }
+
+    let mut owners = served
+        .iter()
+        .filter(|(_, jurisdictions)| jurisdictions.contains(&facility.jurisdiction))
+        .peekable();
+    owners.peek().is_some() && owners.all(|(source, _)| covered(source))
+}
+
+fn to_inspection(record: &SourceFacilityInput) -> Inspection {
+    let inspection_id = match record.inspection_id.as_deref() {
+        Some(id) => format!("{}-{}", record.jurisdiction.code(), id),
+        None => format!(
+            "{}-{}-{}",
+            record.jurisdiction.code(),
+            record.source_id,
+            record.inspected_at.map_or_else(
+                || "undated".to_owned(),
+                |inspected_at| {
+                    inspected_at
+                        .with_timezone(&SOURCE_TIME_ZONE)
+                        .format("%Y%m%d")
+                        .to_string()
+                }
+            )
+        ),
+    };
+
+    Inspection {
+        inspection_id,
+        inspected_at: record.inspected_at,
+        inspected_at_precision: record.inspected_at_precision,
+        inspection_type: record.inspection_type.clone(),
+        raw_score: record.raw_score,
+        letter_grade: record.letter_grade.clone(),
+        placard_status: record.placard_status.clone(),
+        violations: record.violations.clone(),
+    }
+}
+
+/// Merges freshly fetched inspections into a stored timeline. Fetched entries
+/// replace stored ones with the same `inspection_id`; the result is newest first.
+fn merge_inspections(existing: Vec<Inspection>, fetched: Vec<Inspection>) -> Vec<Inspection> {
+    let mut by_id = existing
+        .into_iter()
+        .map(|inspection| (inspection.inspection_id.clone(), inspection))
+        .collect::<HashMap<_, _>>();
+    // Fetched inspections arrive newest first; insert in reverse so the newest
+    // copy of a repeated id wins.
+    for inspection in fetched.into_iter().rev() {
+        by_id.insert(inspection.inspection_id.clone(), inspection);
+    }
+
+    let mut merged = by_id.into_values().collect::<Vec<_>>();
+    merged.sort_by(|left, right| {
+        right
+            .inspected_at
+            .cmp(&left.inspected_at)
+            .then_with(|| left.inspection_id.cmp(&right.inspection_id))
+    });
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    };
+
+    use async_trait::async_trait;
+    use chrono::{TimeZone, Utc};
+    use tokio::time::{Duration, sleep};
+
+    use super::{ConnectorFetchLimits, IngestionService, merge_inspections};
+    use crate::{
+        application::{
+            dto::SourceFacilityInput,
+            services::{CircuitBreakerPolicy, TrustScoreService},
+        },
+        domain::{
+            entities::{
+                CircuitState, ConnectorSyncState, Facility, IngestionRun, IngestionRunOutcome,
+                IngestionTrigger, Inspection, Jurisdiction, RecordDisposition, ValidationReason,
+            },
+            repositories::{DeadLetterQuery, FacilityRepository},
+        },
+        infrastructure::{
+            connectors::{FetchContext, HealthDataConnector},
+            repositories::InMemoryFacilityRepository,
+        },
+    };
+
+    fn inspection(id: &str, day: u32, raw_score: f32) -> Inspection {
+        Inspection {
+            inspection_id: id.to_owned(),
+            inspected_at: Some(Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap()),
+            inspected_at_precision: None,
+            inspection_type: None,
+            raw_score: Some(raw_score),
+            letter_grade: None,
+            placard_status: None,
+            violations: Vec::new(),
+        }
+    }
+
+    struct DelayedConnector {
+        source: &'static str,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for DelayedConnector {
+        fn source_name(&self) -> &str {
+            self.source
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::LosAngelesCounty]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            _context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            sleep(self.delay).await;
+            Ok(vec![SourceFacilityInput {
+                city: "Los Angeles".to_owned(),
+                postal_code: "90012".to_owned(),
+                latitude: 34.05,
+                longitude: -118.24,
+                jurisdiction: Jurisdiction::LosAngelesCounty,
+                raw_score: Some(95.0),
+                letter_grade: Some("A".to_owned()),
+                ..SourceFacilityInput::sample(self.source, "Shared Taqueria")
+            }])
+        }
+    }
+
+    struct StaticConnector {
+        records: Vec<SourceFacilityInput>,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for StaticConnector {
+        fn source_name(&self) -> &str {
+            "static"
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::Pasadena]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            _context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            Ok(self.records.clone())
+        }
+    }
+
+    struct FailingConnector {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for FailingConnector {
+        fn source_name(&self) -> &str {
+            "failing"
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::OrangeCounty]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            _context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("portal unavailable")
+        }
+    }
+
+    /// Serves one Long Beach facility as `source` until `failing` is set.
+    struct SwitchableConnector {
+        source: &'static str,
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for SwitchableConnector {
+        fn source_name(&self) -> &str {
+            self.source
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::LongBeach]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            if self.failing.load(Ordering::SeqCst) {
+                anyhow::bail!("{} portal unavailable", self.source);
+            }
+            let mut records = DelayedConnector {
+                source: self.source,
+                delay: Duration::ZERO,
+            }
+            .fetch_facilities(context)
+            .await?;
+            for record in &mut records {
+                record.name = format!("{} Diner", self.source);
+                record.address = format!("{} Pine Ave", self.source.len());
+                record.city = "Long Beach".to_owned();
+                record.jurisdiction = Jurisdiction::LongBeach;
+            }
+            Ok(records)
+        }
+    }
+
+    /// Serves whatever Long Beach records the test last handed it.
+    struct MutableConnector {
+        source: &'static str,
+        records: std::sync::Mutex<Vec<SourceFacilityInput>>,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for MutableConnector {
+        fn source_name(&self) -> &str {
+            self.source
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::LongBeach]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            _context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            Ok(self.records.lock().unwrap().clone())
+        }
+    }
+
+    /// Serves one Pasadena and one Long Beach facility. While `long_beach_down` is set
+    /// it reports Long Beach as failed and renames the Pasadena one.
+    struct TwoJurisdictionConnector {
+        long_beach_down: AtomicBool,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for TwoJurisdictionConnector {
+        fn source_name(&self) -> &str {
+            "two_jurisdictions"
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::Pasadena, Jurisdiction::LongBeach]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            if self.long_beach_down.load(Ordering::SeqCst) {
+                context.fail_jurisdiction(
+                    Jurisdiction::LongBeach,
+                    &anyhow::anyhow!("long beach portal down"),
+                );
+                return Ok(vec![SourceFacilityInput::sample("pas-1", "Renamed Cafe")]);
+            }
+            Ok(vec![
+                SourceFacilityInput::sample("pas-1", "Pasadena Cafe"),
+                SourceFacilityInput {
+                    jurisdiction: Jurisdiction::LongBeach,
+                    ..SourceFacilityInput::sample("lb-1", "Harbor Grill")
+                },
+            ])
+        }
+    }
+
+    /// Serves `count` distinct Pasadena facilities.
+    struct CountedConnector {
+        count: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for CountedConnector {
+        fn source_name(&self) -> &str {
+            "counted"
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::Pasadena]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            _context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            Ok((0..self.count.load(Ordering::SeqCst))
+                .map(|index| SourceFacilityInput {
+                    address: format!("{index} Colorado Blvd"),
+                    ..SourceFacilityInput::sample(&index.to_string(), &format!("Cafe {index}"))
+                })
+                .collect())
+        }
+    }
+
+    /// Refetched at most weekly.
+    struct WeeklyConnector {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for WeeklyConnector {
+        fn source_name(&self) -> &str {
+            "weekly"
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::Pasadena]
+        }
+
+        fn refresh_interval(&self) -> Option<std::time::Duration> {
+            Some(std::time::Duration::from_secs(7 * 24 * 3_600))
+        }
+
+        async fn fetch_facilities(
+            &self,
+            context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let mut records = DelayedConnector {
+                source: "weekly",
+                delay: Duration::ZERO,
+            }
+            .fetch_facilities(context)
+            .await?;
+            for record in &mut records {
+                record.name = "Weekly Bakery".to_owned();
+                record.address = "9 Colorado Blvd".to_owned();
+                record.city = "Pasadena".to_owned();
+                record.jurisdiction = Jurisdiction::Pasadena;
+            }
+            Ok(records)
+        }
+    }
+
+    /// Counts its syncs in the watermark, resuming from whatever state was stored.
+    struct SyncingConnector {
+        resumed_from: std::sync::Mutex<Vec<Option<String>>>,
+    }
+
+    #[async_trait]
+    impl HealthDataConnector for SyncingConnector {
+        fn source_name(&self) -> &str {
+            "syncing"
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::LosAngelesCounty]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            let resumed = context
+                .resume_sync(|_| true)
+                .await?
+                .and_then(|state| state.watermark);
+            let syncs = resumed.as_deref().map_or(0, |mark| mark.parse().unwrap());
+            self.resumed_from.lock().unwrap().push(resumed);
+            context.save_sync_state(ConnectorSyncState {
+                source: "syncing".to_owned(),
+                watermark: Some((syncs + 1).to_string()),
+                rows: serde_json::Value::Array(Vec::new()),
+                full_sync_at: Utc::now(),
+                synced_at: Utc::now(),
+            });
+            DelayedConnector {
+                source: "syncing",
+                delay: Duration::ZERO,
+            }
+            .fetch_facilities(context)
+            .await
+        }
+    }
+
+    /// Commits one sync state and saves another, then fails.
+    struct CommittingConnector;
+
+    #[async_trait]
+    impl HealthDataConnector for CommittingConnector {
+        fn source_name(&self) -> &str {
+            "committing"
+        }
+
+        fn jurisdictions(&self) -> Vec<Jurisdiction> {
+            vec![Jurisdiction::OrangeCounty]
+        }
+
+        async fn fetch_facilities(
+            &self,
+            context: &FetchContext,
+        ) -> anyhow::Result<Vec<SourceFacilityInput>> {
+            let state = |watermark: &str| ConnectorSyncState {
+                source: "committing".to_owned(),
+                watermark: Some(watermark.to_owned()),
+                rows: serde_json::Value::Array(Vec::new()),
+                full_sync_at: Utc::now(),
+                synced_at: Utc::now(),
+            };
+            context.commit_sync_state(state("moved"));
+            context.save_sync_state(state("fetched"));
+            anyhow::bail!("export unreadable")
+        }
+    }
+
+    #[test]
+    fn merges_fetched_inspections_into_existing_history() {
+        let existing = vec![inspection("lac-1", 1, 90.0), inspection("lac-2", 5, 85.0)];
+        let fetched = vec![inspection("lac-3", 9, 97.0), inspection("lac-2", 5, 88.0)];
+
+        let merged = merge_inspections(existing, fetched);
+
+        let ids = merged
+            .iter()
+            .map(|inspection| inspection.inspection_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["lac-3", "lac-2", "lac-1"]);
+        assert_eq!(merged[1].raw_score, Some(88.0));
+    }
+
+    #[tokio::test]
+    async fn stitches_in_connector_order_and_enforces_fetch_budget() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let connectors: Vec<Arc<dyn HealthDataConnector>> = vec![
+            Arc::new(DelayedConnector {
+                source: "slow",
+                delay: Duration::from_millis(50),
+            }),
+            Arc::new(DelayedConnector {
+                source: "fast",
+                delay: Duration::ZERO,
+            }),
+            Arc::new(DelayedConnector {
+                source: "stuck",
+                delay: Duration::from_secs(30),
+            }),
+        ];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits {
+                connector_budget: Duration::from_millis(300),
+                ..ConnectorFetchLimits::default()
+            },
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        // Both records share a dedupe key and timestamp; the earlier connector wins
+        // even though it finished last.
+        let facilities = repository.list().await.unwrap();
+        assert_eq!(facilities.len(), 1);
+        assert_eq!(facilities[0].id, "lac::slow");
+
+        let stats = service.stats().await.connector_stats;
+        let sources = stats
+            .iter()
+            .map(|status| status.source.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(sources, vec!["slow", "fast", "stuck"]);
+        assert!(stats[2].error.as_deref().unwrap().contains("fetch budget"));
+    }
+
+    #[tokio::test]
+    async fn skips_connector_while_its_circuit_is_open() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let failing = Arc::new(FailingConnector {
+            calls: AtomicUsize::new(0),
+        });
+        let connectors: Vec<Arc<dyn HealthDataConnector>> = vec![
+            Arc::new(DelayedConnector {
+                source: "healthy",
+                delay: Duration::ZERO,
+            }),
+            failing.clone(),
+        ];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy {
+                failure_threshold: 2,
+                cooldown: chrono::Duration::hours(1),
+            },
+            None,
+        );
+
+        for _ in 0..3 {
+            service
+                .refresh(IngestionTrigger::RefreshOnce)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(failing.calls.load(Ordering::SeqCst), 2);
+        let stats = service.stats().await;
+        assert!(
+            stats.connector_stats[1]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("circuit open")
+        );
+        let circuit = stats
+            .circuits
+            .iter()
+            .find(|circuit| circuit.source == "failing")
+            .unwrap();
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.consecutive_failures, 2);
+        assert_eq!(
+            stats
+                .circuits
+                .iter()
+                .find(|circuit| circuit.source == "healthy")
+                .unwrap()
+                .state,
+            CircuitState::Closed
+        );
+    }
+
+    #[tokio::test]
+    async fn new_facility_never_takes_the_id_of_a_failed_connectors_facility() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let grades = Arc::new(SwitchableConnector {
+            source: "lb_grades",
+            failing: AtomicBool::new(false),
+        });
+        let permits = Arc::new(MutableConnector {
+            source: "lb_permits",
+            records: std::sync::Mutex::new(Vec::new()),
+        });
+        let connectors: Vec<Arc<dyn HealthDataConnector>> = vec![grades.clone(), permits.clone()];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        // lb_grades fails while lb_permits first reports a different facility under
+        // the same source ID, whose derived ID `lb::lb_grades` is already taken.
+        grades.failing.store(true, Ordering::SeqCst);
+        *permits.records.lock().unwrap() = vec![SourceFacilityInput {
+            address: "300 Ocean Blvd".to_owned(),
+            city: "Long Beach".to_owned(),
+            postal_code: "90802".to_owned(),
+            jurisdiction: Jurisdiction::LongBeach,
+            ..SourceFacilityInput::sample("lb_grades", "Harbor Grill")
+        }];
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        let mut published = repository
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|facility| (facility.id, facility.name))
+            .collect::<Vec<_>>();
+        published.sort();
+        assert_eq!(
+            published,
+            vec![
+                ("lb::lb_grades".to_owned(), "lb_grades Diner".to_owned()),
+                (
+                    "lb::lb_permits::lb_grades".to_owned(),
+                    "Harbor Grill".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(service.stats().await.unique_facilities, 2);
+    }
+
+    #[tokio::test]
+    async fn failed_jurisdiction_keeps_its_facilities_while_the_rest_of_the_connector_refreshes() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let connector = Arc::new(TwoJurisdictionConnector {
+            long_beach_down: AtomicBool::new(false),
+        });
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            vec![connector.clone()],
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        connector.long_beach_down.store(true, Ordering::SeqCst);
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        let mut published = repository
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|facility| (facility.jurisdiction, facility.name))
+            .collect::<Vec<_>>();
+        published.sort_by_key(|(jurisdiction, _)| jurisdiction.code());
+        assert_eq!(
+            published,
+            vec![
+                (Jurisdiction::LongBeach, "Harbor Grill".to_owned()),
+                (Jurisdiction::Pasadena, "Renamed Cafe".to_owned()),
+            ]
+        );
+        let stats = service.stats().await;
+        assert_eq!(stats.unique_facilities, 2);
+        let status = &stats.connector_stats[0];
+        assert_eq!(status.fetched_records, 1);
+        assert!(status.stale);
+        assert!(status.last_success_at.is_some());
+        assert!(
+            status
+                .error
+                .as_deref()
+                .is_some_and(|error| error.contains("long beach portal down")),
+            "{:?}",
+            status.error
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_connector_keeps_its_facilities_beside_a_healthy_one_on_its_jurisdiction() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let grades = Arc::new(SwitchableConnector {
+            source: "lb_grades",
+            failing: AtomicBool::new(false),
+        });
+        let inspections = Arc::new(SwitchableConnector {
+            source: "lb_inspections",
+            failing: AtomicBool::new(false),
+        });
+        let connectors: Vec<Arc<dyn HealthDataConnector>> =
+            vec![grades.clone(), inspections.clone()];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+        let ids = |facilities: Vec<Facility>| {
+            let mut ids = facilities
+                .into_iter()
+                .map(|facility| facility.id)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+        let published = ids(repository.list().await.unwrap());
+        assert_eq!(published, vec!["lb::lb_grades", "lb::lb_inspections"]);
+        let first_success_at = service.stats().await.connector_stats[0].last_success_at;
+
+        // One of two connectors on Long Beach fails: the other still replaces its own
+        // facility, and the failed one's stays published.
+        grades.failing.store(true, Ordering::SeqCst);
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+        assert_eq!(ids(repository.list().await.unwrap()), published);
+        let stats = service.stats().await;
+        assert_eq!(stats.unique_facilities, 2);
+        let failed = &stats.connector_stats[0];
+        assert_eq!(failed.source, "lb_grades");
+        assert!(failed.stale);
+        assert!(
+            failed
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("portal unavailable")
+        );
+        assert_eq!(failed.last_success_at, first_success_at);
+        assert!(!stats.connector_stats[1].stale);
+
+        // With every connector down nothing is replaced and the run fails.
+        inspections.failing.store(true, Ordering::SeqCst);
+        assert!(
+            service
+                .refresh(IngestionTrigger::RefreshOnce)
+                .await
+                .is_err()
+        );
+        assert_eq!(ids(repository.list().await.unwrap()), published);
+
+        // A recovered connector replaces its facility again.
+        grades.failing.store(false, Ordering::SeqCst);
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+        let stats = service.stats().await.connector_stats;
+        assert!(!stats[0].stale);
+        assert!(stats[1].stale);
+        assert_eq!(ids(repository.list().await.unwrap()), published);
+    }
+
+    #[tokio::test]
+    async fn defers_connectors_until_their_refresh_interval_elapses() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let weekly = Arc::new(WeeklyConnector {
+            calls: AtomicUsize::new(0),
+        });
+        let connectors: Vec<Arc<dyn HealthDataConnector>> = vec![
+            Arc::new(DelayedConnector {
+                source: "daily",
+                delay: Duration::ZERO,
+            }),
+            weekly.clone(),
+        ];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        for _ in 0..2 {
+            service
+                .refresh(IngestionTrigger::RefreshOnce)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(weekly.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(repository.list().await.unwrap().len(), 2);
+        let stats = service.stats().await.connector_stats;
+        assert_eq!(stats[1].source, "weekly");
+        assert!(stats[1].error.is_none());
+        assert_eq!(stats[1].fetched_records, 1);
+    }
+
+    #[tokio::test]
+    async fn deferred_connector_keeps_its_facilities_beside_a_due_one_on_its_jurisdiction() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let weekly = Arc::new(WeeklyConnector {
+            calls: AtomicUsize::new(0),
+        });
+        let mut records = DelayedConnector {
+            source: "static",
+            delay: Duration::ZERO,
+        }
+        .fetch_facilities(&FetchContext::default())
+        .await
+        .unwrap();
+        records[0].name = "Static Cafe".to_owned();
+        records[0].city = "Pasadena".to_owned();
+        records[0].jurisdiction = Jurisdiction::Pasadena;
+        let connectors: Vec<Arc<dyn HealthDataConnector>> =
+            vec![Arc::new(StaticConnector { records }), weekly.clone()];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        for _ in 0..3 {
+            service
+                .refresh(IngestionTrigger::RefreshOnce)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(weekly.calls.load(Ordering::SeqCst), 1);
+        let mut names = repository
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|facility| facility.name)
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["Static Cafe", "Weekly Bakery"]);
+        assert_eq!(service.stats().await.unique_facilities, 2);
+    }
+
+    #[tokio::test]
+    async fn records_each_run_with_its_trigger_outcome_and_connector_counts() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let counted = Arc::new(CountedConnector {
+            count: AtomicUsize::new(4),
+        });
+        let connectors: Vec<Arc<dyn HealthDataConnector>> = vec![
+            counted.clone(),
+            Arc::new(FailingConnector {
+                calls: AtomicUsize::new(0),
+            }),
+        ];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        service.refresh(IngestionTrigger::Startup).await.unwrap();
+        let runs = service.runs(10).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        let published = &runs[0];
+        assert_eq!(published.trigger, IngestionTrigger::Startup);
+        assert_eq!(published.outcome, IngestionRunOutcome::Published);
+        assert_eq!(published.unique_facilities, Some(4));
+        assert!(published.finished_at.is_some());
+        assert!(published.message.is_none());
+        let counts = published
+            .connector_stats
+            .iter()
+            .map(|status| (status.source.as_str(), status.fetched_records, status.stale))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![("counted", 4, false), ("failing", 0, true)]);
+
+        // Shrinking below half the published count is rejected and publishes nothing.
+        counted.count.store(1, Ordering::SeqCst);
+        assert!(service.refresh(IngestionTrigger::ApiRefresh).await.is_err());
+        // A successful fetch of nothing fails the run outright.
+        counted.count.store(0, Ordering::SeqCst);
+        assert!(service.refresh(IngestionTrigger::Scheduler).await.is_err());
+
+        let runs = service.runs(10).await.unwrap();
+        let outcomes = runs
+            .iter()
+            .map(|run| (run.trigger, run.outcome))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                (IngestionTrigger::Scheduler, IngestionRunOutcome::Failed),
+                (
+                    IngestionTrigger::ApiRefresh,
+                    IngestionRunOutcome::RejectedByShrinkGuard
+                ),
+                (IngestionTrigger::Startup, IngestionRunOutcome::Published),
+            ]
+        );
+        assert_eq!(runs[1].unique_facilities, Some(1));
+        assert!(runs[1].message.as_deref().unwrap().contains("too small"));
+        assert!(
+            runs[0]
+                .message
+                .as_deref()
+                .unwrap()
+                .contains("zero facilities")
+        );
+        assert_eq!(service.runs(1).await.unwrap().len(), 1);
+        assert_eq!(repository.list().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn abandons_runs_left_running_by_an_exited_process() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let run = |id: &str, outcome| IngestionRun {
+            id: id.to_owned(),
+            trigger: IngestionTrigger::Scheduler,
+            started_at: Utc::now(),
+            finished_at: None,
+            outcome,
+            unique_facilities: None,
+            connector_stats: Vec::new(),
+            message: None,
+        };
+        repository
+            .record_ingestion_run(run("crashed", IngestionRunOutcome::Running))
+            .await
+            .unwrap();
+        repository
+            .record_ingestion_run(run("failed", IngestionRunOutcome::Failed))
+            .await
+            .unwrap();
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            Vec::new(),
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        service.abandon_interrupted_runs().await;
+
+        let runs = service.runs(10).await.unwrap();
+        let crashed = runs.iter().find(|run| run.id == "crashed").unwrap();
+        assert_eq!(crashed.outcome, IngestionRunOutcome::Abandoned);
+        assert!(crashed.finished_at.is_some());
+        assert!(crashed.message.is_some());
+        let failed = runs.iter().find(|run| run.id == "failed").unwrap();
+        assert_eq!(failed.outcome, IngestionRunOutcome::Failed);
+        assert!(failed.finished_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn stores_sync_state_for_the_next_fetch() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let connector = Arc::new(SyncingConnector {
+            resumed_from: std::sync::Mutex::default(),
+        });
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            vec![connector.clone()],
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        for _ in 0..2 {
+            service
+                .refresh(IngestionTrigger::RefreshOnce)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            *connector.resumed_from.lock().unwrap(),
+            vec![None, Some("1".to_owned())]
+        );
+        let stored = repository.get_sync_state("syncing").await.unwrap().unwrap();
+        assert_eq!(stored.watermark.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn stores_committed_sync_state_when_the_fetch_fails() {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let connectors: Vec<Arc<dyn HealthDataConnector>> = vec![
+            Arc::new(DelayedConnector {
+                source: "healthy",
+                delay: Duration::ZERO,
+            }),
+            Arc::new(CommittingConnector),
+        ];
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            connectors,
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        let stored = repository
+            .get_sync_state("committing")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.watermark.as_deref(), Some("moved"));
+    }
+
+    #[tokio::test]
+    async fn scores_from_the_newest_scored_inspection() {
+        let visit = |day: u32, inspection_type: &str, raw_score: Option<f32>| SourceFacilityInput {
+            address: "1 Colorado Blvd".to_owned(),
+            inspected_at: Some(Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap()),
+            inspection_type: Some(inspection_type.to_owned()),
+            raw_score,
+            ..SourceFacilityInput::sample("1", "Cafe One")
+        };
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            vec![Arc::new(StaticConnector {
+                records: vec![visit(2, "routine", Some(88.0)), visit(9, "followup", None)],
+            })],
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        let facility = &repository.list().await.unwrap()[0];
+        assert_eq!(facility.trust_score, 88);
+        let types = facility
+            .inspections
+            .iter()
+            .map(|inspection| inspection.inspection_type.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(types, vec![Some("followup"), Some("routine")]);
+    }
+
+    #[tokio::test]
+    async fn dead_letters_rejected_and_repaired_records() {
+        let record =
+            |source_id: &str, name: &str, repairs: Vec<ValidationReason>| SourceFacilityInput {
+                address: format!("{source_id} Colorado Blvd"),
+                raw_score: Some(90.0),
+                repairs,
+                ..SourceFacilityInput::sample(source_id, name)
+            };
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        let service = IngestionService::new(
+            repository.clone(),
+            Arc::new(TrustScoreService),
+            vec![Arc::new(StaticConnector {
+                records: vec![
+                    record("1", "Cafe One", Vec::new()),
+                    record(
+                        "2",
+                        "Cafe Two",
+                        vec![ValidationReason::CoordinatesDefaulted],
+                    ),
+                    record("3", " ", Vec::new()),
+                ],
+            })],
+            None,
+            ConnectorFetchLimits::default(),
+            CircuitBreakerPolicy::default(),
+            None,
+        );
+
+        service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await
+            .unwrap();
+
+        assert_eq!(repository.list().await.unwrap().len(), 2);
+        let validation = &service.stats().await.connector_stats[0].validation;
+        assert_eq!(
+            (validation.valid, validation.repaired, validation.rejected),
+            (1, 1, 1)
+        );
+        assert_eq!(validation.reasons[&ValidationReason::MissingName], 1);
+
+        let rejected = service
+            .dead_letters(&DeadLetterQuery {
+                disposition: Some(RecordDisposition::Rejected),
+                limit: 10,
+                ..DeadLetterQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].source_id.as_deref(), Some("3"));
+        assert_eq!(rejected[0].payload["address"], "3 Colorado Blvd");
+    }
 }
@@ -1,9 +1,21 @@
+mod change_detection;
+mod circuit_breaker;
 mod directory_service;
+mod entity_resolution;
+mod geocoding_service;
 mod ingestion_service;
+mod record_validation;
 mod trust_score_service;
 mod vote_service;
+mod webhook_service;
 
+pub use change_detection::{FacilitySnapshot, detect_changes};
+pub use circuit_breaker::{CircuitBreakerPolicy, CircuitDecision};
 pub use directory_service::DirectoryService;
-pub use ingestion_service::IngestionService;
+pub use entity_resolution::{SourceRecord, resolve_entities};
+pub use geocoding_service::GeocodingService;
+pub use ingestion_service::{ConnectorFetchLimits, IngestionService};
+pub use record_validation::{ValidationReport, validate_records};
 pub use trust_score_service::{ScoreSignals, TrustScoreService};
 pub use vote_service::VoteService;
+pub use webhook_service::{NewWebhookSubscription, WebhookDeliveryPolicy, WebhookService};
@@ -0,0 +1,151 @@
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::{RecordDisposition, ValidationCounts, ValidationReason},
+};
+
+/// A repaired or rejected record, as the connector produced it.
+#[derive(Clone, Debug)]
+pub struct FlaggedRecord {
+    pub disposition: RecordDisposition,
+    pub reasons: Vec<ValidationReason>,
+    pub record: SourceFacilityInput,
+}
+
+/// Outcome of validating one connector's records.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    /// Valid and repaired records, in fetch order, ready for normalization.
+    pub accepted: Vec<SourceFacilityInput>,
+    /// Repaired and rejected records, in fetch order.
+    pub flagged: Vec<FlaggedRecord>,
+    pub counts: ValidationCounts,
+}
+
+/// Classifies every record as valid, repaired, or rejected. Rejected records never
+/// reach normalization; repaired ones do, after any fix this stage applies itself.
+pub fn validate_records(records: Vec<SourceFacilityInput>) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    for mut record in records {
+        let mut reasons = rejection_reasons(&record);
+        let rejected = !reasons.is_empty();
+
+        let score_out_of_range = record
+            .raw_score
+            .is_some_and(|score| !(0.0..=100.0).contains(&score));
+        if score_out_of_range {
+            reasons.push(ValidationReason::ScoreOutOfRange);
+        }
+        reasons.extend(record.repairs.iter().copied());
+        reasons.sort();
+        reasons.dedup();
+
+        for reason in &reasons {
+            *report.counts.reasons.entry(*reason).or_default() += 1;
+        }
+
+        let disposition = if rejected {
+            RecordDisposition::Rejected
+        } else if reasons.is_empty() {
+            RecordDisposition::Valid
+        } else {
+            RecordDisposition::Repaired
+        };
+
+        match disposition {
+            RecordDisposition::Valid => report.counts.valid += 1,
+            RecordDisposition::Repaired => report.counts.repaired += 1,
+            RecordDisposition::Rejected => report.counts.rejected += 1,
+        }
+
+        if disposition != RecordDisposition::Valid {
+            report.flagged.push(FlaggedRecord {
+                disposition,
+                reasons,
+                record: record.clone(),
+            });
+        }
+
+        if disposition != RecordDisposition::Rejected {
+            if score_out_of_range {
+                record.raw_score = None;
+            }
+            report.accepted.push(record);
+        }
+    }
+
+    report
+}
+
+fn rejection_reasons(record: &SourceFacilityInput) -> Vec<ValidationReason> {
+    let mut reasons = Vec::new();
+
+    if record.source_id.trim().is_empty() {
+        reasons.push(ValidationReason::MissingSourceId);
+    }
+    if record.name.trim().is_empty() {
+        reasons.push(ValidationReason::MissingName);
+    }
+
+    let coordinates_valid = record.latitude.is_finite()
+        && record.longitude.is_finite()
+        && (-90.0..=90.0).contains(&record.latitude)
+        && (-180.0..=180.0).contains(&record.longitude)
+        && !(record.latitude == 0.0 && record.longitude == 0.0);
+    if !coordinates_valid {
+        reasons.push(ValidationReason::InvalidCoordinates);
+    }
+
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_records;
+    use crate::{
+        application::dto::SourceFacilityInput,
+        domain::entities::{RecordDisposition, ValidationReason},
+    };
+
+    #[test]
+    fn classifies_valid_repaired_and_rejected_records() {
+        let mut defaulted = SourceFacilityInput::sample("2", "Cafe Two");
+        defaulted.repairs = vec![ValidationReason::CoordinatesDefaulted];
+        let mut bad_score = SourceFacilityInput::sample("3", "Cafe Three");
+        bad_score.raw_score = Some(140.0);
+        let mut nowhere = SourceFacilityInput::sample("4", "");
+        nowhere.latitude = 0.0;
+        nowhere.longitude = 0.0;
+
+        let report = validate_records(vec![
+            SourceFacilityInput::sample("1", "Cafe One"),
+            defaulted,
+            bad_score,
+            nowhere,
+        ]);
+
+        assert_eq!(report.accepted.len(), 3);
+        assert_eq!(report.accepted[2].raw_score, None);
+        assert_eq!(
+            (
+                report.counts.valid,
+                report.counts.repaired,
+                report.counts.rejected
+            ),
+            (1, 2, 1)
+        );
+        assert_eq!(report.flagged[1].record.raw_score, Some(140.0));
+        assert_eq!(report.flagged[2].disposition, RecordDisposition::Rejected);
+        assert_eq!(
+            report.flagged[2].reasons,
+            vec![
+                ValidationReason::MissingName,
+                ValidationReason::InvalidCoordinates
+            ]
+        );
+        assert_eq!(
+            report.counts.reasons[&ValidationReason::CoordinatesDefaulted],
+            1
+        );
+    }
+}
@@ -0,0 +1,463 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{Duration, Utc};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        entities::{
+            FacilityEvent, FacilityEventKind, Jurisdiction, WebhookDelivery, WebhookDeliveryStatus,
+            WebhookSubscription,
+        },
+        errors::RepositoryError,
+        repositories::{FacilityRepository, WebhookDeliveryQuery},
+    },
+    infrastructure::webhooks::{WebhookClient, WebhookRequest},
+};
+
+/// How the outbox retries deliveries.
+#[derive(Clone, Copy, Debug)]
+pub struct WebhookDeliveryPolicy {
+    /// Attempts before a delivery is marked failed, the first one included.
+    pub max_attempts: u32,
+    /// Wait after the first failed attempt; doubles with every further failure.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Deliveries claimed per dispatch pass.
+    pub batch_size: usize,
+    /// How long a claimed delivery stays hidden from other dispatchers, so a crash
+    /// mid-send only delays it.
+    pub lease: Duration,
+}
+
+impl Default for WebhookDeliveryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            base_delay: Duration::seconds(30),
+            max_delay: Duration::hours(6),
+            batch_size: 100,
+            lease: Duration::minutes(5),
+        }
+    }
+}
+
+impl WebhookDeliveryPolicy {
+    fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2i32.saturating_pow(attempts.saturating_sub(1).min(30));
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NewWebhookSubscription {
+    pub url: String,
+    /// Generated when omitted.
+    pub secret: Option<String>,
+    pub event_types: Vec<FacilityEventKind>,
+    pub jurisdictions: Vec<Jurisdiction>,
+    pub facility_ids: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct WebhookService {
+    repository: Arc<dyn FacilityRepository>,
+    client: WebhookClient,
+    policy: WebhookDeliveryPolicy,
+}
+
+impl WebhookService {
+    pub fn new(
+        repository: Arc<dyn FacilityRepository>,
+        client: WebhookClient,
+        policy: WebhookDeliveryPolicy,
+    ) -> Self {
+        Self {
+            repository,
+            client,
+            policy,
+        }
+    }
+
+    pub async fn subscribe(
+        &self,
+        request: NewWebhookSubscription,
+    ) -> Result<WebhookSubscription, RepositoryError> {
+        let subscription = WebhookSubscription {
+            id: Uuid::new_v4().to_string(),
+            url: request.url,
+            secret: request
+                .secret
+                .filter(|secret| !secret.is_empty())
+                .unwrap_or_else(|| {
+                    format!(
+                        "whsec_{}{}",
+                        Uuid::new_v4().simple(),
+                        Uuid::new_v4().simple()
+                    )
+                }),
+            event_types: request.event_types,
+            jurisdictions: request.jurisdictions,
+            facility_ids: request.facility_ids,
+            created_at: Utc::now(),
+        };
+
+        self.repository
+            .create_webhook_subscription(subscription.clone())
+            .await?;
+        Ok(subscription)
+    }
+
+    /// Checks that a subscriber's URL is one deliveries may be sent to.
+    pub async fn check_target(&self, url: &str) -> Result<(), String> {
+        self.client.check_target(url).await
+    }
+
+    pub async fn subscriptions(&self) -> Result<Vec<WebhookSubscription>, RepositoryError> {
+        self.repository.list_webhook_subscriptions().await
+    }
+
+    /// Deliveries already queued for the subscription fail on their next attempt.
+    pub async fn unsubscribe(&self, id: &str) -> Result<bool, RepositoryError> {
+        self.repository.delete_webhook_subscription(id).await
+    }
+
+    pub async fn deliveries(
+        &self,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        self.repository.list_webhook_deliveries(query).await
+    }
+
+    /// Queues one outbox delivery per matching subscription and event. Returns how
+    /// many were queued.
+    pub async fn enqueue(&self, events: &[FacilityEvent]) -> Result<usize, RepositoryError> {
+        let subscriptions = self.repository.list_webhook_subscriptions().await?;
+        if subscriptions.is_empty() || events.is_empty() {
+            return Ok(0);
+        }
+
+        let now = Utc::now();
+        let mut deliveries = Vec::new();
+        for event in events {
+            let payload = serde_json::json!({
+                "event_id": event.id,
+                "type": event.change.kind().code(),
+                "data": event,
+            });
+            for subscription in subscriptions
+                .iter()
+                .filter(|subscription| subscription.matches(event))
+            {
+                deliveries.push(WebhookDelivery {
+                    id: Uuid::new_v4().to_string(),
+                    subscription_id: subscription.id.clone(),
+                    event_id: event.id.clone(),
+                    event_type: event.change.kind(),
+                    payload: payload.clone(),
+                    status: WebhookDeliveryStatus::Pending,
+                    attempts: 0,
+                    next_attempt_at: now,
+                    last_attempt_at: None,
+                    last_response_status: None,
+                    last_error: None,
+                    created_at: now,
+                    delivered_at: None,
+                });
+            }
+        }
+
+        let queued = deliveries.len();
+        if queued > 0 {
+            self.repository
+                .enqueue_webhook_deliveries(deliveries)
+                .await?;
+        }
+        Ok(queued)
+    }
+
+    /// Attempts every delivery that is due, one batch at a time. Returns how many
+    /// attempts were made.
+    pub async fn dispatch_due(&self) -> Result<usize, RepositoryError> {
+        // Deliveries rescheduled during this pass are due after `started_at`, so the
+        // loop ends even when every attempt fails.
+        let started_at = Utc::now();
+        let mut attempted = 0usize;
+
+        loop {
+            let now = Utc::now();
+            let lease_until = now.checked_add_signed(self.policy.lease).unwrap_or(now);
+            let batch = self
+                .repository
+                .claim_due_webhook_deliveries(
+                    started_at,
+                    lease_until,
+                    self.policy.batch_size.max(1),
+                )
+                .await?;
+            if batch.is_empty() {
+                return Ok(attempted);
+            }
+
+            let subscriptions = self
+                .repository
+                .list_webhook_subscriptions()
+                .await?
+                .into_iter()
+                .map(|subscription| (subscription.id.clone(), subscription))
+                .collect::<HashMap<_, _>>();
+
+            for delivery in batch {
+                attempted += 1;
+                let subscription = subscriptions.get(&delivery.subscription_id);
+                let delivery = self.attempt(delivery, subscription).await;
+                self.repository.update_webhook_delivery(delivery).await?;
+            }
+        }
+    }
+
+    async fn attempt(
+        &self,
+        mut delivery: WebhookDelivery,
+        subscription: Option<&WebhookSubscription>,
+    ) -> WebhookDelivery {
+        let now = Utc::now();
+        delivery.attempts = delivery.attempts.saturating_add(1);
+        delivery.last_attempt_at = Some(now);
+
+        let Some(subscription) = subscription else {
+            delivery.status = WebhookDeliveryStatus::Failed;
+            delivery.last_response_status = None;
+            delivery.last_error = Some("subscription was deleted".to_owned());
+            return delivery;
+        };
+
+        let body = serde_json::to_vec(&delivery.payload).unwrap_or_default();
+        let outcome = self
+            .client
+            .send(WebhookRequest {
+                url: &subscription.url,
+                secret: &subscription.secret,
+                delivery_id: &delivery.id,
+                event_type: delivery.event_type.code(),
+                timestamp: now.timestamp(),
+                body: &body,
+            })
+            .await;
+
+        delivery.last_response_status = outcome.status;
+        delivery.last_error = outcome.error.clone();
+        if outcome.succeeded() {
+            delivery.status = WebhookDeliveryStatus::Delivered;
+            delivery.delivered_at = Some(Utc::now());
+            info!(delivery_id = %delivery.id, url = %subscription.url, "Delivered webhook");
+        } else if delivery.attempts >= self.policy.max_attempts.max(1) {
+            delivery.status = WebhookDeliveryStatus::Failed;
+            warn!(
+                delivery_id = %delivery.id,
+                url = %subscription.url,
+                attempts = delivery.attempts,
+                error = outcome.error.as_deref().unwrap_or_default(),
+                "Webhook delivery failed; giving up"
+            );
+        } else {
+            delivery.next_attempt_at = now
+                .checked_add_signed(self.policy.backoff(delivery.attempts))
+                .unwrap_or(now);
+            warn!(
+                delivery_id = %delivery.id,
+                url = %subscription.url,
+                attempts = delivery.attempts,
+                error = outcome.error.as_deref().unwrap_or_default(),
+                "Webhook delivery failed; will retry"
+            );
+        }
+
+        delivery
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        Router,
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+    };
+    use chrono::{Duration, Utc};
+    use tokio::net::TcpListener;
+
+    use super::{NewWebhookSubscription, WebhookDeliveryPolicy, WebhookService};
+    use crate::{
+        domain::{
+            entities::{
+                FacilityChange, FacilityEvent, FacilityEventKind, Jurisdiction,
+                WebhookDeliveryStatus,
+            },
+            repositories::WebhookDeliveryQuery,
+        },
+        infrastructure::{
+            repositories::InMemoryFacilityRepository,
+            webhooks::{self, WebhookClient},
+        },
+    };
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+    async fn receive(
+        State((status, received)): State<(StatusCode, Received)>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        received.lock().unwrap().push((headers, body));
+        status
+    }
+
+    /// Starts a local endpoint that records every request and answers with `status`.
+    async fn start_receiver(status: StatusCode) -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state((status, received.clone()));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{address}/hook"), received)
+    }
+
+    fn service(policy: WebhookDeliveryPolicy) -> WebhookService {
+        WebhookService::new(
+            Arc::new(InMemoryFacilityRepository::new()),
+            WebhookClient::new(std::time::Duration::from_secs(5), true),
+            policy,
+        )
+    }
+
+    fn event(id: &str, change: FacilityChange) -> FacilityEvent {
+        FacilityEvent {
+            id: id.to_owned(),
+            run_id: "run-1".to_owned(),
+            facility_id: "lb::42".to_owned(),
+            facility_name: "Harbor Grill".to_owned(),
+            jurisdiction: Jurisdiction::LongBeach,
+            change,
+            detected_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_matching_events_with_a_verifiable_signature() {
+        let (url, received) = start_receiver(StatusCode::OK).await;
+        let service = service(WebhookDeliveryPolicy::default());
+        let subscription = service
+            .subscribe(NewWebhookSubscription {
+                url,
+                secret: Some("partner-secret".to_owned()),
+                event_types: vec![FacilityEventKind::ClosureIssued],
+                jurisdictions: vec![Jurisdiction::LongBeach],
+                ..NewWebhookSubscription::default()
+            })
+            .await
+            .unwrap();
+
+        let queued = service
+            .enqueue(&[
+                event(
+                    "evt-closure",
+                    FacilityChange::ClosureIssued {
+                        placard_status: Some("Red".to_owned()),
+                    },
+                ),
+                event(
+                    "evt-score",
+                    FacilityChange::TrustScoreChanged { from: 80, to: 70 },
+                ),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(queued, 1);
+        assert_eq!(service.dispatch_due().await.unwrap(), 1);
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 1);
+        let (headers, body) = &received[0];
+        let header = |name: &str| headers[name].to_str().unwrap().to_owned();
+        let timestamp = header(webhooks::TIMESTAMP_HEADER).parse::<i64>().unwrap();
+        assert_eq!(
+            header(webhooks::SIGNATURE_HEADER),
+            webhooks::sign("partner-secret", timestamp, body)
+        );
+        assert_eq!(header(webhooks::EVENT_HEADER), "closure_issued");
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["event_id"], "evt-closure");
+        assert_eq!(payload["data"]["change"]["placard_status"], "Red");
+
+        let log = service
+            .deliveries(&WebhookDeliveryQuery {
+                subscription_id: Some(subscription.id),
+                limit: 10,
+                ..WebhookDeliveryQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].status, WebhookDeliveryStatus::Delivered);
+        assert_eq!(log[0].attempts, 1);
+        assert_eq!(log[0].last_response_status, Some(200));
+    }
+
+    #[tokio::test]
+    async fn retries_failed_deliveries_until_attempts_run_out() {
+        let (url, received) = start_receiver(StatusCode::SERVICE_UNAVAILABLE).await;
+        let service = service(WebhookDeliveryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::zero(),
+            ..WebhookDeliveryPolicy::default()
+        });
+        service
+            .subscribe(NewWebhookSubscription {
+                url,
+                ..NewWebhookSubscription::default()
+            })
+            .await
+            .unwrap();
+        service
+            .enqueue(&[event("evt-added", FacilityChange::FacilityAdded)])
+            .await
+            .unwrap();
+        let log = |status| {
+            let service = service.clone();
+            async move {
+                service
+                    .deliveries(&WebhookDeliveryQuery {
+                        status: Some(status),
+                        limit: 10,
+                        ..WebhookDeliveryQuery::default()
+                    })
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // A retry is never due within the pass that scheduled it.
+        assert_eq!(service.dispatch_due().await.unwrap(), 1);
+        let pending = log(WebhookDeliveryStatus::Pending).await;
+        assert_eq!(pending[0].attempts, 1);
+        assert_eq!(pending[0].last_response_status, Some(503));
+
+        assert_eq!(service.dispatch_due().await.unwrap(), 1);
+        let failed = log(WebhookDeliveryStatus::Failed).await;
+        assert_eq!(failed[0].attempts, 2);
+        assert_eq!(received.lock().unwrap().len(), 2);
+        assert_eq!(service.dispatch_due().await.unwrap(), 0);
+    }
+}
@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::PathBuf};
 
 #[derive(Clone, Debug)]
 pub struct Settings {
@@ -6,9 +6,27 @@ pub struct Settings {
     pub port: u16,
     pub cors_origin: String,
     pub ingestion_interval_hours: u64,
+    pub connector_parallelism: usize,
+    pub connector_budget_secs: u64,
+    pub circuit_failure_threshold: u32,
+    pub circuit_cooldown_hours: u64,
+    pub webhook_max_attempts: u32,
+    pub webhook_timeout_secs: u64,
+    pub webhook_dispatch_interval_secs: u64,
+    /// Lets webhooks target loopback, private and link-local addresses.
+    pub webhook_allow_private_targets: bool,
+    /// Bearer token required by the webhook management, dead-letter and LIVES export
+    /// endpoints, which are disabled when it is unset.
+    pub admin_token: Option<String>,
     pub run_mode: RunMode,
     pub database_url: Option<String>,
     pub enable_background_ingestion: bool,
+    pub replay_run_id: Option<String>,
+    /// Where `export_lives` mode writes its ZIP.
+    pub export_path: PathBuf,
+    pub gazetteer_paths: Vec<PathBuf>,
+    /// TOML file declaring the connectors to run; every built-in connector runs when unset.
+    pub connectors_file: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -16,6 +34,10 @@ pub enum RunMode {
     Api,
     Worker,
     RefreshOnce,
+    /// Rebuilds the dataset from an archived run's payloads, then exits.
+    Replay,
+    /// Writes the published facilities as a LIVES batch ZIP, then exits.
+    ExportLives,
 }
 
 impl Settings {
@@ -27,6 +49,8 @@ impl Settings {
         {
             "worker" => RunMode::Worker,
             "refresh_once" => RunMode::RefreshOnce,
+            "replay" => RunMode::Replay,
+            "export_lives" => RunMode::ExportLives,
             _ => RunMode::Api,
         };
 
@@ -42,6 +66,53 @@ impl Settings {
                 .ok()
                 .and_then(|value| value.parse::<u64>().ok())
                 .unwrap_or(24),
+            connector_parallelism: env::var("CLEANPLATED_CONNECTOR_PARALLELISM")
+                .ok()
+                .and_then(|value| value.parse::<usize>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(4),
+            connector_budget_secs: env::var("CLEANPLATED_CONNECTOR_BUDGET_SECS")
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(900),
+            circuit_failure_threshold: env::var("CLEANPLATED_CIRCUIT_FAILURE_THRESHOLD")
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(3),
+            circuit_cooldown_hours: env::var("CLEANPLATED_CIRCUIT_COOLDOWN_HOURS")
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(72),
+            webhook_max_attempts: env::var("CLEANPLATED_WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(8),
+            webhook_timeout_secs: env::var("CLEANPLATED_WEBHOOK_TIMEOUT_SECS")
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(10),
+            webhook_dispatch_interval_secs: env::var("CLEANPLATED_WEBHOOK_DISPATCH_INTERVAL_SECS")
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(30),
+            webhook_allow_private_targets: env::var("CLEANPLATED_WEBHOOK_ALLOW_PRIVATE_TARGETS")
+                .ok()
+                .map(|value| {
+                    matches!(
+                        value.to_ascii_lowercase().as_str(),
+                        "1" | "true" | "yes" | "on"
+                    )
+                })
+                .unwrap_or(false),
+            admin_token: env::var("CLEANPLATED_ADMIN_TOKEN")
+                .ok()
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty()),
             run_mode,
             database_url: env::var("DATABASE_URL")
                 .ok()
@@ -56,6 +127,27 @@ impl Settings {
                     )
                 })
                 .unwrap_or(false),
+            replay_run_id: env::var("CLEANPLATED_REPLAY_RUN_ID")
+                .ok()
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty()),
+            export_path: env::var("CLEANPLATED_EXPORT_PATH")
+                .ok()
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+                .map_or_else(|| PathBuf::from("lives.zip"), PathBuf::from),
+            gazetteer_paths: env::var("CLEANPLATED_GAZETTEER_PATHS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            connectors_file: env::var("CLEANPLATED_CONNECTORS_FILE")
+                .ok()
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from),
         }
     }
 }
@@ -0,0 +1,482 @@
+use serde::Serialize;
+
+/// Street suffixes as written in feeds, mapped to the USPS Publication 28 abbreviation.
+const STREET_SUFFIXES: &[(&str, &str)] = &[
+    ("ALLEY", "ALY"),
+    ("ALY", "ALY"),
+    ("AV", "AVE"),
+    ("AVE", "AVE"),
+    ("AVEN", "AVE"),
+    ("AVENUE", "AVE"),
+    ("AVN", "AVE"),
+    ("BLVD", "BLVD"),
+    ("BOUL", "BLVD"),
+    ("BOULEVARD", "BLVD"),
+    ("BOULV", "BLVD"),
+    ("CENTER", "CTR"),
+    ("CENTRE", "CTR"),
+    ("CIR", "CIR"),
+    ("CIRCLE", "CIR"),
+    ("CNTR", "CTR"),
+    ("COURT", "CT"),
+    ("CRES", "CRES"),
+    ("CRESCENT", "CRES"),
+    ("CROSSING", "XING"),
+    ("CT", "CT"),
+    ("CTR", "CTR"),
+    ("DR", "DR"),
+    ("DRIVE", "DR"),
+    ("DRV", "DR"),
+    ("EXPRESSWAY", "EXPY"),
+    ("EXPY", "EXPY"),
+    ("FREEWAY", "FWY"),
+    ("FWY", "FWY"),
+    ("HIGHWAY", "HWY"),
+    ("HWY", "HWY"),
+    ("LANE", "LN"),
+    ("LN", "LN"),
+    ("LOOP", "LOOP"),
+    ("MALL", "MALL"),
+    ("PARKWAY", "PKWY"),
+    ("PATH", "PATH"),
+    ("PKWY", "PKWY"),
+    ("PKY", "PKWY"),
+    ("PL", "PL"),
+    ("PLACE", "PL"),
+    ("PLAZA", "PLZ"),
+    ("PLZ", "PLZ"),
+    ("PROMENADE", "PROM"),
+    ("PROM", "PROM"),
+    ("RD", "RD"),
+    ("ROAD", "RD"),
+    ("ROW", "ROW"),
+    ("SQ", "SQ"),
+    ("SQUARE", "SQ"),
+    ("ST", "ST"),
+    ("STR", "ST"),
+    ("STREET", "ST"),
+    ("TER", "TER"),
+    ("TERRACE", "TER"),
+    ("TRAIL", "TRL"),
+    ("TRL", "TRL"),
+    ("WALK", "WALK"),
+    ("WAY", "WAY"),
+    ("WY", "WAY"),
+    ("XING", "XING"),
+];
+
+const DIRECTIONALS: &[(&str, &str)] = &[
+    ("E", "E"),
+    ("EAST", "E"),
+    ("N", "N"),
+    ("NE", "NE"),
+    ("NORTH", "N"),
+    ("NORTHEAST", "NE"),
+    ("NORTHWEST", "NW"),
+    ("NW", "NW"),
+    ("S", "S"),
+    ("SE", "SE"),
+    ("SOUTH", "S"),
+    ("SOUTHEAST", "SE"),
+    ("SOUTHWEST", "SW"),
+    ("SW", "SW"),
+    ("W", "W"),
+    ("WEST", "W"),
+];
+
+/// Unit designators that are followed by a unit number.
+const UNIT_DESIGNATORS: &[(&str, &str)] = &[
+    ("#", "#"),
+    ("APARTMENT", "APT"),
+    ("APT", "APT"),
+    ("BLDG", "BLDG"),
+    ("BUILDING", "BLDG"),
+    ("DEPARTMENT", "DEPT"),
+    ("DEPT", "DEPT"),
+    ("FL", "FL"),
+    ("FLOOR", "FL"),
+    ("RM", "RM"),
+    ("ROOM", "RM"),
+    ("SPACE", "SPC"),
+    ("SPC", "SPC"),
+    ("STE", "STE"),
+    ("SUITE", "STE"),
+    ("TRAILER", "TRLR"),
+    ("TRLR", "TRLR"),
+    ("UNIT", "UNIT"),
+];
+
+/// Unit designators that stand alone.
+const BARE_UNIT_DESIGNATORS: &[(&str, &str)] = &[
+    ("FRNT", "FRNT"),
+    ("FRONT", "FRNT"),
+    ("LBBY", "LBBY"),
+    ("LOBBY", "LBBY"),
+    ("LOWER", "LOWR"),
+    ("LOWR", "LOWR"),
+    ("REAR", "REAR"),
+    ("UPPER", "UPPR"),
+    ("UPPR", "UPPR"),
+];
+
+const STATE_CODES: &[&str] = &[
+    "AK", "AL", "AR", "AZ", "CA", "CO", "CT", "DC", "DE", "FL", "GA", "HI", "IA", "ID", "IL", "IN",
+    "KS", "KY", "LA", "MA", "MD", "ME", "MI", "MN", "MO", "MS", "MT", "NC", "ND", "NE", "NH", "NJ",
+    "NM", "NV", "NY", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT", "VA", "VT", "WA",
+    "WI", "WV", "WY",
+];
+
+/// A US street address split into USPS components, each uppercased and abbreviated.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PostalAddress {
+    pub number: Option<String>,
+    pub predirectional: Option<String>,
+    pub street_name: Option<String>,
+    pub suffix: Option<String>,
+    pub postdirectional: Option<String>,
+    /// Designator and number, e.g. `STE 4`.
+    pub unit: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub zip: Option<String>,
+    pub zip4: Option<String>,
+}
+
+impl PostalAddress {
+    /// Parses a one-line address. Comma-separated parts after the street are read as a
+    /// unit, a city, and a trailing state and ZIP, so
+    /// `123 West Main Street, Suite 4, Long Beach, CA 90802` parses fully; without
+    /// commas only the street, unit, state and ZIP are recognized.
+    pub fn parse(line: &str) -> Self {
+        let mut segments = line
+            .split([',', '\n'])
+            .map(tokenize)
+            .filter(|tokens| !tokens.is_empty());
+        let Some(mut street) = segments.next() else {
+            return Self::default();
+        };
+
+        let mut address = Self::default();
+        let mut locality = Vec::new();
+        for segment in segments {
+            if address.unit.is_none() && unit_start(&segment, 0) {
+                address.unit = Some(standard_unit(&segment));
+            } else {
+                locality.extend(segment);
+            }
+        }
+
+        if locality.is_empty() {
+            // Without commas a state is only trusted after a ZIP, and never when it could
+            // just as well end the street (`MAIN CT`, `MAIN ST NE`).
+            if address.take_zip(&mut street)
+                && street.last().is_some_and(|last| {
+                    lookup(STREET_SUFFIXES, last).is_none() && lookup(DIRECTIONALS, last).is_none()
+                })
+            {
+                address.take_state(&mut street);
+            }
+        } else {
+            address.take_zip(&mut locality);
+            address.take_state(&mut locality);
+            if !locality.is_empty() {
+                address.city = Some(locality.join(" "));
+            }
+        }
+
+        address.parse_street(street);
+        address
+    }
+
+    /// Parses a facility's address fields. The separate city, state and postal code fill
+    /// in whatever the address line itself does not carry.
+    pub fn from_parts(line: &str, city: &str, state: &str, postal_code: &str) -> Self {
+        let mut address = Self::parse(line);
+        if address.city.is_none() {
+            address.city = Some(tokenize(city).join(" ")).filter(|city| !city.is_empty());
+        }
+        if address.state.is_none() {
+            let mut tokens = tokenize(state);
+            address.take_state(&mut tokens);
+        }
+        if address.zip.is_none() {
+            let mut tokens = tokenize(postal_code);
+            address.take_zip(&mut tokens);
+        }
+
+        address
+    }
+
+    /// The street line in USPS form, e.g. `123 W MAIN ST STE 4`.
+    pub fn street_line(&self) -> String {
+        [
+            &self.number,
+            &self.predirectional,
+            &self.street_name,
+            &self.suffix,
+            &self.postdirectional,
+            &self.unit,
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+
+    /// The street without number or unit, e.g. `W MAIN ST`; `None` without a street name.
+    pub fn street_key(&self) -> Option<String> {
+        self.street_name.as_ref()?;
+        let parts = [
+            &self.predirectional,
+            &self.street_name,
+            &self.suffix,
+            &self.postdirectional,
+        ];
+
+        Some(
+            parts
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    }
+
+    /// The street line for display, e.g. `123 W Main St Ste 4`. Directionals stay
+    /// uppercase and everything else is title-cased.
+    pub fn display_street_line(&self) -> String {
+        let mut parts = Vec::new();
+        parts.extend(self.number.clone());
+        parts.extend(self.predirectional.clone());
+        parts.extend(self.street_name.as_deref().map(title_case));
+        parts.extend(self.suffix.as_deref().map(title_case));
+        parts.extend(self.postdirectional.clone());
+        if let Some(unit) = self.unit.as_deref() {
+            let (designator, rest) = unit.split_once(' ').unwrap_or((unit, ""));
+            parts.push(
+                format!("{} {rest}", title_case(designator))
+                    .trim()
+                    .to_owned(),
+            );
+        }
+
+        parts.join(" ")
+    }
+
+    pub fn display_city(&self) -> Option<String> {
+        self.city.as_deref().map(title_case)
+    }
+
+    /// `ZIP` or `ZIP+4` as `90802-1234`.
+    pub fn postal_code(&self) -> Option<String> {
+        let zip = self.zip.as_ref()?;
+        Some(match &self.zip4 {
+            Some(zip4) => format!("{zip}-{zip4}"),
+            None => zip.clone(),
+        })
+    }
+
+    fn take_zip(&mut self, tokens: &mut Vec<String>) -> bool {
+        let Some(last) = tokens.last() else {
+            return false;
+        };
+
+        let digits = last.replace('-', "");
+        let valid = digits.chars().all(|character| character.is_ascii_digit())
+            && match digits.len() {
+                5 => !last.contains('-'),
+                9 => !last.contains('-') || last.find('-') == Some(5),
+                _ => false,
+            };
+        if !valid {
+            return false;
+        }
+
+        self.zip = Some(digits[..5].to_owned());
+        self.zip4 = (digits.len() == 9).then(|| digits[5..].to_owned());
+        tokens.pop();
+        true
+    }
+
+    fn take_state(&mut self, tokens: &mut Vec<String>) {
+        let Some(last) = tokens.last() else {
+            return;
+        };
+
+        let state = if STATE_CODES.contains(&last.as_str()) {
+            last.clone()
+        } else if last == "CALIFORNIA" {
+            "CA".to_owned()
+        } else {
+            return;
+        };
+        self.state = Some(state);
+        tokens.pop();
+    }
+
+    fn parse_street(&mut self, tokens: Vec<String>) {
+        let mut tokens = tokens.as_slice();
+        if let Some(first) = tokens.first()
+            && first.starts_with(|character: char| character.is_ascii_digit())
+        {
+            self.number = Some(first.clone());
+            tokens = &tokens[1..];
+        }
+
+        // A designator never starts the street name, so the scan begins at the second
+        // token.
+        if let Some(start) = (1..tokens.len()).find(|&index| unit_start(tokens, index)) {
+            if self.unit.is_none() {
+                self.unit = Some(standard_unit(&tokens[start..]));
+            }
+            tokens = &tokens[..start];
+        }
+
+        // `W MAIN` has a predirectional; `SOUTH ST` is a street named South.
+        if tokens.len() >= 2
+            && (tokens.len() >= 3 || lookup(STREET_SUFFIXES, &tokens[1]).is_none())
+            && let Some(direction) = lookup(DIRECTIONALS, &tokens[0])
+        {
+            self.predirectional = Some(direction.to_owned());
+            tokens = &tokens[1..];
+        }
+
+        if tokens.len() >= 2
+            && let Some(direction) = lookup(DIRECTIONALS, &tokens[tokens.len() - 1])
+        {
+            self.postdirectional = Some(direction.to_owned());
+            tokens = &tokens[..tokens.len() - 1];
+        }
+
+        if tokens.len() >= 2
+            && let Some(suffix) = lookup(STREET_SUFFIXES, &tokens[tokens.len() - 1])
+        {
+            self.suffix = Some(suffix.to_owned());
+            tokens = &tokens[..tokens.len() - 1];
+        }
+
+        if !tokens.is_empty() {
+            self.street_name = Some(tokens.join(" "));
+        }
+    }
+}
+
+/// The USPS abbreviation of a street suffix, directional or unit designator, in any
+/// case, e.g. `Street` -> `ST`.
+pub fn standard_abbreviation(word: &str) -> Option<&'static str> {
+    let word = word.to_ascii_uppercase();
+    [
+        STREET_SUFFIXES,
+        DIRECTIONALS,
+        UNIT_DESIGNATORS,
+        BARE_UNIT_DESIGNATORS,
+    ]
+    .into_iter()
+    .find_map(|table| lookup(table, &word))
+}
+
+/// Uppercases, drops periods (`W.` -> `W`) and splits `#4` into `# 4`.
+fn tokenize(value: &str) -> Vec<String> {
+    value
+        .to_ascii_uppercase()
+        .replace('.', "")
+        .replace('#', " # ")
+        .split_whitespace()
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn lookup(table: &[(&str, &'static str)], word: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(variant, _)| *variant == word)
+        .map(|(_, standard)| *standard)
+}
+
+fn unit_start(tokens: &[String], index: usize) -> bool {
+    let Some(token) = tokens.get(index) else {
+        return false;
+    };
+
+    (lookup(UNIT_DESIGNATORS, token).is_some() && index + 1 < tokens.len())
+        || lookup(BARE_UNIT_DESIGNATORS, token).is_some()
+}
+
+fn standard_unit(tokens: &[String]) -> String {
+    let designator = lookup(UNIT_DESIGNATORS, &tokens[0])
+        .or_else(|| lookup(BARE_UNIT_DESIGNATORS, &tokens[0]))
+        .unwrap_or(tokens[0].as_str());
+
+    std::iter::once(designator)
+        .chain(tokens[1..].iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `MAIN` -> `Main`, `1ST` -> `1st`.
+fn title_case(value: &str) -> String {
+    value
+        .split(' ')
+        .map(|word| {
+            let mut characters = word.chars();
+            match characters.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(characters.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PostalAddress, standard_abbreviation};
+
+    #[test]
+    fn parses_spelling_variants_to_the_same_components() {
+        let abbreviated = PostalAddress::parse("123 W. MAIN ST STE 4");
+        let spelled_out = PostalAddress::parse("123 West Main Street, Suite 4");
+
+        assert_eq!(abbreviated, spelled_out);
+        assert_eq!(abbreviated.number.as_deref(), Some("123"));
+        assert_eq!(abbreviated.predirectional.as_deref(), Some("W"));
+        assert_eq!(abbreviated.street_name.as_deref(), Some("MAIN"));
+        assert_eq!(abbreviated.suffix.as_deref(), Some("ST"));
+        assert_eq!(abbreviated.unit.as_deref(), Some("STE 4"));
+        assert_eq!(abbreviated.street_line(), "123 W MAIN ST STE 4");
+        assert_eq!(abbreviated.display_street_line(), "123 W Main St Ste 4");
+    }
+
+    #[test]
+    fn parses_comma_joined_lines_with_locality() {
+        let address = PostalAddress::parse("5520 E 2nd Street #B, Long Beach, CA 90803-1234");
+
+        assert_eq!(address.street_line(), "5520 E 2ND ST # B");
+        assert_eq!(address.display_city().as_deref(), Some("Long Beach"));
+        assert_eq!(address.state.as_deref(), Some("CA"));
+        assert_eq!(address.postal_code().as_deref(), Some("90803-1234"));
+    }
+
+    #[test]
+    fn keeps_directional_and_suffix_words_that_name_the_street() {
+        assert_eq!(
+            PostalAddress::parse("400 South St").street_line(),
+            "400 SOUTH ST"
+        );
+        assert_eq!(
+            PostalAddress::parse("20 Main Ct 90012").street_line(),
+            "20 MAIN CT"
+        );
+
+        let parts = PostalAddress::from_parts("1 Pine Avenue North", "long beach", "CA", "90802");
+        assert_eq!(parts.street_line(), "1 PINE AVE N");
+        assert_eq!(parts.city.as_deref(), Some("LONG BEACH"));
+        assert_eq!(parts.zip.as_deref(), Some("90802"));
+        assert_eq!(standard_abbreviation("Boulevard"), Some("BLVD"));
+    }
+}
@@ -1,7 +1,9 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Jurisdiction {
     LosAngelesCounty,
     SanDiegoCounty,
@@ -13,6 +15,16 @@ pub enum Jurisdiction {
 }
 
 impl Jurisdiction {
+    pub const ALL: [Self; 7] = [
+        Self::LosAngelesCounty,
+        Self::SanDiegoCounty,
+        Self::LongBeach,
+        Self::RiversideCounty,
+        Self::SanBernardinoCounty,
+        Self::OrangeCounty,
+        Self::Pasadena,
+    ];
+
     pub fn code(&self) -> &'static str {
         match self {
             Self::LosAngelesCounty => "lac",
@@ -37,6 +49,19 @@ impl Jurisdiction {
         }
     }
 
+    /// A central point used when a source gives no usable location.
+    pub fn default_coordinates(&self) -> (f64, f64) {
+        match self {
+            Self::LosAngelesCounty => (34.0522, -118.2437),
+            Self::SanDiegoCounty => (32.7157, -117.1611),
+            Self::LongBeach => (33.7701, -118.1937),
+            Self::RiversideCounty => (33.9806, -117.3755),
+            Self::SanBernardinoCounty => (34.1083, -117.2898),
+            Self::OrangeCounty => (33.7175, -117.8311),
+            Self::Pasadena => (34.1478, -118.1445),
+        }
+    }
+
     pub fn from_code(code: &str) -> Option<Self> {
         match code {
             "lac" => Some(Self::LosAngelesCounty),
@@ -62,7 +87,14 @@ pub struct Violation {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Inspection {
     pub inspection_id: String,
-    pub inspected_at: DateTime<Utc>,
+    /// `None` when the source gave no usable date.
+    pub inspected_at: Option<DateTime<Utc>>,
+    /// Whether the source gave a time of day or only a date.
+    #[serde(default)]
+    pub inspected_at_precision: Option<DatePrecision>,
+    /// The kind of visit when the source says, such as LIVES `routine` or `followup`.
+    #[serde(default)]
+    pub inspection_type: Option<String>,
     pub raw_score: Option<f32>,
     pub letter_grade: Option<String>,
     pub placard_status: Option<String>,
@@ -80,17 +112,204 @@ pub struct Facility {
     pub postal_code: String,
     pub latitude: f64,
     pub longitude: f64,
+    pub location_precision: LocationPrecision,
     pub jurisdiction: Jurisdiction,
     pub trust_score: u8,
     pub inspections: Vec<Inspection>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[cfg(test)]
+impl Facility {
+    /// An uninspected rooftop-located Los Angeles facility for tests to override field
+    /// by field.
+    pub fn sample(id: &str) -> Self {
+        Self {
+            id: id.to_owned(),
+            source_id: id.to_owned(),
+            name: format!("Cafe {id}"),
+            address: "1 Main St".to_owned(),
+            city: "Los Angeles".to_owned(),
+            state: "CA".to_owned(),
+            postal_code: "90012".to_owned(),
+            latitude: 34.0522,
+            longitude: -118.2437,
+            location_precision: LocationPrecision::Rooftop,
+            jurisdiction: Jurisdiction::LosAngelesCounty,
+            trust_score: 90,
+            inspections: Vec::new(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Whether a source date carried a time of day. Date-only values are stored as local
+/// midnight in Pacific time.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DatePrecision {
+    Date,
+    Timestamp,
+}
+
+/// How closely a facility's coordinates locate the premises.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationPrecision {
+    /// Coordinates published by the source for the premises itself.
+    Rooftop,
+    Parcel,
+    ZipCentroid,
+    CityCentroid,
+    /// A fixed fallback point for the whole jurisdiction.
+    JurisdictionDefault,
+}
+
+impl LocationPrecision {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Rooftop => "rooftop",
+            Self::Parcel => "parcel",
+            Self::ZipCentroid => "zip_centroid",
+            Self::CityCentroid => "city_centroid",
+            Self::JurisdictionDefault => "jurisdiction_default",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "rooftop" => Some(Self::Rooftop),
+            "parcel" => Some(Self::Parcel),
+            "zip_centroid" => Some(Self::ZipCentroid),
+            "city_centroid" => Some(Self::CityCentroid),
+            "jurisdiction_default" => Some(Self::JurisdictionDefault),
+            _ => None,
+        }
+    }
+
+    /// Whether the point is on or next to the premises.
+    pub fn is_precise(&self) -> bool {
+        matches!(self, Self::Rooftop | Self::Parcel)
+    }
+
+    /// Whether the point only stands in for a whole city or jurisdiction, and so says
+    /// nothing about distance.
+    pub fn is_placeholder(&self) -> bool {
+        matches!(self, Self::CityCentroid | Self::JurisdictionDefault)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct ConnectorIngestionStatus {
     pub source: String,
     pub fetched_records: usize,
     pub error: Option<String>,
+    /// True when this run failed, wholly or for some of the source's jurisdictions, and
+    /// the source's previously published facilities there were kept.
+    #[serde(default)]
+    pub stale: bool,
+    #[serde(default)]
+    pub last_success_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub validation: ValidationCounts,
+}
+
+/// Why a fetched record was repaired or rejected by record validation.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationReason {
+    MissingSourceId,
+    MissingName,
+    InvalidCoordinates,
+    /// The connector substituted "Unknown Facility" for a missing name.
+    NameDefaulted,
+    /// The connector substituted a city or jurisdiction fallback point.
+    CoordinatesDefaulted,
+    /// The source gave no usable inspection date; the inspection is kept undated.
+    #[serde(alias = "inspection_date_defaulted")]
+    InspectionDateMissing,
+    /// The score was outside 0-100 and was dropped.
+    ScoreOutOfRange,
+}
+
+impl ValidationReason {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingSourceId => "missing_source_id",
+            Self::MissingName => "missing_name",
+            Self::InvalidCoordinates => "invalid_coordinates",
+            Self::NameDefaulted => "name_defaulted",
+            Self::CoordinatesDefaulted => "coordinates_defaulted",
+            Self::InspectionDateMissing => "inspection_date_missing",
+            Self::ScoreOutOfRange => "score_out_of_range",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "missing_source_id" => Some(Self::MissingSourceId),
+            "missing_name" => Some(Self::MissingName),
+            "invalid_coordinates" => Some(Self::InvalidCoordinates),
+            "name_defaulted" => Some(Self::NameDefaulted),
+            "coordinates_defaulted" => Some(Self::CoordinatesDefaulted),
+            // Earlier releases substituted the fetch time and recorded it under this code.
+            "inspection_date_missing" | "inspection_date_defaulted" => {
+                Some(Self::InspectionDateMissing)
+            }
+            "score_out_of_range" => Some(Self::ScoreOutOfRange),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordDisposition {
+    Valid,
+    Repaired,
+    Rejected,
+}
+
+impl RecordDisposition {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Repaired => "repaired",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "valid" => Some(Self::Valid),
+            "repaired" => Some(Self::Repaired),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// Per-connector tally of record validation for one run.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ValidationCounts {
+    pub valid: usize,
+    pub repaired: usize,
+    pub rejected: usize,
+    pub reasons: BTreeMap<ValidationReason, usize>,
+}
+
+/// A repaired or rejected record kept for inspection, with the record as the
+/// connector produced it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeadLetterRecord {
+    pub id: String,
+    pub run_id: String,
+    pub source: String,
+    pub source_id: Option<String>,
+    pub disposition: RecordDisposition,
+    pub reasons: Vec<ValidationReason>,
+    pub payload: serde_json::Value,
+    pub recorded_at: DateTime<Utc>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -100,6 +319,369 @@ pub struct SystemIngestionStatus {
     pub connector_stats: Vec<ConnectorIngestionStatus>,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestionTrigger {
+    Startup,
+    Scheduler,
+    ApiRefresh,
+    RefreshOnce,
+    Replay,
+}
+
+impl IngestionTrigger {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Startup => "startup",
+            Self::Scheduler => "scheduler",
+            Self::ApiRefresh => "api_refresh",
+            Self::RefreshOnce => "refresh_once",
+            Self::Replay => "replay",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "startup" => Some(Self::Startup),
+            "scheduler" => Some(Self::Scheduler),
+            "api_refresh" => Some(Self::ApiRefresh),
+            "refresh_once" => Some(Self::RefreshOnce),
+            "replay" => Some(Self::Replay),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestionRunOutcome {
+    Running,
+    Published,
+    RejectedByShrinkGuard,
+    Failed,
+    /// The process exited before the run finished.
+    Abandoned,
+}
+
+impl IngestionRunOutcome {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Published => "published",
+            Self::RejectedByShrinkGuard => "rejected_by_shrink_guard",
+            Self::Failed => "failed",
+            Self::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "running" => Some(Self::Running),
+            "published" => Some(Self::Published),
+            "rejected_by_shrink_guard" => Some(Self::RejectedByShrinkGuard),
+            "failed" => Some(Self::Failed),
+            "abandoned" => Some(Self::Abandoned),
+            _ => None,
+        }
+    }
+}
+
+/// Audit record for a single ingestion refresh, kept for every run regardless of outcome.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IngestionRun {
+    pub id: String,
+    pub trigger: IngestionTrigger,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub outcome: IngestionRunOutcome,
+    pub unique_facilities: Option<usize>,
+    pub connector_stats: Vec<ConnectorIngestionStatus>,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    #[default]
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "closed" => Some(Self::Closed),
+            "open" => Some(Self::Open),
+            "half_open" => Some(Self::HalfOpen),
+            _ => None,
+        }
+    }
+}
+
+/// What an incrementally syncing connector keeps between runs: how far it has read
+/// and a mirror of every source row as of that point, so a pull of changed rows can
+/// still publish the whole dataset.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectorSyncState {
+    pub source: String,
+    /// Connector-defined high-water mark, such as the newest Socrata `:updated_at`.
+    pub watermark: Option<String>,
+    /// Mirrored source rows, in the connector's own format.
+    pub rows: serde_json::Value,
+    /// When the mirror was last rebuilt from a full pull.
+    pub full_sync_at: DateTime<Utc>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Circuit breaker state for one connector source, persisted across ingestion runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectorCircuit {
+    pub source: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl ConnectorCircuit {
+    pub fn closed(source: &str) -> Self {
+        Self {
+            source: source.to_owned(),
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            last_failure_at: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Which canonical facility a connector's source record was resolved to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SourceRecordLink {
+    pub source: String,
+    pub source_id: String,
+    pub canonical_id: String,
+    pub jurisdiction: Jurisdiction,
+    /// File within a multi-file export the record was read from.
+    #[serde(default)]
+    pub source_file: Option<String>,
+    pub resolved_at: DateTime<Utc>,
+}
+
+/// How well an address matched the gazetteer.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GeocodeMatch {
+    /// The house number, street and ZIP or city were all found.
+    Exact,
+    /// Placed between the nearest house numbers on the same side of the street.
+    Interpolated,
+    /// Only the ZIP was found; the point is the average of its addresses.
+    ZipCentroid,
+    Unmatched,
+}
+
+impl GeocodeMatch {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Interpolated => "interpolated",
+            Self::ZipCentroid => "zip_centroid",
+            Self::Unmatched => "unmatched",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "exact" => Some(Self::Exact),
+            "interpolated" => Some(Self::Interpolated),
+            "zip_centroid" => Some(Self::ZipCentroid),
+            "unmatched" => Some(Self::Unmatched),
+            _ => None,
+        }
+    }
+
+    pub fn location_precision(&self) -> Option<LocationPrecision> {
+        match self {
+            Self::Exact => Some(LocationPrecision::Rooftop),
+            Self::Interpolated => Some(LocationPrecision::Parcel),
+            Self::ZipCentroid => Some(LocationPrecision::ZipCentroid),
+            Self::Unmatched => None,
+        }
+    }
+}
+
+/// A cached geocoding result, keyed by normalized address. Misses are cached too, so
+/// an address is only looked up again once the gazetteer changes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GeocodeCacheEntry {
+    pub address_key: String,
+    /// Fingerprint of the gazetteer files the result came from.
+    pub gazetteer: String,
+    pub quality: GeocodeMatch,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// The gazetteer address the result was placed at, for exact matches.
+    pub matched_address: Option<String>,
+    pub geocoded_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FacilityEventKind {
+    FacilityAdded,
+    FacilityRemoved,
+    GradeDropped,
+    ClosureIssued,
+    Reopened,
+    TrustScoreChanged,
+}
+
+impl FacilityEventKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::FacilityAdded => "facility_added",
+            Self::FacilityRemoved => "facility_removed",
+            Self::GradeDropped => "grade_dropped",
+            Self::ClosureIssued => "closure_issued",
+            Self::Reopened => "reopened",
+            Self::TrustScoreChanged => "trust_score_changed",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "facility_added" => Some(Self::FacilityAdded),
+            "facility_removed" => Some(Self::FacilityRemoved),
+            "grade_dropped" => Some(Self::GradeDropped),
+            "closure_issued" => Some(Self::ClosureIssued),
+            "reopened" => Some(Self::Reopened),
+            "trust_score_changed" => Some(Self::TrustScoreChanged),
+            _ => None,
+        }
+    }
+}
+
+/// What changed for a facility between two published datasets.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FacilityChange {
+    FacilityAdded,
+    FacilityRemoved,
+    GradeDropped { from: String, to: String },
+    ClosureIssued { placard_status: Option<String> },
+    Reopened { placard_status: Option<String> },
+    TrustScoreChanged { from: u8, to: u8 },
+}
+
+impl FacilityChange {
+    pub fn kind(&self) -> FacilityEventKind {
+        match self {
+            Self::FacilityAdded => FacilityEventKind::FacilityAdded,
+            Self::FacilityRemoved => FacilityEventKind::FacilityRemoved,
+            Self::GradeDropped { .. } => FacilityEventKind::GradeDropped,
+            Self::ClosureIssued { .. } => FacilityEventKind::ClosureIssued,
+            Self::Reopened { .. } => FacilityEventKind::Reopened,
+            Self::TrustScoreChanged { .. } => FacilityEventKind::TrustScoreChanged,
+        }
+    }
+}
+
+/// Append-only record of a change detected by an ingestion run before it published.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FacilityEvent {
+    pub id: String,
+    pub run_id: String,
+    pub facility_id: String,
+    pub facility_name: String,
+    pub jurisdiction: Jurisdiction,
+    pub change: FacilityChange,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// A partner endpoint that receives facility events. Empty filters match everything;
+/// an event must pass every non-empty filter.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    /// HMAC-SHA256 key for delivery signatures. Only returned when the subscription
+    /// is created.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub event_types: Vec<FacilityEventKind>,
+    pub jurisdictions: Vec<Jurisdiction>,
+    pub facility_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookSubscription {
+    pub fn matches(&self, event: &FacilityEvent) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.change.kind()))
+            && (self.jurisdictions.is_empty() || self.jurisdictions.contains(&event.jurisdiction))
+            && (self.facility_ids.is_empty() || self.facility_ids.contains(&event.facility_id))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    /// Gave up after the last allowed attempt.
+    Failed,
+}
+
+impl WebhookDeliveryStatus {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "pending" => Some(Self::Pending),
+            "delivered" => Some(Self::Delivered),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// One event queued for one subscription in the webhook outbox. The row doubles as
+/// the delivery log entry.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub event_id: String,
+    pub event_type: FacilityEventKind,
+    /// Request body, fixed when the delivery is queued so retries send identical bytes.
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_response_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum VoteValue {
     Like,
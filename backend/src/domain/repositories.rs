@@ -1,14 +1,56 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
 use crate::domain::{
-    entities::{Facility, FacilityVoteSummary, SystemIngestionStatus, VoteValue},
+    entities::{
+        ConnectorCircuit, ConnectorSyncState, DeadLetterRecord, Facility, FacilityEvent,
+        FacilityEventKind, FacilityVoteSummary, GeocodeCacheEntry, IngestionRun, Jurisdiction,
+        RecordDisposition, SourceRecordLink, SystemIngestionStatus, ValidationReason, VoteValue,
+        WebhookDelivery, WebhookDeliveryStatus, WebhookSubscription,
+    },
     errors::RepositoryError,
 };
 
+/// Filters for listing dead-lettered records. `None` fields match everything.
+#[derive(Clone, Debug, Default)]
+pub struct DeadLetterQuery {
+    pub source: Option<String>,
+    pub run_id: Option<String>,
+    pub disposition: Option<RecordDisposition>,
+    pub reason: Option<ValidationReason>,
+    pub limit: usize,
+}
+
+/// Filters for listing facility events. `None` fields match everything.
+#[derive(Clone, Debug, Default)]
+pub struct FacilityEventQuery {
+    pub facility_id: Option<String>,
+    pub jurisdiction: Option<Jurisdiction>,
+    pub kind: Option<FacilityEventKind>,
+    /// Only events detected strictly after this instant.
+    pub since: Option<DateTime<Utc>>,
+    pub limit: usize,
+}
+
+/// Filters for the webhook delivery log. `None` fields match everything.
+#[derive(Clone, Debug, Default)]
+pub struct WebhookDeliveryQuery {
+    pub subscription_id: Option<String>,
+    pub event_id: Option<String>,
+    pub status: Option<WebhookDeliveryStatus>,
+    pub limit: usize,
+}
+
 #[async_trait]
 pub trait FacilityRepository: Send + Sync {
-    async fn replace_all(&self, facilities: Vec<Facility>) -> Result<(), RepositoryError>;
+    /// Deletes the facilities in `removed_ids` and stores `facilities`, leaving every
+    /// other stored facility untouched.
+    async fn replace_facilities(
+        &self,
+        removed_ids: &[String],
+        facilities: Vec<Facility>,
+    ) -> Result<(), RepositoryError>;
     async fn list(&self) -> Result<Vec<Facility>, RepositoryError>;
     async fn get_by_id(&self, id: &str) -> Result<Option<Facility>, RepositoryError>;
     async fn set_system_ingestion_status(
@@ -18,6 +60,97 @@ pub trait FacilityRepository: Send + Sync {
     async fn get_system_ingestion_status(
         &self,
     ) -> Result<Option<SystemIngestionStatus>, RepositoryError>;
+    /// Inserts or updates an ingestion run record, keyed by `IngestionRun::id`.
+    async fn record_ingestion_run(&self, run: IngestionRun) -> Result<(), RepositoryError>;
+    /// Marks every run still `running` as abandoned, finished at `finished_at` with
+    /// `message`. Returns how many were marked.
+    async fn abandon_running_ingestion_runs(
+        &self,
+        finished_at: DateTime<Utc>,
+        message: &str,
+    ) -> Result<usize, RepositoryError>;
+    /// Returns the most recent ingestion runs, newest first.
+    async fn list_ingestion_runs(&self, limit: usize)
+    -> Result<Vec<IngestionRun>, RepositoryError>;
+    async fn list_connector_circuits(&self) -> Result<Vec<ConnectorCircuit>, RepositoryError>;
+    /// Inserts or updates a connector's circuit breaker state, keyed by source.
+    async fn upsert_connector_circuit(
+        &self,
+        circuit: ConnectorCircuit,
+    ) -> Result<(), RepositoryError>;
+    async fn get_sync_state(
+        &self,
+        source: &str,
+    ) -> Result<Option<ConnectorSyncState>, RepositoryError>;
+    /// Inserts or replaces a connector's sync state, keyed by source.
+    async fn put_sync_state(&self, state: ConnectorSyncState) -> Result<(), RepositoryError>;
+    /// Replaces the dead-lettered records stored for `source` with those of its latest run.
+    async fn replace_dead_letters(
+        &self,
+        source: &str,
+        records: Vec<DeadLetterRecord>,
+    ) -> Result<(), RepositoryError>;
+    async fn list_dead_letters(
+        &self,
+        query: &DeadLetterQuery,
+    ) -> Result<Vec<DeadLetterRecord>, RepositoryError>;
+    /// Returns every link, or only those resolved to `canonical_id`.
+    async fn list_source_links(
+        &self,
+        canonical_id: Option<&str>,
+    ) -> Result<Vec<SourceRecordLink>, RepositoryError>;
+    /// Inserts or repoints links keyed by `(source, source_id)`.
+    async fn upsert_source_links(
+        &self,
+        links: Vec<SourceRecordLink>,
+    ) -> Result<(), RepositoryError>;
+    /// Returns the cached entries among `address_keys`, keyed by address.
+    async fn get_geocodes(
+        &self,
+        address_keys: &[String],
+    ) -> Result<HashMap<String, GeocodeCacheEntry>, RepositoryError>;
+    /// Inserts or replaces entries keyed by `address_key`.
+    async fn upsert_geocodes(&self, entries: Vec<GeocodeCacheEntry>)
+    -> Result<(), RepositoryError>;
+    /// Appends events; stored events are never updated or deleted.
+    async fn append_facility_events(
+        &self,
+        events: Vec<FacilityEvent>,
+    ) -> Result<(), RepositoryError>;
+    /// Returns matching events, newest first.
+    async fn list_facility_events(
+        &self,
+        query: &FacilityEventQuery,
+    ) -> Result<Vec<FacilityEvent>, RepositoryError>;
+    async fn create_webhook_subscription(
+        &self,
+        subscription: WebhookSubscription,
+    ) -> Result<(), RepositoryError>;
+    async fn list_webhook_subscriptions(&self)
+    -> Result<Vec<WebhookSubscription>, RepositoryError>;
+    /// Returns whether a subscription with `id` existed.
+    async fn delete_webhook_subscription(&self, id: &str) -> Result<bool, RepositoryError>;
+    async fn enqueue_webhook_deliveries(
+        &self,
+        deliveries: Vec<WebhookDelivery>,
+    ) -> Result<(), RepositoryError>;
+    /// Returns up to `limit` pending deliveries due at `now`, oldest first, and pushes
+    /// their `next_attempt_at` to `lease_until` so concurrent dispatchers skip them.
+    async fn claim_due_webhook_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError>;
+    async fn update_webhook_delivery(
+        &self,
+        delivery: WebhookDelivery,
+    ) -> Result<(), RepositoryError>;
+    /// Returns matching deliveries, newest first.
+    async fn list_webhook_deliveries(
+        &self,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError>;
     async fn upsert_facility_vote(
         &self,
         facility_id: &str,
@@ -0,0 +1,431 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{fs, sync::Mutex};
+use tracing::{info, warn};
+
+use crate::domain::entities::{
+    ConnectorIngestionStatus, IngestionRun, IngestionRunOutcome, IngestionTrigger,
+};
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Content-addressed store for raw connector responses.
+///
+/// Page bodies live once under `objects/<aa>/<sha256>`; each ingestion run writes
+/// `runs/<run_id>/manifest.json`, which lists the archived pages per source and page
+/// number next to that source's `ConnectorIngestionStatus`.
+pub struct PayloadArchive {
+    root: PathBuf,
+    retention: Duration,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedPage {
+    pub page: usize,
+    pub label: String,
+    pub sha256: String,
+    pub size_bytes: usize,
+    pub archived_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedSource {
+    pub source: String,
+    pub status: Option<ConnectorIngestionStatus>,
+    pub pages: Vec<ArchivedPage>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub run_id: String,
+    pub trigger: IngestionTrigger,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub outcome: IngestionRunOutcome,
+    pub sources: Vec<ArchivedSource>,
+}
+
+/// Archive handle for a single ingestion run. Cloned into every connector's fetch context.
+#[derive(Clone)]
+pub struct ArchiveRun {
+    archive: Arc<PayloadArchive>,
+    run_id: String,
+    pages: Arc<Mutex<Vec<(String, ArchivedPage)>>>,
+}
+
+/// Pages archived by a past run, keyed by source and request label, for replaying
+/// connectors without the network.
+pub struct ReplaySnapshot {
+    archive: Arc<PayloadArchive>,
+    run_id: String,
+    pages: HashMap<(String, String), String>,
+}
+
+impl PayloadArchive {
+    /// Returns `None` unless `CLEANPLATED_ARCHIVE_DIR` is set; archiving is opt-in.
+    pub fn from_env() -> Option<Self> {
+        let root = env::var("CLEANPLATED_ARCHIVE_DIR")
+            .ok()
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())?;
+        let retention_days = env::var("CLEANPLATED_ARCHIVE_RETENTION_DAYS")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+
+        Some(Self::new(root, Duration::days(retention_days)))
+    }
+
+    pub fn new(root: impl Into<PathBuf>, retention: Duration) -> Self {
+        Self {
+            root: root.into(),
+            retention,
+        }
+    }
+
+    pub fn start_run(self: &Arc<Self>, run_id: &str) -> ArchiveRun {
+        ArchiveRun {
+            archive: self.clone(),
+            run_id: run_id.to_owned(),
+            pages: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn object_path(&self, sha256: &str) -> PathBuf {
+        self.root.join("objects").join(&sha256[..2]).join(sha256)
+    }
+
+    pub fn manifest_path(&self, run_id: &str) -> PathBuf {
+        self.root.join("runs").join(run_id).join(MANIFEST_FILE)
+    }
+
+    pub async fn read_manifest(&self, run_id: &str) -> Result<ArchiveManifest> {
+        let path = self.manifest_path(run_id);
+        let raw = fs::read(&path)
+            .await
+            .with_context(|| format!("unable to read archive manifest {}", path.display()))?;
+        serde_json::from_slice(&raw)
+            .with_context(|| format!("unable to decode archive manifest {}", path.display()))
+    }
+
+    /// Loads the page index of a past run so its connectors can be replayed.
+    pub async fn load_snapshot(self: &Arc<Self>, run_id: &str) -> Result<ReplaySnapshot> {
+        let manifest = self.read_manifest(run_id).await?;
+        let pages = manifest
+            .sources
+            .into_iter()
+            .flat_map(|source| {
+                source
+                    .pages
+                    .into_iter()
+                    .map(move |page| ((source.source.clone(), page.label), page.sha256))
+            })
+            .collect::<HashMap<_, _>>();
+
+        Ok(ReplaySnapshot {
+            archive: self.clone(),
+            run_id: manifest.run_id,
+            pages,
+        })
+    }
+
+    /// Reads an archived object and checks it still matches its content hash.
+    pub async fn read_object(&self, sha256: &str) -> Result<Vec<u8>> {
+        let path = self.object_path(sha256);
+        let body = fs::read(&path)
+            .await
+            .with_context(|| format!("unable to read archived object {}", path.display()))?;
+        if hex::encode(Sha256::digest(&body)) != sha256 {
+            anyhow::bail!("archived object {} is corrupt", path.display());
+        }
+
+        Ok(body)
+    }
+
+    /// Deletes run manifests older than the retention window, then removes objects no
+    /// remaining manifest references. A run directory without a readable manifest, as a
+    /// crashed run can leave, expires by its modification time instead. Returns the
+    /// number of runs removed.
+    pub async fn apply_retention(&self) -> Result<usize> {
+        let runs_dir = self.root.join("runs");
+        if fs::metadata(&runs_dir).await.is_err() {
+            return Ok(0);
+        }
+
+        let cutoff = Utc::now() - self.retention;
+        let mut removed_runs = 0usize;
+        let mut referenced = HashSet::new();
+        let mut entries = fs::read_dir(&runs_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let run_id = entry.file_name().to_string_lossy().into_owned();
+            match self.read_manifest(&run_id).await {
+                Ok(manifest) if manifest.started_at >= cutoff => {
+                    referenced.extend(
+                        manifest
+                            .sources
+                            .into_iter()
+                            .flat_map(|source| source.pages)
+                            .map(|page| page.sha256),
+                    );
+                }
+                Ok(_) => {
+                    fs::remove_dir_all(entry.path()).await?;
+                    removed_runs += 1;
+                }
+                Err(error) => {
+                    let modified_at = entry
+                        .metadata()
+                        .await
+                        .ok()
+                        .filter(|metadata| metadata.is_dir())
+                        .and_then(|metadata| metadata.modified().ok())
+                        .map(DateTime::<Utc>::from);
+                    if modified_at.is_some_and(|modified_at| modified_at < cutoff) {
+                        fs::remove_dir_all(entry.path()).await?;
+                        removed_runs += 1;
+                    } else {
+                        warn!(run_id, error = %format!("{error:#}"), "Skipping unreadable archive run");
+                    }
+                }
+            }
+        }
+
+        let objects_dir = self.root.join("objects");
+        if fs::metadata(&objects_dir).await.is_ok() {
+            let mut buckets = fs::read_dir(&objects_dir).await?;
+            while let Some(bucket) = buckets.next_entry().await? {
+                let mut objects = fs::read_dir(bucket.path()).await?;
+                while let Some(object) = objects.next_entry().await? {
+                    let sha256 = object.file_name().to_string_lossy().into_owned();
+                    if !referenced.contains(&sha256) {
+                        fs::remove_file(object.path()).await?;
+                    }
+                }
+            }
+        }
+
+        if removed_runs > 0 {
+            info!(removed_runs, "Applied payload archive retention policy");
+        }
+
+        Ok(removed_runs)
+    }
+
+    async fn store_object(&self, body: &[u8]) -> Result<String> {
+        let sha256 = hex::encode(Sha256::digest(body));
+        let path = self.object_path(&sha256);
+        if fs::metadata(&path).await.is_ok() {
+            return Ok(sha256);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let staging = path.with_extension("partial");
+        fs::write(&staging, body).await?;
+        fs::rename(&staging, &path).await?;
+
+        Ok(sha256)
+    }
+}
+
+impl ArchiveRun {
+    /// Stores one raw response page for `source`. Pages are numbered per source in
+    /// the order they were archived.
+    pub async fn store_page(&self, source: &str, label: &str, body: &[u8]) -> Result<()> {
+        let sha256 = self.archive.store_object(body).await?;
+        let mut pages = self.pages.lock().await;
+        let page = pages
+            .iter()
+            .filter(|(current_source, _)| current_source == source)
+            .count();
+        pages.push((
+            source.to_owned(),
+            ArchivedPage {
+                page,
+                label: label.to_owned(),
+                sha256,
+                size_bytes: body.len(),
+                archived_at: Utc::now(),
+            },
+        ));
+
+        Ok(())
+    }
+
+    /// Writes the run manifest, linking every archived page to its source's status.
+    pub async fn finish(&self, run: &IngestionRun) -> Result<()> {
+        let pages = self.pages.lock().await.clone();
+        let mut sources = run
+            .connector_stats
+            .iter()
+            .map(|status| ArchivedSource {
+                source: status.source.clone(),
+                status: Some(status.clone()),
+                pages: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for (source, page) in pages {
+            match sources.iter_mut().find(|entry| entry.source == source) {
+                Some(entry) => entry.pages.push(page),
+                None => sources.push(ArchivedSource {
+                    source,
+                    status: None,
+                    pages: vec![page],
+                }),
+            }
+        }
+
+        let manifest = ArchiveManifest {
+            run_id: self.run_id.clone(),
+            trigger: run.trigger,
+            started_at: run.started_at,
+            finished_at: run.finished_at,
+            outcome: run.outcome,
+            sources,
+        };
+
+        let path = self.archive.manifest_path(&self.run_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(&manifest)?)
+            .await
+            .with_context(|| format!("unable to write archive manifest {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+impl ReplaySnapshot {
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Returns the body `source` archived under `label`. A request the original run
+    /// never made fails the replay instead of falling back to the network.
+    pub async fn read_page(&self, source: &str, label: &str) -> Result<Vec<u8>> {
+        let sha256 = self
+            .pages
+            .get(&(source.to_owned(), label.to_owned()))
+            .with_context(|| {
+                format!(
+                    "archive run {} has no page '{label}' for {source}",
+                    self.run_id
+                )
+            })?;
+        self.archive.read_object(sha256).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{Duration, Utc};
+
+    use super::PayloadArchive;
+    use crate::domain::entities::{
+        ConnectorIngestionStatus, IngestionRun, IngestionRunOutcome, IngestionTrigger,
+    };
+
+    #[tokio::test]
+    async fn archives_pages_by_content_and_replays_them_by_label() {
+        let root =
+            std::env::temp_dir().join(format!("cleanplated-archive-{}", uuid::Uuid::new_v4()));
+        let archive = Arc::new(PayloadArchive::new(&root, Duration::days(30)));
+        let run = archive.start_run("run-1");
+
+        run.store_page("san_diego_socrata", "offset=0", b"[{\"record_id\":\"1\"}]")
+            .await
+            .unwrap();
+        run.store_page(
+            "san_diego_socrata",
+            "offset=5000",
+            b"[{\"record_id\":\"1\"}]",
+        )
+        .await
+        .unwrap();
+        run.finish(&IngestionRun {
+            id: "run-1".to_owned(),
+            trigger: IngestionTrigger::RefreshOnce,
+            started_at: Utc::now() - Duration::days(1),
+            finished_at: Some(Utc::now()),
+            outcome: IngestionRunOutcome::Published,
+            unique_facilities: Some(1),
+            connector_stats: vec![ConnectorIngestionStatus {
+                source: "san_diego_socrata".to_owned(),
+                fetched_records: 1,
+                ..ConnectorIngestionStatus::default()
+            }],
+            message: None,
+        })
+        .await
+        .unwrap();
+
+        let snapshot = archive.load_snapshot("run-1").await.unwrap();
+        assert_eq!(
+            snapshot
+                .read_page("san_diego_socrata", "offset=5000")
+                .await
+                .unwrap(),
+            b"[{\"record_id\":\"1\"}]"
+        );
+        assert!(
+            snapshot
+                .read_page("san_diego_socrata", "offset=10000")
+                .await
+                .is_err()
+        );
+
+        let manifest = archive.read_manifest("run-1").await.unwrap();
+        let source = &manifest.sources[0];
+        assert_eq!(source.status.as_ref().unwrap().fetched_records, 1);
+        assert_eq!(source.pages.len(), 2);
+        assert_eq!(source.pages[1].page, 1);
+        assert_eq!(source.pages[0].sha256, source.pages[1].sha256);
+        assert!(archive.object_path(&source.pages[0].sha256).exists());
+
+        let expired = PayloadArchive::new(&root, Duration::hours(1));
+        assert_eq!(expired.apply_retention().await.unwrap(), 1);
+        assert!(!archive.object_path(&source.pages[0].sha256).exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn expires_runs_without_a_manifest_by_directory_age() {
+        let root =
+            std::env::temp_dir().join(format!("cleanplated-archive-{}", uuid::Uuid::new_v4()));
+        let archive = PayloadArchive::new(&root, Duration::days(30));
+        let crashed = root.join("runs").join("crashed");
+        let running = root.join("runs").join("running");
+        for dir in [&crashed, &running] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let forty_days_ago =
+            std::time::SystemTime::now() - std::time::Duration::from_secs(40 * 24 * 3_600);
+        std::fs::File::open(&crashed)
+            .unwrap()
+            .set_modified(forty_days_ago)
+            .unwrap();
+
+        assert_eq!(archive.apply_retention().await.unwrap(), 1);
+        assert!(!crashed.exists());
+        assert!(running.exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
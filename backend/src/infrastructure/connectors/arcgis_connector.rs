@@ -0,0 +1,558 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tracing::warn;
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::Jurisdiction,
+    infrastructure::connectors::{
+        FetchContext, HealthDataConnector, ResponseStatusExt, RetryPolicy,
+        config::{
+            ConnectorSettings, check_positive, check_source_name, check_url, parse_jurisdiction,
+        },
+        mapping::{FieldMap, RecordMapper, attr_text},
+        retry::RetrySettings,
+    },
+};
+
+const DEFAULT_PAGE_SIZE: usize = 1_000;
+const DEFAULT_TIMEOUT_SECS: u64 = 20;
+/// Spatial reference requested for geometry, so point `x`/`y` are WGS 84 lon/lat.
+const WGS84_WKID: &str = "4326";
+
+/// A source declared in the connectors file as `type = "arcgis"`: one FeatureServer
+/// layer, optionally joined to other layers, mapped onto facility inputs by config.
+pub struct ArcGisConnector {
+    source_name: String,
+    client: Client,
+    source: ArcGisSource,
+    retry_policy: RetryPolicy,
+}
+
+/// `[connectors.<name>]` keys for `type = "arcgis"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArcGisSettings {
+    /// Source name for statuses and archives; defaults to the entry name.
+    pub source: Option<String>,
+    /// Jurisdiction code (`lac`, `sbc`, ...) whose facilities this layer replaces.
+    pub jurisdiction: String,
+    /// Layer URL, ending in `/FeatureServer/<layer id>`.
+    pub layer_url: String,
+    #[serde(rename = "where")]
+    pub where_clause: String,
+    pub out_fields: String,
+    pub order_by: Option<String>,
+    /// Asks the layer for its match count first and pages only that far.
+    pub count_first: bool,
+    pub page_size: usize,
+    pub max_records: Option<usize>,
+    pub timeout_secs: u64,
+    /// Prefix for a generated `{prefix}-{row}` ID when a feature has no source ID.
+    pub id_prefix: Option<String>,
+    /// Name used, and recorded as a repair, when a feature has none. Without it a
+    /// nameless feature is left for record validation to reject.
+    pub default_name: Option<String>,
+    /// City used when a feature has none; defaults to the jurisdiction label.
+    pub default_city: Option<String>,
+    pub default_state: String,
+    /// Derives an A/B/C grade from the score when no grade field is mapped or set.
+    pub grade_from_score: bool,
+    pub fields: FieldMap,
+    pub joins: Vec<JoinSettings>,
+    pub retry: RetrySettings,
+}
+
+impl Default for ArcGisSettings {
+    fn default() -> Self {
+        Self {
+            source: None,
+            jurisdiction: String::new(),
+            layer_url: String::new(),
+            where_clause: "1=1".to_owned(),
+            out_fields: "*".to_owned(),
+            order_by: None,
+            count_first: false,
+            page_size: DEFAULT_PAGE_SIZE,
+            max_records: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            id_prefix: None,
+            default_name: None,
+            default_city: None,
+            default_state: "CA".to_owned(),
+            grade_from_score: false,
+            fields: FieldMap::default(),
+            joins: Vec::new(),
+            retry: RetrySettings::default(),
+        }
+    }
+}
+
+impl ConnectorSettings for ArcGisSettings {
+    /// Generic layers share only the `CLEANPLATED_ARCGIS_RETRY_*` variables; use
+    /// `${VAR}` references for anything else.
+    fn apply_env(&mut self) -> Result<()> {
+        self.retry.apply_env("CLEANPLATED_ARCGIS")
+    }
+
+    fn validate(&self) -> Result<()> {
+        parse_jurisdiction(&self.jurisdiction)?;
+        if let Some(source) = &self.source {
+            check_source_name("source", source)?;
+        }
+        check_url("layer_url", &self.layer_url)?;
+        check_positive("page_size", self.page_size as u64)?;
+        check_positive("timeout_secs", self.timeout_secs)?;
+        if self.fields.source_id.is_empty() && self.id_prefix.is_none() {
+            bail!("map `fields.source_id` or set `id_prefix`");
+        }
+        self.retry.validate()?;
+        for (index, join) in self.joins.iter().enumerate() {
+            let context = || format!("joins[{index}]");
+            check_url("layer_url", &join.layer_url).with_context(context)?;
+            if join.key.is_empty() || join.on.is_empty() {
+                bail!("joins[{index}]: `key` and `on` are required");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Another layer whose features enrich the primary layer's, matched where the joined
+/// feature's `key` field equals the primary feature's `on` field.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JoinSettings {
+    pub layer_url: String,
+    #[serde(rename = "where")]
+    pub where_clause: String,
+    pub out_fields: String,
+    pub key: String,
+    pub on: String,
+    /// Fails the fetch when the join layer fails; otherwise it is skipped with a warning.
+    pub required: bool,
+    /// Archive label for the join's pages; defaults to `join<n>`.
+    pub label: Option<String>,
+}
+
+impl Default for JoinSettings {
+    fn default() -> Self {
+        Self {
+            layer_url: String::new(),
+            where_clause: "1=1".to_owned(),
+            out_fields: "*".to_owned(),
+            key: String::new(),
+            on: String::new(),
+            required: false,
+            label: None,
+        }
+    }
+}
+
+impl ArcGisConnector {
+    pub fn new(name: &str, settings: ArcGisSettings) -> Result<Self> {
+        let jurisdiction = parse_jurisdiction(&settings.jurisdiction)?;
+        let source_name = settings.source.clone().unwrap_or_else(|| name.to_owned());
+        check_source_name("source", &source_name)?;
+
+        Ok(Self {
+            source_name,
+            client: http_client(settings.timeout_secs),
+            retry_policy: settings.retry.policy(),
+            source: ArcGisSource::new("features", jurisdiction, settings),
+        })
+    }
+}
+
+#[async_trait]
+impl HealthDataConnector for ArcGisConnector {
+    fn source_name(&self) -> &str {
+        &self.source_name
+    }
+
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        vec![self.source.jurisdiction.clone()]
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
+        self.source.fetch(&self.client, context).await
+    }
+}
+
+pub(super) fn http_client(timeout_secs: u64) -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+/// A mapped layer and its joins. Built-in ArcGIS connectors declare their layers with
+/// the same settings a connectors-file entry uses.
+pub(super) struct ArcGisSource {
+    label: String,
+    jurisdiction: Jurisdiction,
+    settings: ArcGisSettings,
+}
+
+impl ArcGisSource {
+    /// `label` prefixes the archive label of every primary-layer page.
+    pub(super) fn new(label: &str, jurisdiction: Jurisdiction, settings: ArcGisSettings) -> Self {
+        Self {
+            label: label.to_owned(),
+            jurisdiction,
+            settings,
+        }
+    }
+
+    pub(super) fn jurisdiction(&self) -> &Jurisdiction {
+        &self.jurisdiction
+    }
+
+    pub(super) async fn fetch(
+        &self,
+        client: &Client,
+        context: &FetchContext,
+    ) -> Result<Vec<SourceFacilityInput>> {
+        let settings = &self.settings;
+        let features = LayerQuery {
+            layer_url: &settings.layer_url,
+            where_clause: &settings.where_clause,
+            out_fields: &settings.out_fields,
+            order_by: settings.order_by.as_deref(),
+            page_size: settings.page_size,
+            max_records: settings.max_records,
+            count_first: settings.count_first,
+        }
+        .fetch(client, context, &self.label)
+        .await
+        .with_context(|| format!("{} ArcGIS layer request failed", self.jurisdiction.label()))?;
+
+        let mut joins = Vec::with_capacity(settings.joins.len());
+        for (index, join) in settings.joins.iter().enumerate() {
+            let label = join
+                .label
+                .clone()
+                .unwrap_or_else(|| format!("join{}", index + 1));
+            let query = LayerQuery {
+                layer_url: &join.layer_url,
+                where_clause: &join.where_clause,
+                out_fields: &join.out_fields,
+                order_by: None,
+                page_size: settings.page_size,
+                max_records: None,
+                count_first: false,
+            };
+            let joined = match query.fetch(client, context, &label).await {
+                Ok(features) => features,
+                Err(error) if !join.required => {
+                    warn!(
+                        jurisdiction = self.jurisdiction.code(),
+                        join = %label,
+                        error = %format!("{error:#}"),
+                        "ArcGIS join failed; proceeding without it"
+                    );
+                    Vec::new()
+                }
+                Err(error) => {
+                    return Err(error.context(format!("ArcGIS join {label} request failed")));
+                }
+            };
+
+            let mut by_key = HashMap::with_capacity(joined.len());
+            for feature in joined {
+                if let Some(key) = feature.text(&join.key) {
+                    by_key.insert(key, feature);
+                }
+            }
+            joins.push((join, by_key));
+        }
+
+        Ok(features
+            .iter()
+            .enumerate()
+            .map(|(index, feature)| {
+                let mut records = vec![feature];
+                records.extend(joins.iter().filter_map(|(join, by_key)| {
+                    feature.text(&join.on).and_then(|key| by_key.get(&key))
+                }));
+                self.map_feature(index, &records)
+            })
+            .collect())
+    }
+
+    /// `records` holds the primary feature followed by its joined features.
+    fn map_feature(&self, index: usize, records: &[&Feature]) -> SourceFacilityInput {
+        let settings = &self.settings;
+        let attributes = records
+            .iter()
+            .map(|record| &record.attributes)
+            .collect::<Vec<_>>();
+        RecordMapper {
+            jurisdiction: &self.jurisdiction,
+            fields: &settings.fields,
+            id_prefix: settings.id_prefix.as_deref(),
+            default_name: settings.default_name.as_deref(),
+            default_city: settings.default_city.as_deref(),
+            default_state: &settings.default_state,
+            grade_from_score: settings.grade_from_score,
+        }
+        .map(
+            index,
+            &attributes,
+            records.iter().find_map(|record| record.point),
+        )
+    }
+}
+
+/// One ArcGIS feature: its attributes and, for point layers, its WGS 84 position.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Feature {
+    pub attributes: Map<String, Value>,
+    /// `(latitude, longitude)`.
+    pub point: Option<(f64, f64)>,
+}
+
+impl Feature {
+    fn from_json(feature: &Value) -> Option<Self> {
+        let attributes = feature.get("attributes")?.as_object()?.clone();
+        let point = feature.get("geometry").and_then(|geometry| {
+            let longitude = geometry.get("x")?.as_f64()?;
+            let latitude = geometry.get("y")?.as_f64()?;
+            Some((latitude, longitude))
+        });
+        Some(Self { attributes, point })
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        attr_text(&self.attributes, name)
+    }
+}
+
+/// A paged `query` request against one FeatureServer layer.
+pub(super) struct LayerQuery<'a> {
+    pub layer_url: &'a str,
+    pub where_clause: &'a str,
+    pub out_fields: &'a str,
+    pub order_by: Option<&'a str>,
+    pub page_size: usize,
+    pub max_records: Option<usize>,
+    /// Requests `returnCountOnly` first and stops paging once that many features are in.
+    pub count_first: bool,
+}
+
+impl LayerQuery<'_> {
+    /// Pages through the layer. Page bodies are archived as `{label} offset=<n>`, and
+    /// the count request as `{label} count`.
+    pub(super) async fn fetch(
+        &self,
+        client: &Client,
+        context: &FetchContext,
+        label: &str,
+    ) -> Result<Vec<Feature>> {
+        let endpoint = format!("{}/query", self.layer_url.trim_end_matches('/'));
+
+        let mut target = self.max_records.unwrap_or(usize::MAX);
+        if self.count_first {
+            let body = context
+                .fetch_text(&format!("{label} count"), || {
+                    get_text(
+                        client,
+                        &endpoint,
+                        vec![
+                            ("where", self.where_clause.to_owned()),
+                            ("returnCountOnly", "true".to_owned()),
+                            ("f", "json".to_owned()),
+                        ],
+                    )
+                })
+                .await?;
+            let response = parse_response(&body).context("ArcGIS count response is invalid")?;
+            let count = response
+                .get("count")
+                .and_then(Value::as_u64)
+                .context("ArcGIS count response has no `count`")?;
+            target = target.min(usize::try_from(count).unwrap_or(usize::MAX));
+        }
+
+        let mut features = Vec::new();
+        let mut offset = 0usize;
+        while offset < target {
+            let request_count = self.page_size.min(target - offset);
+            let mut query = vec![
+                ("where", self.where_clause.to_owned()),
+                ("outFields", self.out_fields.to_owned()),
+                ("returnGeometry", "true".to_owned()),
+                ("outSR", WGS84_WKID.to_owned()),
+                ("resultOffset", offset.to_string()),
+                ("resultRecordCount", request_count.to_string()),
+                ("f", "json".to_owned()),
+            ];
+            if let Some(order_by) = self.order_by {
+                query.push(("orderByFields", order_by.to_owned()));
+            }
+
+            let body = context
+                .fetch_text(&format!("{label} offset={offset}"), || {
+                    get_text(client, &endpoint, query.clone())
+                })
+                .await?;
+            let response = parse_response(&body)
+                .with_context(|| format!("ArcGIS page at offset {offset} is invalid"))?;
+            let page = response
+                .get("features")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if page.is_empty() {
+                break;
+            }
+
+            offset = offset.saturating_add(page.len());
+            features.extend(page.iter().filter_map(Feature::from_json));
+
+            // A server may cap pages below the requested size; it says so by setting
+            // `exceededTransferLimit`, so only a short page without it ends the layer.
+            let exceeded = response
+                .get("exceededTransferLimit")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            if !exceeded && page.len() < request_count {
+                break;
+            }
+        }
+
+        features.truncate(target);
+        Ok(features)
+    }
+}
+
+async fn get_text(client: &Client, endpoint: &str, query: Vec<(&str, String)>) -> Result<String> {
+    Ok(client
+        .get(endpoint)
+        .query(&query)
+        .send()
+        .await?
+        .ensure_success()?
+        .text()
+        .await?)
+}
+
+/// Parses a query response. ArcGIS reports query errors in a 200 response body.
+fn parse_response(body: &str) -> Result<Value> {
+    let response: Value = serde_json::from_str(body)?;
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        let details = error
+            .get("details")
+            .and_then(Value::as_array)
+            .map(|details| {
+                details
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            })
+            .unwrap_or_default();
+        bail!("ArcGIS error: {message} {details}");
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{ArcGisSettings, ArcGisSource, Feature, parse_response};
+    use crate::{
+        domain::entities::{Jurisdiction, LocationPrecision, ValidationReason},
+        infrastructure::connectors::mapping::FieldNames,
+    };
+
+    fn feature(value: Value) -> Feature {
+        Feature::from_json(&value).unwrap()
+    }
+
+    #[test]
+    fn maps_features_through_field_lists_joins_and_geometry() {
+        let mut settings: ArcGisSettings = toml::from_str(
+            r#"
+            jurisdiction = "sbc"
+            layer_url = "https://example.test/FeatureServer/0"
+            id_prefix = "sbc"
+            grade_from_score = true
+
+            [fields]
+            source_id = ["Facility_ID", "Permit_Number"]
+            name = "Facility_Name"
+            city = "City"
+            score = "Score"
+            inspected_at = "Inspection_Date"
+            "#,
+        )
+        .unwrap();
+        settings.fields.postal_code = FieldNames::of(&["ZIP"]);
+        let source = ArcGisSource::new("features", Jurisdiction::SanBernardinoCounty, settings);
+
+        let inspection = feature(json!({
+            "attributes": {
+                "Permit_Number": " PR-1 ",
+                "Facility_Name": "Taqueria",
+                "Score": 92,
+                "Inspection_Date": 1_720_099_800_000_i64
+            },
+            "geometry": { "x": -117.29, "y": 34.11 }
+        }));
+        let inventory = feature(json!({
+            "attributes": { "City": "Fontana", "ZIP": 92335, "Facility_Name": "Ignored" }
+        }));
+        let mapped = source.map_feature(0, &[&inspection, &inventory]);
+        assert_eq!(mapped.source_id, "PR-1");
+        assert_eq!(mapped.name, "Taqueria");
+        assert_eq!(mapped.city, "Fontana");
+        assert_eq!(mapped.postal_code, "92335");
+        assert_eq!(mapped.state, "CA");
+        assert_eq!((mapped.latitude, mapped.longitude), (34.11, -117.29));
+        assert_eq!(mapped.location_precision, LocationPrecision::Rooftop);
+        assert_eq!(mapped.letter_grade.as_deref(), Some("A"));
+        assert!(mapped.inspected_at.is_some());
+        assert!(mapped.repairs.is_empty());
+
+        let bare = feature(json!({ "attributes": { "Facility_Name": "Cart" } }));
+        let mapped = source.map_feature(7, &[&bare]);
+        assert_eq!(mapped.source_id, "sbc-7");
+        assert_eq!(mapped.city, "San Bernardino County");
+        assert_eq!(
+            (mapped.latitude, mapped.longitude),
+            Jurisdiction::SanBernardinoCounty.default_coordinates()
+        );
+        assert_eq!(
+            mapped.repairs,
+            vec![
+                ValidationReason::CoordinatesDefaulted,
+                ValidationReason::InspectionDateMissing
+            ]
+        );
+    }
+
+    #[test]
+    fn surfaces_errors_reported_in_successful_responses() {
+        let error = parse_response(
+            r#"{"error":{"code":400,"message":"Unable to complete operation.","details":["Invalid where clause"]}}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Invalid where clause"));
+        assert!(parse_response(r#"{"features":[]}"#).is_ok());
+    }
+}
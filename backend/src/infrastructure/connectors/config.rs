@@ -0,0 +1,458 @@
+use std::{collections::HashMap, env, fmt::Display, path::Path, str::FromStr, sync::Arc};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use toml::{Table, Value};
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::Jurisdiction,
+    infrastructure::connectors::{
+        ArcGisConnector, CpraConnector, DropFolderConnector, FetchContext, HealthDataConnector,
+        LaCountyConnector, LivesBatchConnector, LongBeachConnector, RetryPolicy, SanDiegoConnector,
+        SocrataConnector,
+    },
+};
+
+/// Connector types in the order they are built when no connectors file is given.
+const BUILT_IN_TYPES: &[&str] = &[
+    "la_county",
+    "san_diego",
+    "long_beach",
+    "lives_batch",
+    "cpra",
+];
+
+/// Connector types that may be declared any number of times, one source per entry.
+const GENERIC_TYPES: &[&str] = &["arcgis", "drop_folder", "socrata"];
+
+/// Settings for one connector type: deserialized from its connectors-file entry, then
+/// overridden by the type's `CLEANPLATED_*` environment variables.
+pub trait ConnectorSettings: DeserializeOwned + Default {
+    fn apply_env(&mut self) -> Result<()>;
+    fn validate(&self) -> Result<()>;
+}
+
+/// Builds the connectors declared in `path`, or every built-in connector configured
+/// from the environment alone when no file is given. Any invalid entry fails the
+/// whole load, so a typo never silently drops a source.
+pub fn load_connectors(path: Option<&Path>) -> Result<Vec<Arc<dyn HealthDataConnector>>> {
+    let Some(path) = path else {
+        let entries = BUILT_IN_TYPES
+            .iter()
+            .map(|kind| {
+                let mut entry = Table::new();
+                entry.insert("type".to_owned(), Value::String((*kind).to_owned()));
+                ((*kind).to_owned(), entry)
+            })
+            .collect();
+        return build_connectors(entries);
+    };
+
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read connectors file {}", path.display()))?;
+    parse_connectors(&text).with_context(|| format!("invalid connectors file {}", path.display()))
+}
+
+fn parse_connectors(text: &str) -> Result<Vec<Arc<dyn HealthDataConnector>>> {
+    let mut document = text.parse::<Table>()?;
+    if let Some(key) = document.keys().find(|key| *key != "connectors") {
+        bail!("unknown top-level key `{key}`; declare sources under [connectors.<name>]");
+    }
+    let Some(connectors) = document.remove("connectors") else {
+        bail!("no [connectors.<name>] entries");
+    };
+    let Value::Table(connectors) = connectors else {
+        bail!("`connectors` must be a table of [connectors.<name>] entries");
+    };
+
+    let entries = connectors
+        .into_iter()
+        .map(|(name, entry)| match entry {
+            Value::Table(entry) => Ok((name, entry)),
+            _ => bail!("connectors.{name}: expected a table"),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    build_connectors(entries)
+}
+
+fn build_connectors(entries: Vec<(String, Table)>) -> Result<Vec<Arc<dyn HealthDataConnector>>> {
+    let mut connectors = Vec::with_capacity(entries.len());
+    let mut types: HashMap<String, String> = HashMap::new();
+    let mut sources: HashMap<String, String> = HashMap::new();
+    for (name, mut entry) in entries {
+        let context = || format!("connectors.{name}");
+        let kind = match entry.remove("type") {
+            Some(Value::String(kind)) => kind,
+            Some(_) => bail!("connectors.{name}: `type` must be a string"),
+            None => bail!("connectors.{name}: missing `type`"),
+        };
+        let enabled = match entry.remove("enabled") {
+            Some(Value::Boolean(enabled)) => enabled,
+            Some(_) => bail!("connectors.{name}: `enabled` must be true or false"),
+            None => true,
+        };
+        let refresh_interval = match entry.remove("refresh_interval_hours") {
+            Some(Value::Integer(hours)) if hours > 0 => Some(std::time::Duration::from_secs(
+                u64::try_from(hours).unwrap_or(u64::MAX / 3_600) * 3_600,
+            )),
+            Some(_) => {
+                bail!("connectors.{name}: `refresh_interval_hours` must be a positive integer")
+            }
+            None => None,
+        };
+        if !enabled {
+            continue;
+        }
+        expand_env_references(&mut entry).with_context(context)?;
+        if !GENERIC_TYPES.contains(&kind.as_str())
+            && let Some(other) = types.insert(kind.clone(), name.clone())
+        {
+            bail!(
+                "connectors.{name}: type `{kind}` is already configured by connectors.{other}; \
+                 each built-in type serves fixed jurisdictions and may appear once"
+            );
+        }
+
+        let connector: Arc<dyn HealthDataConnector> = match kind.as_str() {
+            "la_county" => Arc::new(LaCountyConnector::new(
+                settings(entry).with_context(context)?,
+            )),
+            "san_diego" => Arc::new(SanDiegoConnector::new(
+                settings(entry).with_context(context)?,
+            )),
+            "long_beach" => Arc::new(LongBeachConnector::new(
+                settings(entry).with_context(context)?,
+            )),
+            "lives_batch" => Arc::new(LivesBatchConnector::new(
+                settings(entry).with_context(context)?,
+            )),
+            "cpra" => Arc::new(CpraConnector::new(settings(entry).with_context(context)?)),
+            "arcgis" => Arc::new(
+                settings(entry)
+                    .and_then(|settings| ArcGisConnector::new(&name, settings))
+                    .with_context(context)?,
+            ),
+            "drop_folder" => Arc::new(
+                settings(entry)
+                    .and_then(|settings| DropFolderConnector::new(&name, settings))
+                    .with_context(context)?,
+            ),
+            "socrata" => Arc::new(
+                settings(entry)
+                    .and_then(|settings| SocrataConnector::new(&name, settings))
+                    .with_context(context)?,
+            ),
+            other => bail!(
+                "connectors.{name}: unknown type `{other}`; expected one of {}, {}",
+                BUILT_IN_TYPES.join(", "),
+                GENERIC_TYPES.join(", ")
+            ),
+        };
+        if let Some(other) = sources.insert(connector.source_name().to_owned(), name.clone()) {
+            bail!(
+                "connectors.{name}: source `{}` is already declared by connectors.{other}",
+                connector.source_name()
+            );
+        }
+        connectors.push(match refresh_interval {
+            Some(interval) => Arc::new(ScheduledConnector {
+                inner: connector,
+                interval,
+            }),
+            None => connector,
+        });
+    }
+
+    if connectors.is_empty() {
+        bail!("no enabled connectors");
+    }
+    Ok(connectors)
+}
+
+/// Deserializes a connector's own keys, then applies environment overrides and
+/// validates the result.
+fn settings<S: ConnectorSettings>(entry: Table) -> Result<S> {
+    let mut settings = S::deserialize(Value::Table(entry))?;
+    settings.apply_env()?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+/// Replaces `${NAME}` in string values with the environment variable `NAME`, so
+/// credentials stay out of the file.
+fn expand_env_references(entry: &mut Table) -> Result<()> {
+    for (key, value) in entry.iter_mut() {
+        expand_value(value).with_context(|| format!("`{key}`"))?;
+    }
+    Ok(())
+}
+
+fn expand_value(value: &mut Value) -> Result<()> {
+    match value {
+        Value::String(text) => {
+            let mut expanded = String::with_capacity(text.len());
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${") {
+                let Some(length) = rest[start + 2..].find('}') else {
+                    bail!("unterminated `${{` in {text:?}");
+                };
+                let name = &rest[start + 2..start + 2 + length];
+                let resolved = env::var(name)
+                    .with_context(|| format!("environment variable {name} is not set"))?;
+                expanded.push_str(&rest[..start]);
+                expanded.push_str(&resolved);
+                rest = &rest[start + 3 + length..];
+            }
+            expanded.push_str(rest);
+            *text = expanded;
+        }
+        Value::Array(values) => {
+            for value in values {
+                expand_value(value)?;
+            }
+        }
+        Value::Table(table) => expand_env_references(table)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Wraps a connector declared with `refresh_interval_hours`.
+struct ScheduledConnector {
+    inner: Arc<dyn HealthDataConnector>,
+    interval: std::time::Duration,
+}
+
+#[async_trait]
+impl HealthDataConnector for ScheduledConnector {
+    fn source_name(&self) -> &str {
+        self.inner.source_name()
+    }
+
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        self.inner.jurisdictions()
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.inner.retry_policy()
+    }
+
+    fn refresh_interval(&self) -> Option<std::time::Duration> {
+        Some(self.interval)
+    }
+
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
+        self.inner.fetch_facilities(context).await
+    }
+}
+
+/// Reads a non-empty, trimmed environment variable.
+pub(super) fn env_string(key: &str) -> Option<String> {
+    env::var(key)
+        .ok()
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+/// Overrides `target` with the parsed value of `key` when it is set.
+pub(super) fn override_parsed<T>(target: &mut T, key: &str) -> Result<()>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Some(value) = env_string(key) {
+        *target = value
+            .parse()
+            .map_err(|error| anyhow::anyhow!("{key}={value:?} is invalid: {error}"))?;
+    }
+    Ok(())
+}
+
+/// Overrides an optional record cap; `0` removes the cap.
+pub(super) fn override_limit(target: &mut Option<usize>, key: &str) -> Result<()> {
+    let mut limit = target.unwrap_or_default();
+    override_parsed(&mut limit, key)?;
+    *target = (limit > 0).then_some(limit);
+    Ok(())
+}
+
+pub(super) fn override_bool(target: &mut bool, key: &str) -> Result<()> {
+    if let Some(value) = env_string(key) {
+        *target = match value.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" => false,
+            _ => bail!("{key}={value:?} is not a boolean"),
+        };
+    }
+    Ok(())
+}
+
+pub(super) fn check_url(field: &str, value: &str) -> Result<()> {
+    let url = Url::parse(value).with_context(|| format!("`{field}` is not a URL: {value:?}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("`{field}` must be an http(s) URL: {value:?}");
+    }
+    Ok(())
+}
+
+pub(super) fn parse_jurisdiction(code: &str) -> Result<Jurisdiction> {
+    Jurisdiction::from_code(code).with_context(|| {
+        let codes = Jurisdiction::ALL
+            .iter()
+            .map(Jurisdiction::code)
+            .collect::<Vec<_>>();
+        format!(
+            "`jurisdiction` must be one of {}, got {code:?}",
+            codes.join(", ")
+        )
+    })
+}
+
+/// Source names key statuses, circuits and archive directories.
+pub(super) fn check_source_name(field: &str, value: &str) -> Result<()> {
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if !valid {
+        bail!("`{field}` must be lowercase letters, digits, `_` or `-`: {value:?}");
+    }
+    Ok(())
+}
+
+pub(super) fn check_positive(field: &str, value: u64) -> Result<()> {
+    if value == 0 {
+        bail!("`{field}` must be at least 1");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_connectors;
+
+    #[test]
+    fn builds_enabled_connectors_with_schedules_and_env_references() {
+        let connectors = parse_connectors(
+            r#"
+            [connectors.la]
+            type = "la_county"
+            refresh_interval_hours = 12
+            page_size = 500
+            retry = { max_attempts = 5, base_delay_ms = 250 }
+
+            [connectors.sd]
+            type = "san_diego"
+            app_token = "${CARGO_PKG_NAME}"
+
+            [connectors.lb]
+            type = "long_beach"
+            enabled = false
+
+            [connectors.sbc_grades]
+            type = "arcgis"
+            jurisdiction = "sbc"
+            layer_url = "https://example.test/arcgis/rest/services/Grades/FeatureServer/0"
+            fields.source_id = "PERMIT"
+
+            [connectors.lb_inspections]
+            type = "socrata"
+            jurisdiction = "lb"
+            base_url = "https://data.example.test"
+            dataset_id = "abcd-1234"
+            incremental = true
+            fields.source_id = "permit_id"
+
+            [connectors.riv_grades]
+            type = "arcgis"
+            source = "riverside_grades"
+            jurisdiction = "riv"
+            layer_url = "https://example.test/arcgis/rest/services/Riverside/FeatureServer/2"
+            id_prefix = "riv"
+            "#,
+        )
+        .unwrap();
+
+        let sources = connectors
+            .iter()
+            .map(|connector| connector.source_name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![
+                "la_county_open_data",
+                "lb_inspections",
+                "riverside_grades",
+                "sbc_grades",
+                "san_diego_socrata"
+            ]
+        );
+        assert_eq!(
+            connectors[0].refresh_interval(),
+            Some(std::time::Duration::from_secs(12 * 3_600))
+        );
+        assert_eq!(connectors[2].refresh_interval(), None);
+        let retry = connectors[0].retry_policy();
+        assert_eq!(retry.max_attempts, 5);
+        assert_eq!(retry.base_delay, std::time::Duration::from_millis(250));
+    }
+
+    #[test]
+    fn rejects_invalid_entries_with_their_location() {
+        let error = |text: &str| format!("{:#}", parse_connectors(text).err().unwrap());
+
+        assert!(
+            error("[connectors.la]\ntype = \"la_county\"\npage_sise = 5\n")
+                .contains("connectors.la: unknown field `page_sise`")
+        );
+        assert!(
+            error("[connectors.x]\ntype = \"ftp\"\n").contains("connectors.x: unknown type `ftp`")
+        );
+        assert!(
+            error("[connectors.a]\ntype = \"cpra\"\n[connectors.b]\ntype = \"cpra\"\n")
+                .contains("already configured by connectors.a")
+        );
+        let arcgis = "type = \"arcgis\"\nlayer_url = \"https://example.test/FeatureServer/0\"\nid_prefix = \"x\"\n";
+        assert!(
+            error(&format!("[connectors.x]\n{arcgis}jurisdiction = \"nyc\"\n"))
+                .contains("connectors.x: `jurisdiction` must be one of lac, sdc")
+        );
+        assert!(
+            error(&format!(
+                "[connectors.la]\ntype = \"la_county\"\n[connectors.x]\n{arcgis}jurisdiction = \"lac\"\nsource = \"la_county_open_data\"\n"
+            ))
+            .contains("source `la_county_open_data` is already declared by connectors.la")
+        );
+        assert!(
+            error("[connectors.lb]\ntype = \"long_beach\"\nclosures_url = \"ftp://x\"\n")
+                .contains("`closures_url` must be an http(s) URL")
+        );
+        assert!(
+            error("[connectors.sd]\ntype = \"san_diego\"\napp_token = \"${CLEANPLATED_UNSET_TOKEN}\"\n")
+                .contains("environment variable CLEANPLATED_UNSET_TOKEN is not set")
+        );
+        assert!(
+            error("[connectors.cpra]\ntype = \"cpra\"\npasadena_export_profile = \"pas_2024\"\n")
+                .contains("`pasadena_export_profile` names undeclared profile \"pas_2024\"")
+        );
+        assert!(
+            error("[connectors.cpra]\ntype = \"cpra\"\n[connectors.cpra.profiles.pas]\nfields.city = \"City\"\n")
+                .contains("connectors.cpra: profiles.pas: `fields.name` must be mapped")
+        );
+        assert!(
+            error("[connectors.lb]\ntype = \"long_beach\"\nretry.max_attempts = 0\n")
+                .contains("connectors.lb: `retry.max_attempts` must be at least 1")
+        );
+        assert!(
+            error("[connectors.sd]\ntype = \"san_diego\"\nretry.attempts = 2\n")
+                .contains("unknown field `attempts`")
+        );
+        assert!(
+            error("[connectors.oc_drop]\ntype = \"drop_folder\"\njurisdiction = \"oc\"\n")
+                .contains("connectors.oc_drop: `directory` must not be empty")
+        );
+    }
+}
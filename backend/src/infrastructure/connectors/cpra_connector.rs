@@ -1,21 +1,30 @@
 use std::{
-    collections::HashSet,
     collections::hash_map::DefaultHasher,
-    env,
+    collections::{BTreeMap, HashSet},
     hash::{Hash, Hasher},
     time::Duration,
 };
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use reqwest::Client;
+use serde::Deserialize;
 use serde_json::{Map, Value, json};
 
 use crate::{
     application::dto::SourceFacilityInput,
-    domain::entities::{Jurisdiction, Violation},
-    infrastructure::connectors::HealthDataConnector,
+    domain::entities::{Jurisdiction, LocationPrecision, ValidationReason, Violation},
+    infrastructure::connectors::{
+        FetchContext, HealthDataConnector, ResponseStatusExt, RetryPolicy,
+        arcgis_connector::LayerQuery,
+        config::{
+            ConnectorSettings, check_positive, check_url, env_string, override_bool,
+            override_parsed,
+        },
+        cpra_profile::{ExportMapping, ExportOrigin, MappingProfile, validate_profiles},
+        dates::{self, SourceDate},
+        retry::RetrySettings,
+    },
 };
 
 const DEFAULT_TIMEOUT_SECS: u64 = 20;
@@ -39,6 +48,9 @@ pub struct CpraConnector {
     client: Client,
     orange_county_url: Option<String>,
     pasadena_url: Option<String>,
+    mapping: ExportMapping,
+    orange_county_profile: Option<String>,
+    pasadena_profile: Option<String>,
     oc_live_enabled: bool,
     oc_live_endpoint: String,
     oc_live_path: String,
@@ -51,75 +63,198 @@ pub struct CpraConnector {
     pasadena_directory_url: String,
     pasadena_page_size: usize,
     pasadena_max_records: usize,
+    retry_policy: RetryPolicy,
+}
+
+/// `[connectors.<name>]` keys for `type = "cpra"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CpraSettings {
+    /// CPRA export replacing the Orange County live feed when set.
+    pub orange_county_export_url: Option<String>,
+    /// CPRA export replacing the Pasadena directory when set.
+    pub pasadena_export_url: Option<String>,
+    /// Mapping profiles for export layouts, by name. An export uses its named profile,
+    /// else the first whose `matches` its URL, else auto-detected columns.
+    pub profiles: BTreeMap<String, MappingProfile>,
+    pub orange_county_export_profile: Option<String>,
+    pub pasadena_export_profile: Option<String>,
+    /// Worksheet read from Excel exports, matched case-insensitively; the first sheet
+    /// when unset. A profile's `sheet` takes precedence.
+    pub sheet: Option<String>,
+    /// Rows above the header row of Excel exports, such as a title block.
+    pub header_row_offset: usize,
+    pub timeout_secs: u64,
+    pub oc_live_enabled: bool,
+    pub oc_live_endpoint: String,
+    pub oc_live_path: String,
+    /// Terms the Orange County closure search is swept with; empty uses the default
+    /// sweep of `""`, `a`-`z` and `0`-`9`.
+    pub oc_live_search_terms: Vec<String>,
+    pub oc_live_page_size: usize,
+    pub oc_live_max_records: usize,
+    pub oc_live_per_term_max_records: usize,
+    pub oc_live_days_window: u32,
+    pub pasadena_live_enabled: bool,
+    pub pasadena_directory_url: String,
+    pub pasadena_page_size: usize,
+    pub pasadena_max_records: usize,
+    pub retry: RetrySettings,
+}
+
+impl Default for CpraSettings {
+    fn default() -> Self {
+        Self {
+            orange_county_export_url: None,
+            pasadena_export_url: None,
+            profiles: BTreeMap::new(),
+            orange_county_export_profile: None,
+            pasadena_export_profile: None,
+            sheet: None,
+            header_row_offset: 0,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            oc_live_enabled: true,
+            oc_live_endpoint: DEFAULT_OC_LIVE_ENDPOINT.to_owned(),
+            oc_live_path: DEFAULT_OC_LIVE_PATH.to_owned(),
+            oc_live_search_terms: Vec::new(),
+            oc_live_page_size: DEFAULT_OC_LIVE_PAGE_SIZE,
+            oc_live_max_records: DEFAULT_OC_LIVE_MAX_RECORDS,
+            oc_live_per_term_max_records: DEFAULT_OC_LIVE_PER_TERM_MAX_RECORDS,
+            oc_live_days_window: DEFAULT_OC_LIVE_DAYS_WINDOW,
+            pasadena_live_enabled: true,
+            pasadena_directory_url: DEFAULT_PASADENA_DIRECTORY_URL.to_owned(),
+            pasadena_page_size: DEFAULT_PASADENA_PAGE_SIZE,
+            pasadena_max_records: DEFAULT_PASADENA_MAX_RECORDS,
+            retry: RetrySettings::default(),
+        }
+    }
+}
+
+impl ConnectorSettings for CpraSettings {
+    fn apply_env(&mut self) -> Result<()> {
+        if let Some(url) = env_string("CLEANPLATED_OC_CPRA_EXPORT_URL") {
+            self.orange_county_export_url = Some(url);
+        }
+        if let Some(url) = env_string("CLEANPLATED_PASADENA_CPRA_EXPORT_URL") {
+            self.pasadena_export_url = Some(url);
+        }
+        if let Some(profile) = env_string("CLEANPLATED_OC_CPRA_EXPORT_PROFILE") {
+            self.orange_county_export_profile = Some(profile);
+        }
+        if let Some(profile) = env_string("CLEANPLATED_PASADENA_CPRA_EXPORT_PROFILE") {
+            self.pasadena_export_profile = Some(profile);
+        }
+        if let Some(sheet) = env_string("CLEANPLATED_CPRA_SHEET") {
+            self.sheet = Some(sheet);
+        }
+        override_parsed(
+            &mut self.header_row_offset,
+            "CLEANPLATED_CPRA_HEADER_ROW_OFFSET",
+        )?;
+        override_parsed(&mut self.timeout_secs, "CLEANPLATED_CPRA_TIMEOUT_SECS")?;
+        override_bool(&mut self.oc_live_enabled, "CLEANPLATED_OC_LIVE_ENABLED")?;
+        override_parsed(&mut self.oc_live_endpoint, "CLEANPLATED_OC_LIVE_ENDPOINT")?;
+        override_parsed(&mut self.oc_live_path, "CLEANPLATED_OC_LIVE_PATH")?;
+        if let Some(terms) = env_string("CLEANPLATED_OC_LIVE_SEARCH_TERMS") {
+            self.oc_live_search_terms = parse_search_terms(&terms);
+        }
+        override_parsed(&mut self.oc_live_page_size, "CLEANPLATED_OC_LIVE_PAGE_SIZE")?;
+        override_parsed(
+            &mut self.oc_live_max_records,
+            "CLEANPLATED_OC_LIVE_MAX_RECORDS",
+        )?;
+        override_parsed(
+            &mut self.oc_live_per_term_max_records,
+            "CLEANPLATED_OC_LIVE_PER_TERM_MAX_RECORDS",
+        )?;
+        override_parsed(
+            &mut self.oc_live_days_window,
+            "CLEANPLATED_OC_LIVE_DAYS_WINDOW",
+        )?;
+        override_bool(
+            &mut self.pasadena_live_enabled,
+            "CLEANPLATED_PASADENA_LIVE_ENABLED",
+        )?;
+        override_parsed(
+            &mut self.pasadena_directory_url,
+            "CLEANPLATED_PASADENA_DIRECTORY_URL",
+        )?;
+        override_parsed(
+            &mut self.pasadena_page_size,
+            "CLEANPLATED_PASADENA_PAGE_SIZE",
+        )?;
+        override_parsed(
+            &mut self.pasadena_max_records,
+            "CLEANPLATED_PASADENA_MAX_RECORDS",
+        )?;
+        self.retry.apply_env("CLEANPLATED_CPRA")
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(url) = &self.orange_county_export_url {
+            check_url("orange_county_export_url", url)?;
+        }
+        if let Some(url) = &self.pasadena_export_url {
+            check_url("pasadena_export_url", url)?;
+        }
+        validate_profiles(
+            &self.profiles,
+            &[
+                (
+                    "orange_county_export_profile",
+                    self.orange_county_export_profile.as_deref(),
+                ),
+                (
+                    "pasadena_export_profile",
+                    self.pasadena_export_profile.as_deref(),
+                ),
+            ],
+        )?;
+        check_url("oc_live_endpoint", &self.oc_live_endpoint)?;
+        check_url("pasadena_directory_url", &self.pasadena_directory_url)?;
+        check_positive("timeout_secs", self.timeout_secs)?;
+        check_positive("oc_live_page_size", self.oc_live_page_size as u64)?;
+        check_positive("pasadena_page_size", self.pasadena_page_size as u64)?;
+        self.retry.validate()
+    }
 }
 
 impl Default for CpraConnector {
     fn default() -> Self {
-        Self::from_env()
+        Self::new(CpraSettings::default())
     }
 }
 
 impl CpraConnector {
-    pub fn from_env() -> Self {
-        let orange_county_url = env::var("CLEANPLATED_OC_CPRA_EXPORT_URL")
-            .ok()
-            .map(|value| value.trim().to_owned())
-            .filter(|value| !value.is_empty());
-        let pasadena_url = env::var("CLEANPLATED_PASADENA_CPRA_EXPORT_URL")
-            .ok()
-            .map(|value| value.trim().to_owned())
-            .filter(|value| !value.is_empty());
-        let timeout_secs = env::var("CLEANPLATED_CPRA_TIMEOUT_SECS")
-            .ok()
-            .and_then(|value| value.parse::<u64>().ok())
-            .unwrap_or(DEFAULT_TIMEOUT_SECS);
-
-        let oc_live_enabled = parse_bool_env("CLEANPLATED_OC_LIVE_ENABLED", true);
-        let oc_live_endpoint = env::var("CLEANPLATED_OC_LIVE_ENDPOINT")
-            .ok()
-            .map(|value| value.trim().to_owned())
-            .filter(|value| !value.is_empty())
-            .unwrap_or_else(|| DEFAULT_OC_LIVE_ENDPOINT.to_owned());
-        let oc_live_path = env::var("CLEANPLATED_OC_LIVE_PATH")
-            .ok()
-            .map(|value| value.trim().to_owned())
-            .filter(|value| !value.is_empty())
-            .unwrap_or_else(|| DEFAULT_OC_LIVE_PATH.to_owned());
-        let oc_live_search_terms = parse_search_terms(
-            env::var("CLEANPLATED_OC_LIVE_SEARCH_TERMS").ok(),
-            default_orange_county_search_terms(),
-        );
-        let oc_live_page_size = env::var("CLEANPLATED_OC_LIVE_PAGE_SIZE")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_OC_LIVE_PAGE_SIZE);
-        let oc_live_max_records = env::var("CLEANPLATED_OC_LIVE_MAX_RECORDS")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_OC_LIVE_MAX_RECORDS);
-        let oc_live_per_term_max_records = env::var("CLEANPLATED_OC_LIVE_PER_TERM_MAX_RECORDS")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_OC_LIVE_PER_TERM_MAX_RECORDS);
-        let oc_live_days_window = env::var("CLEANPLATED_OC_LIVE_DAYS_WINDOW")
-            .ok()
-            .and_then(|value| value.parse::<u32>().ok())
-            .unwrap_or(DEFAULT_OC_LIVE_DAYS_WINDOW);
-
-        let pasadena_live_enabled = parse_bool_env("CLEANPLATED_PASADENA_LIVE_ENABLED", true);
-        let pasadena_directory_url = env::var("CLEANPLATED_PASADENA_DIRECTORY_URL")
-            .ok()
-            .map(|value| value.trim().to_owned())
-            .filter(|value| !value.is_empty())
-            .unwrap_or_else(|| DEFAULT_PASADENA_DIRECTORY_URL.to_owned());
-        let pasadena_page_size = env::var("CLEANPLATED_PASADENA_PAGE_SIZE")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_PASADENA_PAGE_SIZE);
-        let pasadena_max_records = env::var("CLEANPLATED_PASADENA_MAX_RECORDS")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_PASADENA_MAX_RECORDS);
+    pub fn new(settings: CpraSettings) -> Self {
+        let CpraSettings {
+            orange_county_export_url: orange_county_url,
+            pasadena_export_url: pasadena_url,
+            profiles,
+            orange_county_export_profile: orange_county_profile,
+            pasadena_export_profile: pasadena_profile,
+            sheet,
+            header_row_offset,
+            timeout_secs,
+            oc_live_enabled,
+            oc_live_endpoint,
+            oc_live_path,
+            oc_live_search_terms,
+            oc_live_page_size,
+            oc_live_max_records,
+            oc_live_per_term_max_records,
+            oc_live_days_window,
+            pasadena_live_enabled,
+            pasadena_directory_url,
+            pasadena_page_size,
+            pasadena_max_records,
+            retry,
+        } = settings;
+        let oc_live_search_terms = if oc_live_search_terms.is_empty() {
+            default_orange_county_search_terms()
+        } else {
+            oc_live_search_terms
+        };
 
         let client = Client::builder()
             .timeout(Duration::from_secs(timeout_secs))
@@ -132,6 +267,14 @@ impl CpraConnector {
             client,
             orange_county_url,
             pasadena_url,
+            mapping: ExportMapping {
+                source: "cpra_import_orange_pasadena".to_owned(),
+                profiles,
+                sheet,
+                header_row_offset,
+            },
+            orange_county_profile,
+            pasadena_profile,
             oc_live_enabled,
             oc_live_endpoint,
             oc_live_path,
@@ -144,59 +287,56 @@ impl CpraConnector {
             pasadena_directory_url,
             pasadena_page_size,
             pasadena_max_records,
+            retry_policy: retry.policy(),
         }
     }
 
     async fn fetch_export(
         &self,
+        context: &FetchContext,
         source_url: &str,
+        profile: Option<&str>,
         jurisdiction: Jurisdiction,
         id_prefix: &str,
     ) -> Result<Vec<SourceFacilityInput>> {
-        let response = self
-            .client
-            .get(source_url)
-            .send()
-            .await
-            .with_context(|| format!("{} CPRA export request failed", jurisdiction.label()))?
-            .error_for_status()
-            .with_context(|| {
-                format!(
-                    "{} CPRA export returned non-success status",
-                    jurisdiction.label()
-                )
-            })?;
-
-        let content_type = response
-            .headers()
-            .get(reqwest::header::CONTENT_TYPE)
-            .and_then(|value| value.to_str().ok())
-            .unwrap_or_default()
-            .to_ascii_lowercase();
-        let body = response
-            .text()
-            .await
-            .with_context(|| format!("{} CPRA export body read failed", jurisdiction.label()))?;
-
-        if body.trim().is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let records = if content_type.contains("json") || body.trim_start().starts_with(['{', '['])
-        {
-            parse_json_records(&body)?
-        } else {
-            parse_csv_records(&body)?
-        };
-
-        Ok(records
-            .into_iter()
-            .enumerate()
-            .filter_map(|(idx, record)| map_record(record, jurisdiction.clone(), id_prefix, idx))
-            .collect::<Vec<_>>())
+        let body = context
+            .fetch_bytes(&format!("{id_prefix} export"), || async {
+                self.client
+                    .get(source_url)
+                    .send()
+                    .await
+                    .with_context(|| {
+                        format!("{} CPRA export request failed", jurisdiction.label())
+                    })?
+                    .ensure_success()
+                    .with_context(|| {
+                        format!(
+                            "{} CPRA export returned non-success status",
+                            jurisdiction.label()
+                        )
+                    })?
+                    .bytes()
+                    .await
+                    .map(Vec::from)
+                    .with_context(|| {
+                        format!("{} CPRA export body read failed", jurisdiction.label())
+                    })
+            })
+            .await?;
+
+        self.mapping.map_export(
+            &body,
+            ExportOrigin::Url(source_url),
+            profile,
+            &jurisdiction,
+            id_prefix,
+        )
     }
 
-    async fn fetch_orange_county_live(&self) -> Result<Vec<SourceFacilityInput>> {
+    async fn fetch_orange_county_live(
+        &self,
+        context: &FetchContext,
+    ) -> Result<Vec<SourceFacilityInput>> {
         let page_size = self.oc_live_page_size.clamp(1, 250);
         let max_records = self.oc_live_max_records.max(1);
         let max_per_term = self.oc_live_per_term_max_records.max(page_size);
@@ -205,16 +345,20 @@ impl CpraConnector {
             self.oc_live_path
         );
 
-        let _ = self
-            .client
-            .get(&base_page_url)
-            .send()
-            .await
-            .with_context(|| {
-                format!("Orange County live bootstrap request failed: {base_page_url}")
-            })?
-            .error_for_status()
-            .context("Orange County live bootstrap returned non-success status")?;
+        // The bootstrap only primes the session; replays skip it since no search
+        // request reaches the network.
+        if !context.is_replay() {
+            let _ = self
+                .client
+                .get(&base_page_url)
+                .send()
+                .await
+                .with_context(|| {
+                    format!("Orange County live bootstrap request failed: {base_page_url}")
+                })?
+                .ensure_success()
+                .context("Orange County live bootstrap returned non-success status")?;
+        }
 
         let mut rows = Vec::new();
         let mut seen_inspections = HashSet::new();
@@ -247,32 +391,33 @@ impl CpraConnector {
                     "task": "searchInspections"
                 });
 
-                let response = self
-                    .client
-                    .post(&self.oc_live_endpoint)
-                    .header(reqwest::header::ACCEPT, "application/json, text/plain, */*")
-                    .header(reqwest::header::ORIGIN, "https://inspections.myhealthdepartment.com")
-                    .header(reqwest::header::REFERER, &base_page_url)
-                    .header("X-Requested-With", "XMLHttpRequest")
-                    .json(&payload)
-                    .send()
-                    .await
-                    .with_context(|| {
-                        format!(
-                            "Orange County live request failed (term='{term}', start={start})"
-                        )
-                    })?
-                    .error_for_status()
-                    .with_context(|| {
-                        format!(
-                            "Orange County live returned non-success status (term='{term}', start={start})"
-                        )
-                    })?;
-
-                let body = response
-                    .text()
-                    .await
-                    .context("Orange County live response body read failed")?;
+                let body = context
+                    .fetch_text(&format!("oc-live term={term} start={start}"), || async {
+                        self.client
+                            .post(&self.oc_live_endpoint)
+                            .header(reqwest::header::ACCEPT, "application/json, text/plain, */*")
+                            .header(reqwest::header::ORIGIN, "https://inspections.myhealthdepartment.com")
+                            .header(reqwest::header::REFERER, &base_page_url)
+                            .header("X-Requested-With", "XMLHttpRequest")
+                            .json(&payload)
+                            .send()
+                            .await
+                            .with_context(|| {
+                                format!(
+                                    "Orange County live request failed (term='{term}', start={start})"
+                                )
+                            })?
+                            .ensure_success()
+                            .with_context(|| {
+                                format!(
+                                    "Orange County live returned non-success status (term='{term}', start={start})"
+                                )
+                            })?
+                            .text()
+                            .await
+                            .context("Orange County live response body read failed")
+                    })
+                    .await?;
                 if body.trim().is_empty() {
                     break;
                 }
@@ -334,7 +479,7 @@ impl CpraConnector {
             );
         }
 
-        if let Ok(legacy_rows) = self.fetch_orange_county_live_closures_legacy().await {
+        if let Ok(legacy_rows) = self.fetch_orange_county_live_closures_legacy(context).await {
             for record in legacy_rows {
                 let source_id = rec_string(
                     &record,
@@ -369,105 +514,38 @@ impl CpraConnector {
         Ok(rows
             .into_iter()
             .enumerate()
-            .filter_map(|(idx, record)| {
-                map_record(record, Jurisdiction::OrangeCounty, "oc-live", idx)
-            })
+            .map(|(idx, record)| map_record(record, Jurisdiction::OrangeCounty, "oc-live", idx))
             .collect::<Vec<_>>())
     }
 
-    async fn fetch_pasadena_live_directory(&self) -> Result<Vec<SourceFacilityInput>> {
-        let base_url = self.pasadena_directory_url.trim_end_matches('/').to_owned();
-        let query_url = format!("{base_url}/query");
-        let page_size = self.pasadena_page_size.clamp(1, 1_000);
-        let max_records = self.pasadena_max_records.max(1);
-
-        let count_response: Value = self
-            .client
-            .get(&query_url)
-            .query(&[("where", "1=1"), ("returnCountOnly", "true"), ("f", "json")])
-            .send()
-            .await
-            .context("Pasadena directory count request failed")?
-            .error_for_status()
-            .context("Pasadena directory count request returned non-success status")?
-            .json()
-            .await
-            .context("Pasadena directory count JSON parse failed")?;
-
-        let total_count = count_response
-            .get("count")
-            .and_then(Value::as_u64)
-            .map(|value| value as usize)
-            .unwrap_or(0);
-
-        if total_count == 0 {
-            return Ok(Vec::new());
+    async fn fetch_pasadena_live_directory(
+        &self,
+        context: &FetchContext,
+    ) -> Result<Vec<SourceFacilityInput>> {
+        let features = LayerQuery {
+            layer_url: &self.pasadena_directory_url,
+            where_clause: "1=1",
+            out_fields: "*",
+            order_by: None,
+            page_size: self.pasadena_page_size.clamp(1, 1_000),
+            max_records: Some(self.pasadena_max_records.max(1)),
+            count_first: true,
         }
+        .fetch(&self.client, context, "pas-live")
+        .await
+        .context("Pasadena directory request failed")?;
 
-        let target = total_count.min(max_records);
-        let mut rows = Vec::new();
-        let mut offset = 0usize;
-
-        while offset < target {
-            let request_count = (target - offset).min(page_size);
-            let response: Value = self
-                .client
-                .get(&query_url)
-                .query(&[
-                    ("where", "1=1"),
-                    ("outFields", "*"),
-                    ("returnGeometry", "true"),
-                    ("f", "json"),
-                    ("resultOffset", &offset.to_string()),
-                    ("resultRecordCount", &request_count.to_string()),
-                ])
-                .send()
-                .await
-                .context("Pasadena directory page request failed")?
-                .error_for_status()
-                .context("Pasadena directory page request returned non-success status")?
-                .json()
-                .await
-                .context("Pasadena directory page JSON parse failed")?;
-
-            let Some(features) = response.get("features").and_then(Value::as_array) else {
-                break;
-            };
-
-            if features.is_empty() {
-                break;
-            }
-
-            for feature in features {
-                if rows.len() >= target {
-                    break;
-                }
-
-                let Some(mut attributes) = feature
-                    .get("attributes")
-                    .and_then(Value::as_object)
-                    .cloned()
-                else {
-                    continue;
-                };
-
-                if let Some(geometry) = feature.get("geometry").and_then(Value::as_object) {
-                    if let Some(latitude) = geometry.get("y").and_then(Value::as_f64) {
-                        attributes.insert("latitude".to_owned(), Value::from(latitude));
-                    }
-                    if let Some(longitude) = geometry.get("x").and_then(Value::as_f64) {
-                        attributes.insert("longitude".to_owned(), Value::from(longitude));
-                    }
+        let rows = features
+            .into_iter()
+            .map(|feature| {
+                let mut attributes = feature.attributes;
+                if let Some((latitude, longitude)) = feature.point {
+                    attributes.insert("latitude".to_owned(), Value::from(latitude));
+                    attributes.insert("longitude".to_owned(), Value::from(longitude));
                 }
-
-                rows.push(attributes);
-            }
-
-            if features.len() < request_count {
-                break;
-            }
-            offset += request_count;
-        }
+                attributes
+            })
+            .collect::<Vec<_>>();
 
         tracing::info!(
             source = "cpra_import_orange_pasadena",
@@ -479,11 +557,14 @@ impl CpraConnector {
         Ok(rows
             .into_iter()
             .enumerate()
-            .filter_map(|(idx, record)| map_record(record, Jurisdiction::Pasadena, "pas-live", idx))
+            .map(|(idx, record)| map_record(record, Jurisdiction::Pasadena, "pas-live", idx))
             .collect::<Vec<_>>())
     }
 
-    async fn fetch_orange_county_live_closures_legacy(&self) -> Result<Vec<Map<String, Value>>> {
+    async fn fetch_orange_county_live_closures_legacy(
+        &self,
+        context: &FetchContext,
+    ) -> Result<Vec<Map<String, Value>>> {
         let page_size = self.oc_live_page_size.clamp(1, 200);
         let max_records = self.oc_live_max_records.max(1);
         let inspection_purposes = [
@@ -519,31 +600,34 @@ impl CpraConnector {
                 "sort": "This_Form.inspectionDate|DESC",
             });
 
-            let response = self
-                .client
-                .post(DEFAULT_OC_LIVE_LEGACY_ENDPOINT)
-                .header(reqwest::header::ACCEPT, "application/json, text/plain, */*")
-                .header(
-                    reqwest::header::ORIGIN,
-                    "https://inspections.myhealthdepartment.com",
-                )
-                .header(reqwest::header::REFERER, DEFAULT_OC_LIVE_LEGACY_REFERER)
-                .header("X-Requested-With", "XMLHttpRequest")
-                .json(&payload)
-                .send()
-                .await
-                .with_context(|| {
-                    format!("Orange County legacy closures request failed (page={page})")
-                })?
-                .error_for_status()
-                .with_context(|| {
-                    format!("Orange County legacy closures non-success status (page={page})")
-                })?;
-
-            let body = response
-                .text()
-                .await
-                .context("Orange County legacy closures body read failed")?;
+            let body = context
+                .fetch_text(&format!("oc-legacy page={page}"), || async {
+                    self.client
+                        .post(DEFAULT_OC_LIVE_LEGACY_ENDPOINT)
+                        .header(reqwest::header::ACCEPT, "application/json, text/plain, */*")
+                        .header(
+                            reqwest::header::ORIGIN,
+                            "https://inspections.myhealthdepartment.com",
+                        )
+                        .header(reqwest::header::REFERER, DEFAULT_OC_LIVE_LEGACY_REFERER)
+                        .header("X-Requested-With", "XMLHttpRequest")
+                        .json(&payload)
+                        .send()
+                        .await
+                        .with_context(|| {
+                            format!("Orange County legacy closures request failed (page={page})")
+                        })?
+                        .ensure_success()
+                        .with_context(|| {
+                            format!(
+                                "Orange County legacy closures non-success status (page={page})"
+                            )
+                        })?
+                        .text()
+                        .await
+                        .context("Orange County legacy closures body read failed")
+                })
+                .await?;
             let parsed = parse_json_relaxed(&body)
                 .context("Orange County legacy closures JSON parse failed")?;
 
@@ -589,130 +673,104 @@ impl CpraConnector {
 
 #[async_trait]
 impl HealthDataConnector for CpraConnector {
-    fn source_name(&self) -> &'static str {
+    fn source_name(&self) -> &str {
         "cpra_import_orange_pasadena"
     }
 
-    async fn fetch_facilities(&self) -> Result<Vec<SourceFacilityInput>> {
-        let mut facilities = Vec::new();
-        let mut errors = Vec::new();
-        let mut source_enabled = false;
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        let mut jurisdictions = Vec::new();
+        if self.orange_county_url.is_some() || self.oc_live_enabled {
+            jurisdictions.push(Jurisdiction::OrangeCounty);
+        }
+        if self.pasadena_url.is_some() || self.pasadena_live_enabled {
+            jurisdictions.push(Jurisdiction::Pasadena);
+        }
+        jurisdictions
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
+        let mut fetched = Vec::new();
 
         if let Some(url) = &self.orange_county_url {
-            source_enabled = true;
-            match self
-                .fetch_export(url, Jurisdiction::OrangeCounty, "oc")
+            let result = self
+                .fetch_export(
+                    context,
+                    url,
+                    self.orange_county_profile.as_deref(),
+                    Jurisdiction::OrangeCounty,
+                    "oc",
+                )
                 .await
-            {
-                Ok(records) => facilities.extend(records),
-                Err(error) => errors.push(format!("Orange County CPRA export failed: {error:#}")),
-            }
+                .context("Orange County CPRA export failed");
+            fetched.push((Jurisdiction::OrangeCounty, result));
         } else if self.oc_live_enabled {
-            source_enabled = true;
-            match self.fetch_orange_county_live().await {
-                Ok(records) => facilities.extend(records),
-                Err(error) => errors.push(format!("Orange County live portal failed: {error:#}")),
-            }
+            let result = self
+                .fetch_orange_county_live(context)
+                .await
+                .context("Orange County live portal failed");
+            fetched.push((Jurisdiction::OrangeCounty, result));
         }
 
         if let Some(url) = &self.pasadena_url {
-            source_enabled = true;
-            match self.fetch_export(url, Jurisdiction::Pasadena, "pas").await {
-                Ok(records) => facilities.extend(records),
-                Err(error) => errors.push(format!("Pasadena CPRA export failed: {error:#}")),
-            }
+            let result = self
+                .fetch_export(
+                    context,
+                    url,
+                    self.pasadena_profile.as_deref(),
+                    Jurisdiction::Pasadena,
+                    "pas",
+                )
+                .await
+                .context("Pasadena CPRA export failed");
+            fetched.push((Jurisdiction::Pasadena, result));
         } else if self.pasadena_live_enabled {
-            source_enabled = true;
-            match self.fetch_pasadena_live_directory().await {
-                Ok(records) => facilities.extend(records),
-                Err(error) => errors.push(format!("Pasadena live directory failed: {error:#}")),
-            }
+            let result = self
+                .fetch_pasadena_live_directory(context)
+                .await
+                .context("Pasadena live directory failed");
+            fetched.push((Jurisdiction::Pasadena, result));
         }
 
-        if facilities.is_empty() {
-            if !source_enabled {
-                anyhow::bail!(
-                    "CPRA connector not configured and live fallbacks disabled (set CLEANPLATED_OC_CPRA_EXPORT_URL and/or CLEANPLATED_PASADENA_CPRA_EXPORT_URL)"
-                );
-            }
-            if errors.is_empty() {
-                anyhow::bail!("CPRA connector fetched zero records from all enabled sources");
-            }
-
+        if fetched.is_empty() {
             anyhow::bail!(
-                "CPRA connector failed across all enabled sources: {}",
-                errors.join(" | ")
+                "CPRA connector not configured and live fallbacks disabled (set CLEANPLATED_OC_CPRA_EXPORT_URL and/or CLEANPLATED_PASADENA_CPRA_EXPORT_URL)"
             );
         }
 
-        Ok(facilities)
-    }
-}
-
-fn parse_json_records(body: &str) -> Result<Vec<Map<String, Value>>> {
-    let value: Value = serde_json::from_str(body).context("JSON export parse failed")?;
-
-    let map_list = if let Some(array) = value.as_array() {
-        array
-            .iter()
-            .filter_map(|item| item.as_object().cloned())
-            .collect::<Vec<_>>()
-    } else if let Some(object) = value.as_object() {
-        if let Some(features) = object.get("features").and_then(Value::as_array) {
-            features
-                .iter()
-                .filter_map(|item| item.get("attributes"))
-                .filter_map(Value::as_object)
-                .cloned()
-                .collect::<Vec<_>>()
-        } else {
-            ["data", "results", "records", "value"]
-                .iter()
-                .find_map(|key| object.get(*key))
-                .and_then(Value::as_array)
-                .map(|array| {
-                    array
-                        .iter()
-                        .filter_map(Value::as_object)
-                        .cloned()
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default()
+        // A jurisdiction that failed or came back empty is reported on its own, so
+        // the refresh keeps its stored facilities while the other one is replaced.
+        let mut facilities = Vec::new();
+        let mut failures = Vec::new();
+        for (jurisdiction, result) in fetched {
+            match result.and_then(|records| {
+                anyhow::ensure!(!records.is_empty(), "fetched zero records");
+                Ok(records)
+            }) {
+                Ok(records) => facilities.extend(records),
+                Err(error) => failures.push((jurisdiction, error)),
+            }
         }
-    } else {
-        Vec::new()
-    };
 
-    Ok(map_list)
-}
-
-fn parse_csv_records(body: &str) -> Result<Vec<Map<String, Value>>> {
-    let mut reader = csv::ReaderBuilder::new()
-        .flexible(true)
-        .from_reader(body.as_bytes());
-
-    let headers = reader
-        .headers()
-        .context("CSV headers parse failed")?
-        .iter()
-        .map(str::to_owned)
-        .collect::<Vec<_>>();
-
-    let mut records = Vec::new();
-    for row in reader.records() {
-        let row = row.context("CSV row parse failed")?;
-        let mut map = Map::new();
-
-        for (idx, value) in row.iter().enumerate() {
-            if let Some(header) = headers.get(idx) {
-                map.insert(header.clone(), Value::String(value.trim().to_owned()));
-            }
+        if facilities.is_empty() {
+            anyhow::bail!(
+                "CPRA connector failed for every enabled source: {}",
+                failures
+                    .iter()
+                    .map(|(_, error)| format!("{error:#}"))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            );
+        }
+        for (jurisdiction, error) in failures {
+            context.fail_jurisdiction(jurisdiction, &error);
         }
 
-        records.push(map);
+        Ok(facilities)
     }
-
-    Ok(records)
 }
 
 fn parse_json_relaxed(body: &str) -> Result<Value> {
@@ -769,17 +827,12 @@ fn sanitize_json_control_chars(input: &str) -> String {
     output
 }
 
-fn parse_search_terms(raw: Option<String>, default_terms: Vec<String>) -> Vec<String> {
-    raw.map(|value| {
-        value
-            .split(',')
-            .map(str::trim)
-            .filter(|term| !term.is_empty())
-            .map(ToOwned::to_owned)
-            .collect::<Vec<_>>()
-    })
-    .filter(|terms| !terms.is_empty())
-    .unwrap_or(default_terms)
+fn parse_search_terms(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|term| !term.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
 }
 
 fn default_orange_county_search_terms() -> Vec<String> {
@@ -790,12 +843,16 @@ fn default_orange_county_search_terms() -> Vec<String> {
     terms
 }
 
-fn map_record(
+/// Maps one export row by auto-detecting its columns, for exports without a profile.
+/// A row without a recognizable name is still returned, with an empty name, so record
+/// validation rejects it visibly.
+pub(super) fn map_record(
     record: Map<String, Value>,
     jurisdiction: Jurisdiction,
     id_prefix: &str,
     row_index: usize,
-) -> Option<SourceFacilityInput> {
+) -> SourceFacilityInput {
+    let mut repairs = Vec::new();
     let name = rec_string(
         &record,
         &[
@@ -810,7 +867,8 @@ fn map_record(
             "CERS_Estab_LKPname",
             "Name_of_Restaurant_Cafe",
         ],
-    )?;
+    )
+    .unwrap_or_default();
     let address = rec_string(
         &record,
         &[
@@ -903,12 +961,19 @@ fn map_record(
         Jurisdiction::Pasadena => (34.1478, -118.1445),
         _ => (34.0522, -118.2437),
     };
-    let latitude = rec_f64(&record, &["latitude", "Latitude", "FACILITY_LATITUDE"])
-        .unwrap_or(default_coordinates.0);
-    let longitude = rec_f64(&record, &["longitude", "Longitude", "FACILITY_LONGITUDE"])
-        .unwrap_or(default_coordinates.1);
+    let ((latitude, longitude), location_precision) =
+        rec_f64(&record, &["latitude", "Latitude", "FACILITY_LATITUDE"])
+            .zip(rec_f64(
+                &record,
+                &["longitude", "Longitude", "FACILITY_LONGITUDE"],
+            ))
+            .map(|coordinates| (coordinates, LocationPrecision::Rooftop))
+            .unwrap_or_else(|| {
+                repairs.push(ValidationReason::CoordinatesDefaulted);
+                (default_coordinates, LocationPrecision::JurisdictionDefault)
+            });
 
-    let inspected_at = rec_datetime(
+    let inspection_date = rec_date(
         &record,
         &[
             "inspected_at",
@@ -920,8 +985,20 @@ fn map_record(
             "inspectionDate",
             "LastInspection",
         ],
-    )
-    .unwrap_or_else(Utc::now);
+    );
+    if inspection_date.is_none() {
+        repairs.push(ValidationReason::InspectionDateMissing);
+    }
+
+    let inspection_id = rec_string(
+        &record,
+        &[
+            "inspection_id",
+            "inspectionID",
+            "inspectionId",
+            "SERIAL_NUMBER",
+        ],
+    );
 
     let violations = rec_string(
         &record,
@@ -950,7 +1027,7 @@ fn map_record(
     })
     .unwrap_or_default();
 
-    Some(SourceFacilityInput {
+    SourceFacilityInput {
         source_id,
         name,
         address,
@@ -959,16 +1036,28 @@ fn map_record(
         postal_code,
         latitude,
         longitude,
+        location_precision,
         jurisdiction,
-        inspected_at,
+        inspection_id,
+        inspected_at: inspection_date.map(|date| date.at),
+        inspected_at_precision: inspection_date.map(|date| date.precision),
+        inspection_type: None,
         raw_score,
         letter_grade,
         placard_status,
         violations,
-    })
+        repairs,
+        source_file: None,
+    }
 }
 
-fn stable_id(prefix: &str, name: &str, address: &str, city: &str, row_index: usize) -> String {
+pub(super) fn stable_id(
+    prefix: &str,
+    name: &str,
+    address: &str,
+    city: &str,
+    row_index: usize,
+) -> String {
     let mut hasher = DefaultHasher::new();
     format!("{name}|{address}|{city}|{row_index}").hash(&mut hasher);
     format!("{prefix}-{:016x}", hasher.finish())
@@ -1017,69 +1106,71 @@ fn rec_bool(record: &Map<String, Value>, keys: &[&str]) -> Option<bool> {
     })
 }
 
-fn rec_datetime(record: &Map<String, Value>, keys: &[&str]) -> Option<DateTime<Utc>> {
-    let value = keys.iter().find_map(|key| record.get(*key))?;
-
-    if let Some(number) = value.as_i64() {
-        return timestamp_to_datetime(number);
-    }
-
-    let text = value.as_str()?.trim();
-    if text.is_empty() {
-        return None;
-    }
-
-    if let Ok(number) = text.parse::<i64>() {
-        return timestamp_to_datetime(number);
-    }
-
-    if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
-        return Some(parsed.with_timezone(&Utc));
-    }
-
-    if let Ok(parsed) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
-        return parsed
-            .and_hms_opt(0, 0, 0)
-            .map(|naive| DateTime::from_naive_utc_and_offset(naive, Utc));
-    }
-
-    if let Ok(parsed) = NaiveDate::parse_from_str(text, "%m/%d/%Y") {
-        return parsed
-            .and_hms_opt(0, 0, 0)
-            .map(|naive| DateTime::from_naive_utc_and_offset(naive, Utc));
-    }
-
-    if let Ok(parsed) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
-        return Some(DateTime::from_naive_utc_and_offset(parsed, Utc));
-    }
-
-    if let Ok(parsed) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f") {
-        return Some(DateTime::from_naive_utc_and_offset(parsed, Utc));
-    }
+fn rec_date(record: &Map<String, Value>, keys: &[&str]) -> Option<SourceDate> {
+    keys.iter()
+        .find_map(|key| record.get(*key))
+        .and_then(dates::from_json)
+}
 
-    if let Ok(parsed) = NaiveDateTime::parse_from_str(text, "%m/%d/%Y %H:%M:%S") {
-        return Some(DateTime::from_naive_utc_and_offset(parsed, Utc));
-    }
+#[cfg(test)]
+mod tests {
+    use axum::{Router, http::StatusCode, routing::get};
+    use tokio::net::TcpListener;
+
+    use super::{CpraConnector, CpraSettings};
+    use crate::domain::entities::Jurisdiction;
+    use crate::infrastructure::connectors::{FetchContext, HealthDataConnector, RetryPolicy};
+
+    #[tokio::test]
+    async fn reports_a_failed_jurisdiction_without_failing_the_other() {
+        let app = Router::new()
+            .route(
+                "/pasadena.csv",
+                get(|| async {
+                    "facility_id,facility_name,inspection_date\n1,Taco Spot,2024-03-01\n"
+                }),
+            )
+            .route("/oc.csv", get(|| async { StatusCode::NOT_FOUND }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let connector = |pasadena: &str| {
+            CpraConnector::new(CpraSettings {
+                orange_county_export_url: Some(format!("http://{address}/oc.csv")),
+                pasadena_export_url: Some(format!("http://{address}/{pasadena}")),
+                oc_live_enabled: false,
+                ..CpraSettings::default()
+            })
+        };
+        let context = FetchContext::live("cpra", RetryPolicy::default(), None);
 
-    None
-}
+        // Pasadena is published on its own; Orange County keeps its stored data.
+        let records = connector("pasadena.csv")
+            .fetch_facilities(&context)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].jurisdiction, Jurisdiction::Pasadena);
+        let failed = context.take_failed_jurisdictions();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, Jurisdiction::OrangeCounty);
+        assert!(
+            failed[0].1.contains("Orange County CPRA export failed"),
+            "{}",
+            failed[0].1
+        );
 
-fn timestamp_to_datetime(raw: i64) -> Option<DateTime<Utc>> {
-    if raw > 10_000_000_000 {
-        DateTime::from_timestamp_millis(raw)
-    } else {
-        DateTime::from_timestamp(raw, 0)
+        let error = connector("oc.csv")
+            .fetch_facilities(&context)
+            .await
+            .unwrap_err();
+        let message = format!("{error:#}");
+        assert!(
+            message.contains("Orange County CPRA export failed"),
+            "{message}"
+        );
+        assert!(message.contains("Pasadena CPRA export failed"), "{message}");
+        assert!(context.take_failed_jurisdictions().is_empty());
     }
 }
-
-fn parse_bool_env(key: &str, default_value: bool) -> bool {
-    env::var(key)
-        .ok()
-        .map(|value| value.trim().to_ascii_lowercase())
-        .and_then(|value| match value.as_str() {
-            "1" | "true" | "yes" | "on" => Some(true),
-            "0" | "false" | "no" | "off" => Some(false),
-            _ => None,
-        })
-        .unwrap_or(default_value)
-}
@@ -0,0 +1,639 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result, bail};
+use chrono::format::{Item, StrftimeItems};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::{Jurisdiction, LocationPrecision, ValidationReason, Violation},
+    infrastructure::connectors::{
+        config::check_source_name,
+        cpra_connector::{map_record, stable_id},
+        dates::{self, SourceDate},
+        export_files::{SheetOptions, parse_export},
+        mapping::{FieldNames, attr_date, attr_text},
+    },
+};
+
+const DEFAULT_VIOLATION_CODE: &str = "CPRA";
+
+/// Maps CPRA export files through named profiles, falling back to auto-detected columns
+/// for files no profile claims.
+#[derive(Clone, Debug, Default)]
+pub(super) struct ExportMapping {
+    /// Source name used when logging parsed files.
+    pub source: String,
+    pub profiles: BTreeMap<String, MappingProfile>,
+    pub sheet: Option<String>,
+    pub header_row_offset: usize,
+}
+
+/// Where an export body came from, which profiles are matched against.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum ExportOrigin<'a> {
+    Url(&'a str),
+    /// A local file, whose name is also recorded as each record's source file.
+    File(&'a str),
+}
+
+impl ExportOrigin<'_> {
+    fn name(&self) -> &str {
+        match self {
+            Self::Url(name) | Self::File(name) => name,
+        }
+    }
+
+    /// The recorded source file for a record read from `bundled` within this export.
+    fn source_file(&self, bundled: Option<&str>) -> Option<String> {
+        match (self, bundled) {
+            (Self::File(file), Some(bundled)) => Some(format!("{file}/{bundled}")),
+            (Self::File(file), None) => Some((*file).to_owned()),
+            (Self::Url(_), bundled) => bundled.map(str::to_owned),
+        }
+    }
+}
+
+impl ExportMapping {
+    /// Parses an export body in any supported format and maps every row, through the
+    /// `named` profile when given.
+    pub(super) fn map_export(
+        &self,
+        body: &[u8],
+        origin: ExportOrigin<'_>,
+        named: Option<&str>,
+        jurisdiction: &Jurisdiction,
+        id_prefix: &str,
+    ) -> Result<Vec<SourceFacilityInput>> {
+        let files = parse_export(body, |file| {
+            self.sheet_options(
+                self.profile(named, file, origin.name())
+                    .map(|(_, profile)| profile),
+            )
+        })?;
+
+        let mut facilities = Vec::new();
+        for file in files {
+            let profile = self.profile(named, file.name.as_deref(), origin.name());
+            let source_file = origin.source_file(file.name.as_deref());
+            tracing::info!(
+                source = self.source.as_str(),
+                jurisdiction = jurisdiction.code(),
+                file = source_file.as_deref().unwrap_or(origin.name()),
+                profile = profile.map_or("auto-detect", |(name, _)| name),
+                records = file.records.len(),
+                "CPRA export parsed"
+            );
+
+            // Row numbers run across the whole bundle so generated IDs stay distinct.
+            let first_row = facilities.len();
+            for (offset, record) in file.records.into_iter().enumerate() {
+                let idx = first_row + offset;
+                let mut input = match profile {
+                    Some((_, profile)) => {
+                        profile.map(&record, jurisdiction.clone(), id_prefix, idx)
+                    }
+                    None => map_record(record, jurisdiction.clone(), id_prefix, idx),
+                };
+                input.source_file = source_file.clone();
+                facilities.push(input);
+            }
+        }
+
+        Ok(facilities)
+    }
+
+    /// The export's named profile, else the first whose patterns match the file's name
+    /// inside a bundle, else the first whose patterns match the export itself.
+    fn profile(
+        &self,
+        named: Option<&str>,
+        file: Option<&str>,
+        export: &str,
+    ) -> Option<(&str, &MappingProfile)> {
+        let matching = |target: &str| {
+            self.profiles
+                .iter()
+                .find(|(_, profile)| profile.matches(target))
+        };
+        match named {
+            Some(name) => self.profiles.get_key_value(name),
+            None => file.and_then(matching).or_else(|| matching(export)),
+        }
+        .map(|(name, profile)| (name.as_str(), profile))
+    }
+
+    fn sheet_options<'a>(&'a self, profile: Option<&'a MappingProfile>) -> SheetOptions<'a> {
+        SheetOptions {
+            sheet: profile
+                .and_then(|profile| profile.sheet.as_deref())
+                .or(self.sheet.as_deref()),
+            header_row_offset: profile
+                .and_then(|profile| profile.header_row_offset)
+                .unwrap_or(self.header_row_offset),
+        }
+    }
+}
+
+/// Validates every declared profile and that each `(field, name)` selection names one.
+pub(super) fn validate_profiles(
+    profiles: &BTreeMap<String, MappingProfile>,
+    selections: &[(&str, Option<&str>)],
+) -> Result<()> {
+    for (name, profile) in profiles {
+        check_source_name("profile name", name)?;
+        profile
+            .validate()
+            .with_context(|| format!("profiles.{name}"))?;
+    }
+    for (field, selected) in selections {
+        if let Some(profile) = selected
+            && !profiles.contains_key(*profile)
+        {
+            bail!("`{field}` names undeclared profile {profile:?}");
+        }
+    }
+    Ok(())
+}
+
+/// A named mapping for one CPRA export layout, declared as
+/// `[connectors.<name>.profiles.<profile>]`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MappingProfile {
+    /// Substrings of an export URL, or of a file name inside a ZIP export, that select
+    /// this profile when the export names none.
+    pub matches: Vec<String>,
+    /// Worksheet to read from Excel files, overriding the connector's `sheet`.
+    pub sheet: Option<String>,
+    /// Rows above the header row, overriding the connector's `header_row_offset`.
+    pub header_row_offset: Option<usize>,
+    pub fields: ProfileFields,
+    /// Each rule yields violations from one set of columns, so numbered columns such as
+    /// `Violation_1` and `Violation_2` are one rule each.
+    pub violations: Vec<ViolationRule>,
+}
+
+/// Source columns for each facility field. Unmapped fields are left empty (or take the
+/// jurisdiction defaults) rather than guessed.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileFields {
+    pub source_id: ColumnRule,
+    pub name: ColumnRule,
+    pub address: ColumnRule,
+    pub city: ColumnRule,
+    pub state: ColumnRule,
+    pub postal_code: ColumnRule,
+    pub latitude: ColumnRule,
+    pub longitude: ColumnRule,
+    pub inspection_id: ColumnRule,
+    pub inspected_at: ColumnRule,
+    pub score: ColumnRule,
+    pub grade: ColumnRule,
+    pub placard_status: ColumnRule,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ViolationRule {
+    pub description: ColumnRule,
+    /// Splits the description into one violation per non-empty part.
+    pub split: Option<String>,
+    #[serde(default)]
+    pub code: ColumnRule,
+    #[serde(default = "default_violation_code")]
+    pub default_code: String,
+    #[serde(default)]
+    pub points: ColumnRule,
+    /// Read as a boolean (`true`/`yes`/`y`/`1`/`x`), after any lookup.
+    #[serde(default)]
+    pub critical: ColumnRule,
+}
+
+fn default_violation_code() -> String {
+    DEFAULT_VIOLATION_CODE.to_owned()
+}
+
+/// Where a field's value comes from: a column name, a list of candidate columns, or a
+/// table combining `column` or `concat` with transforms.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(from = "ColumnSpec")]
+pub struct ColumnRule {
+    pub column: FieldNames,
+    /// Columns joined with `separator`, skipping empty ones.
+    pub concat: Vec<String>,
+    pub separator: String,
+    /// `strftime` format for dates the built-in formats do not recognize.
+    pub date_format: Option<String>,
+    /// Multiplies numeric values, such as `10` for a 0-10 score.
+    pub scale: Option<f64>,
+    /// Replaces values, matched case-insensitively; values missing from the lookup are
+    /// treated as empty.
+    pub lookup: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColumnSpec {
+    Names(FieldNames),
+    Rule(RuleTable),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleTable {
+    #[serde(default)]
+    column: FieldNames,
+    #[serde(default)]
+    concat: Vec<String>,
+    #[serde(default = "default_separator")]
+    separator: String,
+    date_format: Option<String>,
+    scale: Option<f64>,
+    #[serde(default)]
+    lookup: HashMap<String, String>,
+}
+
+fn default_separator() -> String {
+    " ".to_owned()
+}
+
+impl From<ColumnSpec> for ColumnRule {
+    fn from(spec: ColumnSpec) -> Self {
+        match spec {
+            ColumnSpec::Names(column) => Self {
+                column,
+                ..Self::default()
+            },
+            ColumnSpec::Rule(rule) => Self {
+                column: rule.column,
+                concat: rule.concat,
+                separator: rule.separator,
+                date_format: rule.date_format,
+                scale: rule.scale,
+                lookup: rule.lookup,
+            },
+        }
+    }
+}
+
+impl MappingProfile {
+    fn validate(&self) -> Result<()> {
+        if self.matches.iter().any(|pattern| pattern.trim().is_empty()) {
+            bail!("`matches` entries must not be empty");
+        }
+        if self.fields.name.is_unmapped() {
+            bail!("`fields.name` must be mapped");
+        }
+        let fields = &self.fields;
+        for (field, rule) in [
+            ("source_id", &fields.source_id),
+            ("name", &fields.name),
+            ("address", &fields.address),
+            ("city", &fields.city),
+            ("state", &fields.state),
+            ("postal_code", &fields.postal_code),
+            ("latitude", &fields.latitude),
+            ("longitude", &fields.longitude),
+            ("inspection_id", &fields.inspection_id),
+            ("inspected_at", &fields.inspected_at),
+            ("score", &fields.score),
+            ("grade", &fields.grade),
+            ("placard_status", &fields.placard_status),
+        ] {
+            rule.validate()
+                .with_context(|| format!("`fields.{field}`"))?;
+        }
+        for (index, violation) in self.violations.iter().enumerate() {
+            violation
+                .validate()
+                .with_context(|| format!("`violations[{index}]`"))?;
+        }
+        Ok(())
+    }
+
+    /// Whether an export URL or file name selects this profile.
+    fn matches(&self, target: &str) -> bool {
+        self.matches.iter().any(|pattern| target.contains(pattern))
+    }
+
+    /// Maps one export row. Like the auto-detect mapping, a row without a name is still
+    /// returned so record validation rejects it visibly.
+    pub(super) fn map(
+        &self,
+        record: &Map<String, Value>,
+        jurisdiction: Jurisdiction,
+        id_prefix: &str,
+        row_index: usize,
+    ) -> SourceFacilityInput {
+        let fields = &self.fields;
+        let mut repairs = Vec::new();
+
+        let name = fields.name.text(record).unwrap_or_default();
+        let address = fields.address.text(record).unwrap_or_default();
+        let city = fields
+            .city
+            .text(record)
+            .unwrap_or_else(|| jurisdiction.label().to_owned());
+        let source_id = fields
+            .source_id
+            .text(record)
+            .unwrap_or_else(|| stable_id(id_prefix, &name, &address, &city, row_index));
+
+        let ((latitude, longitude), location_precision) = fields
+            .latitude
+            .number(record)
+            .zip(fields.longitude.number(record))
+            .map(|coordinates| (coordinates, LocationPrecision::Rooftop))
+            .unwrap_or_else(|| {
+                repairs.push(ValidationReason::CoordinatesDefaulted);
+                (
+                    jurisdiction.default_coordinates(),
+                    LocationPrecision::JurisdictionDefault,
+                )
+            });
+
+        let inspection_date = fields.inspected_at.date(record);
+        if inspection_date.is_none() {
+            repairs.push(ValidationReason::InspectionDateMissing);
+        }
+
+        let violations = self
+            .violations
+            .iter()
+            .flat_map(|rule| rule.extract(record))
+            .collect();
+
+        SourceFacilityInput {
+            source_id,
+            name,
+            address,
+            city,
+            state: fields.state.text(record).unwrap_or_else(|| "CA".to_owned()),
+            postal_code: fields.postal_code.text(record).unwrap_or_default(),
+            latitude,
+            longitude,
+            location_precision,
+            jurisdiction,
+            inspection_id: fields.inspection_id.text(record),
+            inspected_at: inspection_date.map(|date| date.at),
+            inspected_at_precision: inspection_date.map(|date| date.precision),
+            inspection_type: None,
+            raw_score: fields.score.number(record).map(|score| score as f32),
+            letter_grade: fields.grade.text(record),
+            placard_status: fields.placard_status.text(record),
+            violations,
+            repairs,
+            source_file: None,
+        }
+    }
+}
+
+impl ViolationRule {
+    fn validate(&self) -> Result<()> {
+        if self.description.is_unmapped() {
+            bail!("`description` must be mapped");
+        }
+        if self.split.as_deref() == Some("") {
+            bail!("`split` must not be empty");
+        }
+        for (field, rule) in [
+            ("description", &self.description),
+            ("code", &self.code),
+            ("points", &self.points),
+            ("critical", &self.critical),
+        ] {
+            rule.validate().with_context(|| format!("`{field}`"))?;
+        }
+        Ok(())
+    }
+
+    fn extract(&self, record: &Map<String, Value>) -> Vec<Violation> {
+        let Some(description) = self.description.text(record) else {
+            return Vec::new();
+        };
+        let descriptions = match &self.split {
+            Some(separator) => description
+                .split(separator.as_str())
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            None => vec![description],
+        };
+
+        let code = self
+            .code
+            .text(record)
+            .unwrap_or_else(|| self.default_code.clone());
+        let points = self
+            .points
+            .number(record)
+            .map(|points| points as i16)
+            .unwrap_or(0);
+        let critical = self.critical.flag(record).unwrap_or(false);
+        descriptions
+            .into_iter()
+            .map(|description| Violation {
+                code: code.clone(),
+                description,
+                points,
+                critical,
+            })
+            .collect()
+    }
+}
+
+impl ColumnRule {
+    fn is_unmapped(&self) -> bool {
+        self.column.is_empty() && self.concat.is_empty()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !self.column.is_empty() && !self.concat.is_empty() {
+            bail!("set `column` or `concat`, not both");
+        }
+        if let Some(format) = &self.date_format
+            && StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+        {
+            bail!("`date_format` is not a valid strftime format: {format:?}");
+        }
+        if let Some(scale) = self.scale
+            && !(scale.is_finite() && scale != 0.0)
+        {
+            bail!("`scale` must be a non-zero number");
+        }
+        Ok(())
+    }
+
+    /// The column value (or joined columns), after the lookup.
+    fn text(&self, record: &Map<String, Value>) -> Option<String> {
+        let value = if self.concat.is_empty() {
+            self.column
+                .names()
+                .find_map(|name| attr_text(record, name))?
+        } else {
+            let parts = self
+                .concat
+                .iter()
+                .filter_map(|name| attr_text(record, name))
+                .collect::<Vec<_>>();
+            if parts.is_empty() {
+                return None;
+            }
+            parts.join(&self.separator)
+        };
+
+        if self.lookup.is_empty() {
+            return Some(value);
+        }
+        self.lookup
+            .iter()
+            .find(|(from, _)| from.eq_ignore_ascii_case(&value))
+            .map(|(_, to)| to.clone())
+    }
+
+    fn number(&self, record: &Map<String, Value>) -> Option<f64> {
+        let number = self.text(record)?.parse::<f64>().ok()?;
+        Some(number * self.scale.unwrap_or(1.0))
+    }
+
+    fn date(&self, record: &Map<String, Value>) -> Option<SourceDate> {
+        match &self.date_format {
+            Some(format) => dates::parse_with_format(&self.text(record)?, format),
+            // Plain columns keep epoch numbers, which `text` would stringify.
+            None if self.concat.is_empty() && self.lookup.is_empty() => {
+                self.column.names().find_map(|name| attr_date(record, name))
+            }
+            None => dates::parse(&self.text(record)?),
+        }
+    }
+
+    fn flag(&self, record: &Map<String, Value>) -> Option<bool> {
+        match self.text(record)?.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" | "x" => Some(true),
+            "false" | "no" | "n" | "0" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    use super::MappingProfile;
+    use crate::domain::entities::{Jurisdiction, LocationPrecision, ValidationReason};
+
+    fn profile(text: &str) -> MappingProfile {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn maps_columns_through_transforms_and_extracts_violations() {
+        let profile = profile(
+            r#"
+            matches = ["closures_2024"]
+
+            [fields]
+            source_id = ["Permit", "Record"]
+            name = "Estab"
+            address = { concat = ["Street_No", "Street"] }
+            inspected_at = { column = "Insp_Date", date_format = "%Y%m%d" }
+            score = { column = "Rating", scale = 10 }
+            grade = { column = "Placard", lookup = { g = "A", y = "B", r = "C" } }
+
+            [[violations]]
+            description = "Closure_Reasons"
+            split = ";"
+            code = "Closure_Code"
+            critical = { column = "Severity", lookup = { major = "yes", minor = "no" } }
+
+            [[violations]]
+            description = "Violation_2"
+            default_code = "V2"
+            "#,
+        );
+        profile.validate().unwrap();
+        assert!(profile.matches("https://example.test/exports/closures_2024.csv"));
+        assert!(!profile.matches("https://example.test/exports/closures_2023.csv"));
+
+        let record = json!({
+            "Record": 42,
+            "Estab": " Taco Spot ",
+            "Street_No": "12",
+            "Street": "Main St",
+            "Insp_Date": "20240704",
+            "Rating": "9.5",
+            "Placard": "G",
+            "Closure_Reasons": "No hot water; Vermin;",
+            "Closure_Code": "C1",
+            "Severity": "Major",
+            "Violation_2": "",
+        });
+        let input = profile.map(
+            record.as_object().unwrap(),
+            Jurisdiction::OrangeCounty,
+            "oc",
+            0,
+        );
+
+        assert_eq!(input.source_id, "42");
+        assert_eq!(input.name, "Taco Spot");
+        assert_eq!(input.address, "12 Main St");
+        assert_eq!(input.city, "Orange County");
+        assert_eq!(
+            input.inspected_at,
+            Some(Utc.with_ymd_and_hms(2024, 7, 4, 7, 0, 0).unwrap())
+        );
+        assert_eq!(input.raw_score, Some(95.0));
+        assert_eq!(input.letter_grade.as_deref(), Some("A"));
+        assert_eq!(
+            input.location_precision,
+            LocationPrecision::JurisdictionDefault
+        );
+        assert_eq!(input.repairs, vec![ValidationReason::CoordinatesDefaulted]);
+
+        let violations = input
+            .violations
+            .iter()
+            .map(|violation| {
+                (
+                    violation.code.as_str(),
+                    violation.description.as_str(),
+                    violation.critical,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            vec![("C1", "No hot water", true), ("C1", "Vermin", true)]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_profiles() {
+        let error = |text: &str| format!("{:#}", profile(text).validate().unwrap_err());
+
+        assert!(error("[fields]\naddress = \"Street\"\n").contains("`fields.name` must be mapped"));
+        assert!(
+            error("[fields]\nname = { column = \"A\", concat = [\"B\"] }\n").contains("not both")
+        );
+        assert!(
+            error(
+                "[fields]\nname = \"A\"\ninspected_at = { column = \"D\", date_format = \"%Q\" }\n"
+            )
+            .contains("`fields.inspected_at`: `date_format`")
+        );
+        assert!(
+            error("[fields]\nname = \"A\"\n\n[[violations]]\ndescription = \"V\"\nsplit = \"\"\n")
+                .contains("`violations[0]`: `split`")
+        );
+        assert!(
+            toml::from_str::<MappingProfile>("[fields]\nname = { columns = \"A\" }\n").is_err()
+        );
+    }
+}
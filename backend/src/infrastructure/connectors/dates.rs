@@ -0,0 +1,238 @@
+use chrono::{
+    DateTime, Datelike, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::{America::Los_Angeles, Tz};
+use serde_json::Value;
+
+use crate::domain::entities::DatePrecision;
+
+/// Every connected source publishes local times for Southern California.
+pub const SOURCE_TIME_ZONE: Tz = Los_Angeles;
+
+/// Epoch values above this are milliseconds rather than seconds.
+const EPOCH_MILLIS_THRESHOLD: i64 = 10_000_000_000;
+
+/// Digit strings shorter than this are calendar dates such as `20240301`, not epochs.
+const EPOCH_MIN_DIGITS: usize = 10;
+
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+    "%m/%d/%Y %H:%M:%S",
+    "%m/%d/%Y %H:%M",
+    "%m/%d/%Y %I:%M:%S %p",
+    "%m/%d/%Y %I:%M %p",
+];
+
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d", "%Y%m%d", "%m/%d/%Y", "%m-%d-%Y", "%m/%d/%y", "%m-%d-%y",
+];
+
+/// A source date resolved to an instant, remembering whether the source gave a time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceDate {
+    pub at: DateTime<Utc>,
+    pub precision: DatePrecision,
+}
+
+impl SourceDate {
+    fn timestamp(at: DateTime<Utc>) -> Self {
+        Self {
+            at,
+            precision: DatePrecision::Timestamp,
+        }
+    }
+}
+
+/// Parses a source date string. Values with an offset keep it; naive timestamps and
+/// bare dates are read as Pacific time, a bare date as local midnight. Only digit
+/// strings of at least ten digits are epochs, so a LIVES `YYYYMMDD` stays a date.
+pub fn parse(value: &str) -> Option<SourceDate> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if value.len() >= EPOCH_MIN_DIGITS
+        && let Ok(epoch) = value.parse::<i64>()
+    {
+        return from_epoch(epoch);
+    }
+
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(SourceDate::timestamp(parsed.with_timezone(&Utc)));
+    }
+
+    if let Some(naive) = DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    {
+        return local_to_utc(naive).map(SourceDate::timestamp);
+    }
+
+    // `%Y` accepts two-digit years, so implausible years fall through to `%y`.
+    let date = DATE_FORMATS.iter().find_map(|format| {
+        NaiveDate::parse_from_str(value, format)
+            .ok()
+            .filter(|date| date.year() >= 1900)
+    })?;
+    local_to_utc(date.and_hms_opt(0, 0, 0)?).map(|at| SourceDate {
+        at,
+        precision: DatePrecision::Date,
+    })
+}
+
+/// Parses a date with an explicit `strftime` format, read as Pacific time like
+/// [`parse`]. A format without time fields yields a date-precision value.
+pub fn parse_with_format(value: &str, format: &str) -> Option<SourceDate> {
+    let value = value.trim();
+    if let Ok(parsed) = DateTime::parse_from_str(value, format) {
+        return Some(SourceDate::timestamp(parsed.with_timezone(&Utc)));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+        return local_to_utc(naive).map(SourceDate::timestamp);
+    }
+    let date = NaiveDate::parse_from_str(value, format).ok()?;
+    local_to_utc(date.and_hms_opt(0, 0, 0)?).map(|at| SourceDate {
+        at,
+        precision: DatePrecision::Date,
+    })
+}
+
+/// Reads a Unix epoch in seconds or milliseconds, which is already an instant. An
+/// epoch on exact UTC midnight is how ArcGIS sends date-only fields, so it becomes
+/// that UTC calendar day at Pacific midnight rather than the evening before.
+pub fn from_epoch(raw: i64) -> Option<SourceDate> {
+    let at = if raw > EPOCH_MILLIS_THRESHOLD {
+        DateTime::from_timestamp_millis(raw)
+    } else {
+        DateTime::from_timestamp(raw, 0)
+    }?;
+    if at.time() == NaiveTime::MIN {
+        return local_to_utc(at.date_naive().and_time(NaiveTime::MIN)).map(|at| SourceDate {
+            at,
+            precision: DatePrecision::Date,
+        });
+    }
+    Some(SourceDate::timestamp(at))
+}
+
+/// Parses a JSON epoch number or date string.
+pub fn from_json(value: &Value) -> Option<SourceDate> {
+    match value {
+        Value::Number(number) => number.as_i64().and_then(from_epoch),
+        Value::String(text) => parse(text),
+        _ => None,
+    }
+}
+
+/// Resolves a Pacific wall-clock time. An hour repeated when DST ends takes its first
+/// (daylight) occurrence; a time skipped when DST starts moves forward an hour.
+fn local_to_utc(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    let local = match SOURCE_TIME_ZONE.from_local_datetime(&naive) {
+        LocalResult::Single(local) | LocalResult::Ambiguous(local, _) => local,
+        LocalResult::None => SOURCE_TIME_ZONE
+            .from_local_datetime(&(naive + TimeDelta::hours(1)))
+            .earliest()?,
+    };
+    Some(local.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    use super::{from_json, parse, parse_with_format};
+    use crate::domain::entities::DatePrecision;
+
+    #[test]
+    fn reads_naive_dates_as_pacific_time() {
+        // Pacific standard time is UTC-8, daylight time UTC-7.
+        let winter = parse("03/04/2024").unwrap();
+        assert_eq!(
+            winter.at,
+            Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap()
+        );
+        assert_eq!(winter.precision, DatePrecision::Date);
+
+        let summer = parse("2024-07-04").unwrap();
+        assert_eq!(
+            summer.at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 7, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse("7/4/24").unwrap().at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 7, 0, 0).unwrap()
+        );
+
+        let timestamp = parse("2024-07-04T13:30:00.000").unwrap();
+        assert_eq!(
+            timestamp.at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 20, 30, 0).unwrap()
+        );
+        assert_eq!(timestamp.precision, DatePrecision::Timestamp);
+    }
+
+    #[test]
+    fn resolves_dst_transitions_and_absolute_values() {
+        // 02:30 is skipped on 2024-03-10 and 01:30 happens twice on 2024-11-03.
+        assert_eq!(
+            parse("2024-03-10 02:30:00").unwrap().at,
+            Utc.with_ymd_and_hms(2024, 3, 10, 10, 30, 0).unwrap()
+        );
+        assert_eq!(
+            parse("2024-11-03 01:30:00").unwrap().at,
+            Utc.with_ymd_and_hms(2024, 11, 3, 8, 30, 0).unwrap()
+        );
+
+        assert_eq!(
+            parse("2024-07-04T13:30:00Z").unwrap().at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 13, 30, 0).unwrap()
+        );
+        let epoch = from_json(&json!(1_720_099_800_000_i64)).unwrap();
+        assert_eq!(
+            epoch.at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 13, 30, 0).unwrap()
+        );
+        assert_eq!(epoch.precision, DatePrecision::Timestamp);
+        assert!(parse("not a date").is_none());
+    }
+
+    #[test]
+    fn reads_compact_dates_and_utc_midnight_epochs_as_calendar_days() {
+        // A LIVES `YYYYMMDD` is not 20,240,301 seconds after 1970.
+        let lives = parse("20240301").unwrap();
+        assert_eq!(lives.at, Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap());
+        assert_eq!(lives.precision, DatePrecision::Date);
+        assert_eq!(
+            parse("1720099800").unwrap().at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 13, 30, 0).unwrap()
+        );
+
+        // ArcGIS sends the date-only field 2024-07-04 as UTC midnight in milliseconds.
+        let arcgis = from_json(&json!(1_720_051_200_000_i64)).unwrap();
+        assert_eq!(
+            arcgis.at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 7, 0, 0).unwrap()
+        );
+        assert_eq!(arcgis.precision, DatePrecision::Date);
+    }
+
+    #[test]
+    fn parses_explicit_formats_as_pacific_time() {
+        let date = parse_with_format("20240704", "%Y%m%d").unwrap();
+        assert_eq!(date.at, Utc.with_ymd_and_hms(2024, 7, 4, 7, 0, 0).unwrap());
+        assert_eq!(date.precision, DatePrecision::Date);
+
+        let timestamp = parse_with_format("04.07.2024 13:30", "%d.%m.%Y %H:%M").unwrap();
+        assert_eq!(
+            timestamp.at,
+            Utc.with_ymd_and_hms(2024, 7, 4, 20, 30, 0).unwrap()
+        );
+        assert_eq!(timestamp.precision, DatePrecision::Timestamp);
+        assert!(parse_with_format("2024-07-04", "%Y%m%d").is_none());
+    }
+}
@@ -0,0 +1,522 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::{ConnectorSyncState, Jurisdiction},
+    infrastructure::connectors::{
+        FetchContext, HealthDataConnector,
+        config::{ConnectorSettings, check_source_name, parse_jurisdiction},
+        cpra_profile::{ExportMapping, ExportOrigin, MappingProfile, validate_profiles},
+    },
+};
+
+const DEFAULT_MIN_FILE_AGE_SECS: u64 = 60;
+const EXPORT_EXTENSIONS: &[&str] = &["csv", "json", "xlsx", "xlsm", "xls", "zip"];
+
+/// A source declared as `type = "drop_folder"`: CPRA export files copied into a local
+/// directory by hand. Each new file is parsed like a CPRA export and moved to the
+/// processed or failed folder; every processed file is republished on each run.
+pub struct DropFolderConnector {
+    source_name: String,
+    jurisdiction: Jurisdiction,
+    directory: PathBuf,
+    processed_dir: PathBuf,
+    failed_dir: PathBuf,
+    min_file_age: Duration,
+    id_prefix: String,
+    profile: Option<String>,
+    mapping: ExportMapping,
+}
+
+/// `[connectors.<name>]` keys for `type = "drop_folder"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DropFolderSettings {
+    /// Source name for statuses and archives; defaults to the entry name.
+    pub source: Option<String>,
+    /// Jurisdiction code whose facilities the folder's exports replace.
+    pub jurisdiction: String,
+    /// Directory watched for new export files; subdirectories are ignored.
+    pub directory: PathBuf,
+    /// Defaults to `<directory>/processed`.
+    pub processed_dir: Option<PathBuf>,
+    /// Defaults to `<directory>/failed`.
+    pub failed_dir: Option<PathBuf>,
+    /// Files modified more recently are left for a later run, so a file still being
+    /// copied in is never read half-written.
+    pub min_file_age_secs: u64,
+    /// Prefix for generated IDs of rows without one; defaults to the jurisdiction code.
+    pub id_prefix: Option<String>,
+    /// Profile every file is mapped with; otherwise profiles are matched by file name.
+    pub profile: Option<String>,
+    pub profiles: BTreeMap<String, MappingProfile>,
+    pub sheet: Option<String>,
+    pub header_row_offset: usize,
+}
+
+impl Default for DropFolderSettings {
+    fn default() -> Self {
+        Self {
+            source: None,
+            jurisdiction: String::new(),
+            directory: PathBuf::new(),
+            processed_dir: None,
+            failed_dir: None,
+            min_file_age_secs: DEFAULT_MIN_FILE_AGE_SECS,
+            id_prefix: None,
+            profile: None,
+            profiles: BTreeMap::new(),
+            sheet: None,
+            header_row_offset: 0,
+        }
+    }
+}
+
+impl ConnectorSettings for DropFolderSettings {
+    /// Drop folders have no fixed variables; use `${VAR}` references instead.
+    fn apply_env(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        parse_jurisdiction(&self.jurisdiction)?;
+        if let Some(source) = &self.source {
+            check_source_name("source", source)?;
+        }
+        if self.directory.as_os_str().is_empty() {
+            bail!("`directory` must not be empty");
+        }
+        if let Some(prefix) = &self.id_prefix {
+            check_source_name("id_prefix", prefix)?;
+        }
+        validate_profiles(&self.profiles, &[("profile", self.profile.as_deref())])
+    }
+}
+
+/// One export file taken from the folder, as remembered in the connector's sync state.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct IngestedFile {
+    /// Name within the processed folder.
+    file: String,
+    sha256: String,
+    ingested_at: DateTime<Utc>,
+}
+
+impl DropFolderConnector {
+    pub fn new(name: &str, settings: DropFolderSettings) -> Result<Self> {
+        let jurisdiction = parse_jurisdiction(&settings.jurisdiction)?;
+        let source_name = settings.source.unwrap_or_else(|| name.to_owned());
+        check_source_name("source", &source_name)?;
+
+        Ok(Self {
+            source_name: source_name.clone(),
+            processed_dir: settings
+                .processed_dir
+                .unwrap_or_else(|| settings.directory.join("processed")),
+            failed_dir: settings
+                .failed_dir
+                .unwrap_or_else(|| settings.directory.join("failed")),
+            directory: settings.directory,
+            min_file_age: Duration::from_secs(settings.min_file_age_secs),
+            id_prefix: settings
+                .id_prefix
+                .unwrap_or_else(|| jurisdiction.code().to_owned()),
+            jurisdiction,
+            profile: settings.profile,
+            mapping: ExportMapping {
+                source: source_name,
+                profiles: settings.profiles,
+                sheet: settings.sheet,
+                header_row_offset: settings.header_row_offset,
+            },
+        })
+    }
+
+    fn map_file(&self, file: &str, body: &[u8]) -> Result<Vec<SourceFacilityInput>> {
+        self.mapping.map_export(
+            body,
+            ExportOrigin::File(file),
+            self.profile.as_deref(),
+            &self.jurisdiction,
+            &self.id_prefix,
+        )
+    }
+
+    /// Takes every settled file in the folder: an export whose content was already
+    /// ingested is set aside as processed without being read again, a new one that maps
+    /// to records is added to `ledger` and moved to the processed folder, and anything
+    /// else is moved to the failed folder with an `.error.txt` note beside it.
+    ///
+    /// The ledger is committed before each move, so a fetch that fails afterwards
+    /// still remembers every file that has left the folder.
+    async fn take_new_files(
+        &self,
+        context: &FetchContext,
+        ledger: &mut Vec<IngestedFile>,
+        full_sync_at: DateTime<Utc>,
+    ) -> Result<()> {
+        for path in self.settled_files().await? {
+            let name = file_name(&path);
+            let body = fs::read(&path)
+                .await
+                .with_context(|| format!("unable to read {}", path.display()))?;
+            let sha256 = hex::encode(Sha256::digest(&body));
+
+            if let Some(previous) = ledger.iter().find(|entry| entry.sha256 == sha256) {
+                let moved = move_into(&path, &self.processed_dir).await?;
+                info!(
+                    source = self.source_name.as_str(),
+                    file = %name,
+                    moved_to = %moved,
+                    same_as = %previous.file,
+                    "Export already ingested; set aside without ingesting it again"
+                );
+                continue;
+            }
+
+            let mapped = if is_export_file(&name) {
+                self.map_file(&name, &body).and_then(|records| {
+                    if records.is_empty() {
+                        bail!("export contains no records");
+                    }
+                    Ok(records.len())
+                })
+            } else {
+                Err(anyhow::anyhow!(
+                    "unsupported file type; expected one of {}",
+                    EXPORT_EXTENSIONS.join(", ")
+                ))
+            };
+
+            match mapped {
+                Ok(records) => {
+                    let moved = free_name(&path, &self.processed_dir).await?;
+                    ledger.push(IngestedFile {
+                        file: moved.clone(),
+                        sha256,
+                        ingested_at: Utc::now(),
+                    });
+                    context.commit_sync_state(self.ledger_state(ledger, full_sync_at)?);
+                    if let Err(error) = move_as(&path, &self.processed_dir, &moved).await {
+                        ledger.pop();
+                        context.commit_sync_state(self.ledger_state(ledger, full_sync_at)?);
+                        return Err(error);
+                    }
+                    info!(source = self.source_name.as_str(), file = %moved, records, "Ingested dropped export");
+                }
+                Err(error) => {
+                    let error = format!("{error:#}");
+                    let moved = move_into(&path, &self.failed_dir).await?;
+                    warn!(source = self.source_name.as_str(), file = %moved, %error, "Dropped export failed");
+                    fs::write(
+                        self.failed_dir.join(format!("{moved}.error.txt")),
+                        format!("{error}\n"),
+                    )
+                    .await
+                    .with_context(|| format!("unable to write the error note for {moved}"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn ledger_state(
+        &self,
+        ledger: &[IngestedFile],
+        full_sync_at: DateTime<Utc>,
+    ) -> Result<ConnectorSyncState> {
+        Ok(ConnectorSyncState {
+            source: self.source_name.clone(),
+            watermark: ledger.last().map(|entry| entry.sha256.clone()),
+            rows: serde_json::to_value(ledger)?,
+            full_sync_at,
+            synced_at: Utc::now(),
+        })
+    }
+
+    /// Visible files directly in the folder that have not changed for `min_file_age`,
+    /// in name order.
+    async fn settled_files(&self) -> Result<Vec<PathBuf>> {
+        let mut entries = fs::read_dir(&self.directory)
+            .await
+            .with_context(|| format!("unable to read drop folder {}", self.directory.display()))?;
+        let now = SystemTime::now();
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            let settled = metadata.modified().is_ok_and(|modified| {
+                now.duration_since(modified).unwrap_or_default() >= self.min_file_age
+            });
+            if metadata.is_file() && settled && !file_name(&entry.path()).starts_with('.') {
+                files.push(entry.path());
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+#[async_trait]
+impl HealthDataConnector for DropFolderConnector {
+    fn source_name(&self) -> &str {
+        &self.source_name
+    }
+
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        vec![self.jurisdiction.clone()]
+    }
+
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
+        let stored = context.resume_sync(|_| true).await?;
+        let mut ledger: Vec<IngestedFile> = match &stored {
+            Some(state) => serde_json::from_value(state.rows.clone())
+                .context("stored drop-folder ledger is unreadable")?,
+            None => Vec::new(),
+        };
+
+        // Replays publish the files the archived run published and never move files.
+        if !context.is_replay() {
+            let full_sync_at = stored.map_or_else(Utc::now, |state| state.full_sync_at);
+            self.take_new_files(context, &mut ledger, full_sync_at)
+                .await?;
+            context.commit_sync_state(self.ledger_state(&ledger, full_sync_at)?);
+        }
+        let ledger = serde_json::to_string(&ledger)?;
+        let ledger: Vec<IngestedFile> = serde_json::from_str(
+            &context
+                .fetch_text("ledger", || std::future::ready(Ok(ledger.clone())))
+                .await?,
+        )?;
+        if ledger.is_empty() {
+            bail!(
+                "no exports have been ingested from {} yet",
+                self.directory.display()
+            );
+        }
+
+        // A processed file that can no longer be read is skipped rather than failing
+        // the fetch, which would also lose this run's ledger entries.
+        let mut facilities = Vec::new();
+        for entry in &ledger {
+            let path = self.processed_dir.join(&entry.file);
+            let records = context
+                .fetch_bytes(&format!("file {}", entry.sha256), || async {
+                    fs::read(&path)
+                        .await
+                        .with_context(|| format!("unable to read {}", path.display()))
+                })
+                .await
+                .and_then(|body| self.map_file(&entry.file, &body));
+            match records {
+                Ok(records) => facilities.extend(records),
+                Err(error) => warn!(
+                    source = self.source_name.as_str(),
+                    file = %entry.file,
+                    error = %format!("{error:#}"),
+                    "Processed export could not be republished"
+                ),
+            }
+        }
+        if facilities.is_empty() {
+            bail!(
+                "none of the exports ingested from {} could be republished",
+                self.directory.display()
+            );
+        }
+        Ok(facilities)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn is_export_file(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        EXPORT_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+    })
+}
+
+/// Moves `path` into `directory`, prefixing a timestamp when the name is taken, and
+/// returns the name it was stored under.
+async fn move_into(path: &Path, directory: &Path) -> Result<String> {
+    let name = free_name(path, directory).await?;
+    move_as(path, directory, &name).await?;
+    Ok(name)
+}
+
+/// Creates `directory` and returns the name `path` can take in it: its own, or a
+/// timestamp-prefixed one when that is taken.
+async fn free_name(path: &Path, directory: &Path) -> Result<String> {
+    fs::create_dir_all(directory)
+        .await
+        .with_context(|| format!("unable to create {}", directory.display()))?;
+    let name = file_name(path);
+    if fs::try_exists(directory.join(&name)).await? {
+        return Ok(format!("{}-{name}", Utc::now().format("%Y%m%dT%H%M%S%.3f")));
+    }
+    Ok(name)
+}
+
+async fn move_as(path: &Path, directory: &Path, name: &str) -> Result<()> {
+    let target = directory.join(name);
+
+    // A rename cannot cross filesystems, so fall back to copying.
+    if fs::rename(path, &target).await.is_err() {
+        fs::copy(path, &target).await.with_context(|| {
+            format!("unable to move {} to {}", path.display(), target.display())
+        })?;
+        fs::remove_file(path).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{DropFolderConnector, DropFolderSettings};
+    use crate::infrastructure::connectors::{FetchContext, HealthDataConnector, RetryPolicy};
+
+    fn drop_folder(directory: &Path) -> DropFolderConnector {
+        DropFolderConnector::new(
+            "oc_drop",
+            DropFolderSettings {
+                jurisdiction: "oc".to_owned(),
+                directory: directory.to_owned(),
+                min_file_age_secs: 0,
+                ..DropFolderSettings::default()
+            },
+        )
+        .unwrap()
+    }
+
+    fn names(directory: &Path) -> Vec<String> {
+        let mut names = std::fs::read_dir(directory)
+            .map(|entries| {
+                entries
+                    .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn ingests_each_export_once_and_republishes_processed_files() {
+        let directory =
+            std::env::temp_dir().join(format!("cleanplated-drop-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let connector = drop_folder(&directory);
+        let q1 = "facility_id,facility_name,inspection_date\n1,Taco Spot,2024-03-01\n";
+        std::fs::write(directory.join("q1.csv"), q1).unwrap();
+        std::fs::write(directory.join("notes.txt"), "call back").unwrap();
+
+        let context = FetchContext::live(&connector.source_name, RetryPolicy::default(), None);
+        let first = connector.fetch_facilities(&context).await.unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].source_file.as_deref(), Some("q1.csv"));
+        assert_eq!(names(&directory.join("processed")), vec!["q1.csv"]);
+        assert_eq!(
+            names(&directory.join("failed")),
+            vec!["notes.txt", "notes.txt.error.txt"]
+        );
+        let state = context.take_sync_state().unwrap();
+
+        // The same export under a new name is set aside; a new quarter is added.
+        std::fs::write(directory.join("q1 (copy).csv"), q1).unwrap();
+        std::fs::write(
+            directory.join("q2.csv"),
+            "facility_id,facility_name,inspection_date\n1,Taco Spot,2024-06-01\n",
+        )
+        .unwrap();
+        let context = FetchContext::live(&connector.source_name, RetryPolicy::default(), None)
+            .with_sync_state(Some(state));
+        let second = connector.fetch_facilities(&context).await.unwrap();
+        let files = second
+            .iter()
+            .map(|record| record.source_file.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec!["q1.csv", "q2.csv"]);
+        assert_eq!(
+            names(&directory.join("processed")),
+            vec!["q1 (copy).csv", "q1.csv", "q2.csv"]
+        );
+        assert!(names(&directory).iter().all(|name| !name.ends_with(".csv")));
+
+        let state = context.take_sync_state().unwrap();
+        let ledger = state.rows.as_array().unwrap();
+        assert_eq!(ledger.len(), 2);
+
+        let context = FetchContext::live(&connector.source_name, RetryPolicy::default(), None)
+            .with_sync_state(Some(state));
+        assert_eq!(connector.fetch_facilities(&context).await.unwrap().len(), 2);
+
+        // A folder that has never yielded an export has nothing to publish.
+        std::fs::create_dir(directory.join("empty")).unwrap();
+        let empty = drop_folder(&directory.join("empty"));
+        let context = FetchContext::live(&empty.source_name, RetryPolicy::default(), None);
+        assert!(empty.fetch_facilities(&context).await.is_err());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn remembers_moved_files_when_the_fetch_fails_afterwards() {
+        let root = std::env::temp_dir().join(format!("cleanplated-drop-{}", uuid::Uuid::new_v4()));
+        let directory = root.join("drop");
+        std::fs::create_dir_all(&directory).unwrap();
+        // The failed folder cannot be created beneath a regular file.
+        std::fs::write(root.join("blocker"), "").unwrap();
+        let broken = DropFolderConnector::new(
+            "oc_drop",
+            DropFolderSettings {
+                jurisdiction: "oc".to_owned(),
+                directory: directory.clone(),
+                failed_dir: Some(root.join("blocker").join("failed")),
+                min_file_age_secs: 0,
+                ..DropFolderSettings::default()
+            },
+        )
+        .unwrap();
+        std::fs::write(
+            directory.join("a-q1.csv"),
+            "facility_id,facility_name,inspection_date\n1,Taco Spot,2024-03-01\n",
+        )
+        .unwrap();
+        std::fs::write(directory.join("b-notes.txt"), "call back").unwrap();
+
+        let context = FetchContext::live(&broken.source_name, RetryPolicy::default(), None);
+        assert!(broken.fetch_facilities(&context).await.is_err());
+        assert_eq!(names(&directory.join("processed")), vec!["a-q1.csv"]);
+
+        // The export has left the folder, so only the committed ledger still knows it.
+        let state = context.take_committed_sync_state().unwrap();
+        let context = FetchContext::live(&broken.source_name, RetryPolicy::default(), None)
+            .with_sync_state(Some(state));
+        std::fs::remove_file(directory.join("b-notes.txt")).unwrap();
+        let republished = drop_folder(&directory)
+            .fetch_facilities(&context)
+            .await
+            .unwrap();
+        assert_eq!(republished.len(), 1);
+        assert_eq!(republished[0].source_file.as_deref(), Some("a-q1.csv"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
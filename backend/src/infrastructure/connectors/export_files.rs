@@ -0,0 +1,451 @@
+use std::io::{Cursor, Read};
+
+use anyhow::{Context, Result, bail};
+use calamine::{Data, Reader, open_workbook_auto_from_rs};
+use serde_json::{Map, Number, Value};
+use zip::ZipArchive;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const EMPTY_ZIP_MAGIC: &[u8] = b"PK\x05\x06";
+/// Legacy `.xls` workbooks are OLE compound files.
+const OLE_MAGIC: &[u8] = b"\xD0\xCF\x11\xE0";
+/// Upper bound on one decompressed ZIP entry, so a hostile bundle cannot exhaust memory.
+const MAX_ENTRY_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Which worksheet of a workbook to read and how many rows sit above its header row.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct SheetOptions<'a> {
+    /// Sheet name, matched case-insensitively; the first sheet when unset.
+    pub sheet: Option<&'a str>,
+    pub header_row_offset: usize,
+}
+
+/// Rows read from one file of an export.
+#[derive(Debug)]
+pub(super) struct ExportFile {
+    /// The file's path inside a ZIP bundle; `None` for a single-file export.
+    pub name: Option<String>,
+    pub records: Vec<Map<String, Value>>,
+}
+
+/// Parses an export body of any supported format, sniffed from its content rather than
+/// its Content-Type so archived exports replay through the same parser: a ZIP of
+/// CSV/XLSX files, an Excel workbook, JSON, or CSV. `sheet_options` is asked for each
+/// workbook with its file name inside a bundle.
+pub(super) fn parse_export<'a>(
+    body: &[u8],
+    sheet_options: impl Fn(Option<&str>) -> SheetOptions<'a>,
+) -> Result<Vec<ExportFile>> {
+    if body.starts_with(ZIP_MAGIC) || body.starts_with(EMPTY_ZIP_MAGIC) {
+        let mut archive = ZipArchive::new(Cursor::new(body)).context("ZIP export parse failed")?;
+        // Office Open XML and OpenDocument workbooks are ZIPs with these parts.
+        let is_workbook = archive.index_for_name("[Content_Types].xml").is_some()
+            || archive.index_for_name("mimetype").is_some();
+        if !is_workbook {
+            return parse_bundle(&mut archive, &sheet_options);
+        }
+    }
+
+    let records = if body.starts_with(ZIP_MAGIC) || body.starts_with(OLE_MAGIC) {
+        parse_workbook(body, sheet_options(None))?
+    } else {
+        parse_text(&String::from_utf8_lossy(body))?
+    };
+    Ok(vec![ExportFile {
+        name: None,
+        records,
+    }])
+}
+
+fn parse_bundle<'a>(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    sheet_options: &impl Fn(Option<&str>) -> SheetOptions<'a>,
+) -> Result<Vec<ExportFile>> {
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).context("ZIP entry read failed")?;
+        let name = entry.name().to_owned();
+        let Some(kind) = BundleFile::of(&name) else {
+            if entry.is_file() {
+                tracing::debug!(file = %name, "Skipping unsupported file in export bundle");
+            }
+            continue;
+        };
+
+        let mut body = Vec::new();
+        (&mut entry)
+            .take(MAX_ENTRY_BYTES + 1)
+            .read_to_end(&mut body)
+            .with_context(|| format!("unable to extract {name}"))?;
+        if body.len() as u64 > MAX_ENTRY_BYTES {
+            bail!("{name} is larger than {MAX_ENTRY_BYTES} bytes uncompressed");
+        }
+
+        let records = match kind {
+            BundleFile::Csv => parse_csv_records(&String::from_utf8_lossy(&body)),
+            BundleFile::Workbook => parse_workbook(&body, sheet_options(Some(&name))),
+        }
+        .with_context(|| format!("unable to parse {name}"))?;
+        files.push(ExportFile {
+            name: Some(name),
+            records,
+        });
+    }
+
+    if files.is_empty() {
+        bail!("ZIP export contains no CSV or Excel files");
+    }
+    Ok(files)
+}
+
+enum BundleFile {
+    Csv,
+    Workbook,
+}
+
+impl BundleFile {
+    /// Classifies a bundle entry by extension, skipping directories and the metadata
+    /// files archivers add.
+    fn of(name: &str) -> Option<Self> {
+        let file_name = name.rsplit('/').next().unwrap_or(name);
+        if file_name.starts_with('.') || name.starts_with("__MACOSX/") {
+            return None;
+        }
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "xlsx" | "xlsm" | "xls" => Some(Self::Workbook),
+            _ => None,
+        }
+    }
+}
+
+fn parse_text(body: &str) -> Result<Vec<Map<String, Value>>> {
+    let body = body.trim_start_matches('\u{feff}');
+    if body.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    if body.trim_start().starts_with(['{', '[']) {
+        parse_json_records(body)
+    } else {
+        parse_csv_records(body)
+    }
+}
+
+fn parse_workbook(body: &[u8], options: SheetOptions<'_>) -> Result<Vec<Map<String, Value>>> {
+    let mut workbook =
+        open_workbook_auto_from_rs(Cursor::new(body)).context("Excel workbook parse failed")?;
+    let sheet_names = workbook.sheet_names();
+    let sheet = match options.sheet {
+        Some(wanted) => sheet_names
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(wanted))
+            .with_context(|| {
+                format!(
+                    "workbook has no sheet {wanted:?}; sheets are {}",
+                    sheet_names.join(", ")
+                )
+            })?,
+        None => sheet_names.first().context("workbook has no sheets")?,
+    }
+    .clone();
+    let range = workbook
+        .worksheet_range(&sheet)
+        .with_context(|| format!("unable to read sheet {sheet:?}"))?;
+
+    let mut rows = range.rows().skip(options.header_row_offset);
+    let Some(header_row) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let headers = header_row
+        .iter()
+        .map(|cell| cell_value(cell).map(|value| value_text(&value)))
+        .collect::<Vec<_>>();
+
+    Ok(rows
+        .filter_map(|row| {
+            let record = headers
+                .iter()
+                .zip(row)
+                .filter_map(|(header, cell)| Some((header.clone()?, cell_value(cell)?)))
+                .collect::<Map<_, _>>();
+            (!record.is_empty()).then_some(record)
+        })
+        .collect())
+}
+
+/// A cell as JSON, with Excel dates written as naive date or date-time text so they are
+/// read as Pacific time like every other source date. Empty and error cells are `None`.
+fn cell_value(cell: &Data) -> Option<Value> {
+    match cell {
+        Data::Int(number) => Some(Value::from(*number)),
+        // Excel stores every number as a float; whole ones are usually IDs or ZIP codes.
+        Data::Float(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+            Some(Value::from(*number as i64))
+        }
+        Data::Float(number) => Number::from_f64(*number).map(Value::Number),
+        Data::String(text) => {
+            let text = text.trim();
+            (!text.is_empty()).then(|| Value::String(text.to_owned()))
+        }
+        Data::Bool(flag) => Some(Value::Bool(*flag)),
+        Data::DateTime(date) => {
+            let naive = date.as_datetime()?;
+            let text = if naive.time() == chrono::NaiveTime::MIN {
+                naive.format("%Y-%m-%d").to_string()
+            } else {
+                naive.format("%Y-%m-%d %H:%M:%S").to_string()
+            };
+            Some(Value::String(text))
+        }
+        Data::DateTimeIso(text) | Data::DurationIso(text) => Some(Value::String(text.clone())),
+        Data::Error(_) | Data::Empty => None,
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub(super) fn parse_json_records(body: &str) -> Result<Vec<Map<String, Value>>> {
+    let value: Value = serde_json::from_str(body).context("JSON export parse failed")?;
+
+    let map_list = if let Some(array) = value.as_array() {
+        array
+            .iter()
+            .filter_map(|item| item.as_object().cloned())
+            .collect::<Vec<_>>()
+    } else if let Some(object) = value.as_object() {
+        if let Some(features) = object.get("features").and_then(Value::as_array) {
+            features
+                .iter()
+                .filter_map(|item| item.get("attributes"))
+                .filter_map(Value::as_object)
+                .cloned()
+                .collect::<Vec<_>>()
+        } else {
+            ["data", "results", "records", "value"]
+                .iter()
+                .find_map(|key| object.get(*key))
+                .and_then(Value::as_array)
+                .map(|array| {
+                    array
+                        .iter()
+                        .filter_map(Value::as_object)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        }
+    } else {
+        Vec::new()
+    };
+
+    Ok(map_list)
+}
+
+pub(super) fn parse_csv_records(body: &str) -> Result<Vec<Map<String, Value>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(body.trim_start_matches('\u{feff}').as_bytes());
+
+    let headers = reader
+        .headers()
+        .context("CSV headers parse failed")?
+        .iter()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row.context("CSV row parse failed")?;
+        let mut map = Map::new();
+
+        for (idx, value) in row.iter().enumerate() {
+            if let Some(header) = headers.get(idx) {
+                map.insert(header.clone(), Value::String(value.trim().to_owned()));
+            }
+        }
+
+        records.push(map);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use serde_json::json;
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::{SheetOptions, parse_export};
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(body).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// A minimal XLSX with inline-string and numeric cells, one sheet per entry.
+    fn xlsx(sheets: &[(&str, &[&[&str]])]) -> Vec<u8> {
+        let mut files = vec![
+            (
+                "[Content_Types].xml".to_owned(),
+                concat!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+                    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+                    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+                    r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+                    r#"</Types>"#
+                )
+                .to_owned(),
+            ),
+            (
+                "_rels/.rels".to_owned(),
+                concat!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+                    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+                    r#"</Relationships>"#
+                )
+                .to_owned(),
+            ),
+        ];
+
+        let mut workbook_sheets = String::new();
+        let mut relationships = String::new();
+        for (index, (name, rows)) in sheets.iter().enumerate() {
+            let id = index + 1;
+            workbook_sheets.push_str(&format!(
+                r#"<sheet name="{name}" sheetId="{id}" r:id="rId{id}"/>"#
+            ));
+            relationships.push_str(&format!(
+                r#"<Relationship Id="rId{id}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{id}.xml"/>"#
+            ));
+
+            let mut data = String::new();
+            for (row_index, row) in rows.iter().enumerate() {
+                data.push_str(&format!(r#"<row r="{}">"#, row_index + 1));
+                for (column, cell) in row.iter().enumerate() {
+                    let reference = format!("{}{}", (b'A' + column as u8) as char, row_index + 1);
+                    if cell.parse::<f64>().is_ok() {
+                        data.push_str(&format!(r#"<c r="{reference}"><v>{cell}</v></c>"#));
+                    } else if !cell.is_empty() {
+                        data.push_str(&format!(
+                            r#"<c r="{reference}" t="inlineStr"><is><t>{cell}</t></is></c>"#
+                        ));
+                    }
+                }
+                data.push_str("</row>");
+            }
+            files.push((
+                format!("xl/worksheets/sheet{id}.xml"),
+                format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>{data}</sheetData></worksheet>"#
+                ),
+            ));
+        }
+        files.push((
+            "xl/workbook.xml".to_owned(),
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>{workbook_sheets}</sheets></workbook>"#
+            ),
+        ));
+        files.push((
+            "xl/_rels/workbook.xml.rels".to_owned(),
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{relationships}</Relationships>"#
+            ),
+        ));
+
+        zip(&files
+            .iter()
+            .map(|(name, body)| (name.as_str(), body.as_bytes()))
+            .collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn reads_the_selected_sheet_below_its_header_offset() {
+        let workbook = xlsx(&[
+            ("Summary", &[&["Closures by month"]]),
+            (
+                "Inspections",
+                &[
+                    &["Orange County Health Care Agency", ""],
+                    &["", ""],
+                    &["Facility", "Score"],
+                    &["Taco Spot", "92"],
+                    &["", ""],
+                    &["Noodle Bar", ""],
+                ],
+            ),
+        ]);
+
+        let files = parse_export(&workbook, |_| SheetOptions {
+            sheet: Some("inspections"),
+            header_row_offset: 2,
+        })
+        .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, None);
+        assert_eq!(
+            files[0].records,
+            vec![
+                json!({"Facility": "Taco Spot", "Score": 92})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+                json!({"Facility": "Noodle Bar"})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ]
+        );
+
+        let error = parse_export(&workbook, |_| SheetOptions {
+            sheet: Some("Closures"),
+            header_row_offset: 0,
+        })
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("sheets are Summary, Inspections"));
+    }
+
+    #[test]
+    fn reads_every_csv_and_workbook_in_a_bundle() {
+        let workbook = xlsx(&[("Sheet1", &[&["Facility"], &["Noodle Bar"]])]);
+        let bundle = zip(&[
+            (
+                "2024/closures.csv",
+                "\u{feff}Facility,Score\nTaco Spot,92\n".as_bytes(),
+            ),
+            ("2024/README.txt", b"See attached.".as_slice()),
+            ("__MACOSX/2024/._closures.csv", b"\0".as_slice()),
+            ("2024/inspections.xlsx", workbook.as_slice()),
+        ]);
+
+        let files = parse_export(&bundle, |_| SheetOptions::default()).unwrap();
+        let summary = files
+            .iter()
+            .map(|file| (file.name.as_deref().unwrap(), file.records.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![("2024/closures.csv", 1), ("2024/inspections.xlsx", 1)]
+        );
+        assert_eq!(files[0].records[0]["Facility"], "Taco Spot");
+        assert_eq!(files[1].records[0]["Facility"], "Noodle Bar");
+
+        let error =
+            parse_export(&zip(&[("notes.txt", b"none")]), |_| SheetOptions::default()).unwrap_err();
+        assert!(format!("{error:#}").contains("no CSV or Excel files"));
+    }
+}
@@ -1,288 +1,153 @@
-use std::{collections::HashMap, env, time::Duration};
-
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
 use reqwest::Client;
-use serde::{Deserialize, de::DeserializeOwned};
-use tracing::warn;
+use serde::Deserialize;
 
 use crate::{
-    application::dto::SourceFacilityInput, domain::entities::Jurisdiction,
-    infrastructure::connectors::HealthDataConnector,
+    application::dto::SourceFacilityInput,
+    domain::entities::Jurisdiction,
+    infrastructure::connectors::{
+        FetchContext, HealthDataConnector, RetryPolicy,
+        arcgis_connector::{ArcGisSettings, ArcGisSource, JoinSettings, http_client},
+        config::{ConnectorSettings, check_positive, check_url, override_limit, override_parsed},
+        mapping::{FieldMap, FieldNames},
+        retry::RetrySettings,
+    },
 };
 
 const DEFAULT_INVENTORY_URL: &str = "https://services.arcgis.com/RmCCgQtiZLDCtblq/arcgis/rest/services/Environmental_Health_Restaurant_and_Market_Inventory_12312025/FeatureServer";
 const DEFAULT_INSPECTIONS_URL: &str = "https://services.arcgis.com/RmCCgQtiZLDCtblq/arcgis/rest/services/Environmental_Health_Restaurant_and_Market_Inspections_01012023_to_123120025/FeatureServer";
 const DEFAULT_PAGE_SIZE: usize = 2_000;
+const DEFAULT_TIMEOUT_SECS: u64 = 20;
 
-#[derive(Clone)]
 pub struct LaCountyConnector {
     client: Client,
-    inventory_url: String,
-    inspections_url: String,
-    page_size: usize,
-    max_records: Option<usize>,
+    source: ArcGisSource,
+    retry_policy: RetryPolicy,
 }
 
-impl Default for LaCountyConnector {
-    fn default() -> Self {
-        Self::from_env()
-    }
+/// `[connectors.<name>]` keys for `type = "la_county"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LaCountySettings {
+    pub inventory_url: String,
+    pub inspections_url: String,
+    pub page_size: usize,
+    pub max_records: Option<usize>,
+    pub timeout_secs: u64,
+    pub retry: RetrySettings,
 }
 
-impl LaCountyConnector {
-    pub fn from_env() -> Self {
-        let inventory_url = env::var("CLEANPLATED_LA_INVENTORY_URL")
-            .unwrap_or_else(|_| DEFAULT_INVENTORY_URL.to_owned());
-        let inspections_url = env::var("CLEANPLATED_LA_INSPECTIONS_URL")
-            .unwrap_or_else(|_| DEFAULT_INSPECTIONS_URL.to_owned());
-        let page_size = env::var("CLEANPLATED_LA_PAGE_SIZE")
-            .ok()
-            .or_else(|| env::var("CLEANPLATED_LA_LIMIT").ok())
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_PAGE_SIZE)
-            .max(1);
-        let max_records = env::var("CLEANPLATED_LA_MAX_RECORDS")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .filter(|value| *value > 0);
-        let timeout_secs = env::var("CLEANPLATED_LA_TIMEOUT_SECS")
-            .ok()
-            .and_then(|value| value.parse::<u64>().ok())
-            .unwrap_or(20);
-
-        let client = Client::builder()
-            .timeout(Duration::from_secs(timeout_secs))
-            .build()
-            .unwrap_or_else(|_| Client::new());
-
+impl Default for LaCountySettings {
+    fn default() -> Self {
         Self {
-            client,
-            inventory_url,
-            inspections_url,
-            page_size,
-            max_records,
+            inventory_url: DEFAULT_INVENTORY_URL.to_owned(),
+            inspections_url: DEFAULT_INSPECTIONS_URL.to_owned(),
+            page_size: DEFAULT_PAGE_SIZE,
+            max_records: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            retry: RetrySettings::default(),
         }
     }
+}
 
-    async fn query_features<T>(
-        &self,
-        source_url: &str,
-        where_clause: &str,
-        out_fields: &str,
-        order_by_fields: Option<&str>,
-    ) -> Result<Vec<T>>
-    where
-        T: DeserializeOwned,
-    {
-        let mut offset = 0usize;
-        let mut rows = Vec::<T>::new();
-
-        loop {
-            let mut query = vec![
-                ("where".to_owned(), where_clause.to_owned()),
-                ("outFields".to_owned(), out_fields.to_owned()),
-                ("resultOffset".to_owned(), offset.to_string()),
-                ("resultRecordCount".to_owned(), self.page_size.to_string()),
-                ("f".to_owned(), "json".to_owned()),
-            ];
-            if let Some(order_by) = order_by_fields {
-                query.push(("orderByFields".to_owned(), order_by.to_owned()));
-            }
-
-            let response: ArcGisResponse<T> = self
-                .client
-                .get(format!("{}/0/query", source_url.trim_end_matches('/')))
-                .query(&query)
-                .send()
-                .await?
-                .error_for_status()?
-                .json()
-                .await?;
-
-            let page_count = response.features.len();
-            rows.extend(response.features.into_iter().map(|f| f.attributes));
-
-            if let Some(max_records) = self.max_records {
-                if rows.len() >= max_records {
-                    rows.truncate(max_records);
-                    break;
-                }
-            }
-
-            if page_count == 0 {
-                break;
-            }
-
-            offset = offset.saturating_add(page_count);
-            let exceeded = response.exceeded_transfer_limit.unwrap_or(false);
-            if !exceeded && page_count < self.page_size {
-                break;
-            }
-        }
+impl ConnectorSettings for LaCountySettings {
+    fn apply_env(&mut self) -> Result<()> {
+        override_parsed(&mut self.inventory_url, "CLEANPLATED_LA_INVENTORY_URL")?;
+        override_parsed(&mut self.inspections_url, "CLEANPLATED_LA_INSPECTIONS_URL")?;
+        override_parsed(&mut self.page_size, "CLEANPLATED_LA_LIMIT")?;
+        override_parsed(&mut self.page_size, "CLEANPLATED_LA_PAGE_SIZE")?;
+        override_limit(&mut self.max_records, "CLEANPLATED_LA_MAX_RECORDS")?;
+        override_parsed(&mut self.timeout_secs, "CLEANPLATED_LA_TIMEOUT_SECS")?;
+        self.retry.apply_env("CLEANPLATED_LA")
+    }
 
-        Ok(rows)
+    fn validate(&self) -> Result<()> {
+        check_url("inventory_url", &self.inventory_url)?;
+        check_url("inspections_url", &self.inspections_url)?;
+        check_positive("page_size", self.page_size as u64)?;
+        check_positive("timeout_secs", self.timeout_secs)?;
+        self.retry.validate()
     }
+}
 
-    async fn fetch_inspections(&self) -> Result<Vec<LaInspectionAttrs>> {
-        self.query_features(
-            &self.inspections_url,
-            "FACILITY_ID IS NOT NULL",
-            "ACTIVITY_DATE,FACILITY_ID,FACILITY_NAME,FACILITY_ADDRESS,FACILITY_CITY,FACILITY_STATE,FACILITY_ZIP,SCORE,GRADE",
-            Some("ACTIVITY_DATE DESC"),
-        )
-        .await
-        .context("LA inspections request failed")
+impl Default for LaCountyConnector {
+    fn default() -> Self {
+        Self::new(LaCountySettings::default())
     }
+}
 
-    async fn fetch_inventory(&self) -> Result<HashMap<String, LaInventoryAttrs>> {
-        let rows: Vec<LaInventoryAttrs> = self
-            .query_features(
-                &self.inventory_url,
-                "FACILITY_ID IS NOT NULL",
-                "FACILITY_ID,FACILITY_NAME,FACILITY_ADDRESS,FACILITY_CITY,FACILITY__STATE,FACILITY_ZIP,FACILITY_LATITUDE,FACILITY_LONGITUDE",
-                None,
-            )
-            .await
-            .context("LA inventory request failed")?;
+impl LaCountyConnector {
+    pub fn new(settings: LaCountySettings) -> Self {
+        let LaCountySettings {
+            inventory_url,
+            inspections_url,
+            page_size,
+            max_records,
+            timeout_secs,
+            retry,
+        } = settings;
+
+        // Inspections carry the score and date; the inventory adds coordinates and
+        // fills fields the inspection row leaves empty.
+        let layer = ArcGisSettings {
+            layer_url: format!("{}/0", inspections_url.trim_end_matches('/')),
+            where_clause: "FACILITY_ID IS NOT NULL".to_owned(),
+            out_fields: "ACTIVITY_DATE,FACILITY_ID,FACILITY_NAME,FACILITY_ADDRESS,FACILITY_CITY,FACILITY_STATE,FACILITY_ZIP,SCORE,GRADE".to_owned(),
+            order_by: Some("ACTIVITY_DATE DESC".to_owned()),
+            page_size,
+            max_records,
+            default_name: Some("Unknown Facility".to_owned()),
+            default_city: Some("Los Angeles".to_owned()),
+            fields: FieldMap {
+                source_id: FieldNames::of(&["FACILITY_ID"]),
+                name: FieldNames::of(&["FACILITY_NAME"]),
+                address: FieldNames::of(&["FACILITY_ADDRESS"]),
+                city: FieldNames::of(&["FACILITY_CITY"]),
+                state: FieldNames::of(&["FACILITY_STATE", "FACILITY__STATE"]),
+                postal_code: FieldNames::of(&["FACILITY_ZIP"]),
+                latitude: FieldNames::of(&["FACILITY_LATITUDE"]),
+                longitude: FieldNames::of(&["FACILITY_LONGITUDE"]),
+                inspected_at: FieldNames::of(&["ACTIVITY_DATE"]),
+                score: FieldNames::of(&["SCORE"]),
+                grade: FieldNames::of(&["GRADE"]),
+                ..FieldMap::default()
+            },
+            joins: vec![JoinSettings {
+                layer_url: format!("{}/0", inventory_url.trim_end_matches('/')),
+                where_clause: "FACILITY_ID IS NOT NULL".to_owned(),
+                out_fields: "FACILITY_ID,FACILITY_NAME,FACILITY_ADDRESS,FACILITY_CITY,FACILITY__STATE,FACILITY_ZIP,FACILITY_LATITUDE,FACILITY_LONGITUDE".to_owned(),
+                key: "FACILITY_ID".to_owned(),
+                on: "FACILITY_ID".to_owned(),
+                required: false,
+                label: Some("inventory".to_owned()),
+            }],
+            ..ArcGisSettings::default()
+        };
 
-        let mut inventory_map = HashMap::with_capacity(rows.len());
-        for attrs in rows {
-            if let Some(id) = attrs.facility_id.clone() {
-                inventory_map.insert(id.trim().to_owned(), attrs);
-            }
+        Self {
+            client: http_client(timeout_secs),
+            source: ArcGisSource::new("inspections", Jurisdiction::LosAngelesCounty, layer),
+            retry_policy: retry.policy(),
         }
-
-        Ok(inventory_map)
     }
 }
 
 #[async_trait]
 impl HealthDataConnector for LaCountyConnector {
-    fn source_name(&self) -> &'static str {
+    fn source_name(&self) -> &str {
         "la_county_open_data"
     }
 
-    async fn fetch_facilities(&self) -> Result<Vec<SourceFacilityInput>> {
-        let inspections = self.fetch_inspections().await?;
-
-        let inventory = match self.fetch_inventory().await {
-            Ok(records) => records,
-            Err(error) => {
-                warn!(error = %error, "LA inventory enrichment failed; proceeding without inventory join");
-                HashMap::new()
-            }
-        };
-
-        let facilities = inspections
-            .into_iter()
-            .filter_map(|inspection| {
-                let facility_id = inspection.facility_id?.trim().to_owned();
-                let inv = inventory.get(&facility_id);
-
-                let (latitude, longitude) = inv
-                    .map(|record| {
-                        (
-                            record.facility_latitude.unwrap_or(34.0522),
-                            record.facility_longitude.unwrap_or(-118.2437),
-                        )
-                    })
-                    .unwrap_or((34.0522, -118.2437));
-
-                let inspected_at = inspection
-                    .activity_date
-                    .and_then(DateTime::from_timestamp_millis)
-                    .unwrap_or_else(Utc::now);
-
-                Some(SourceFacilityInput {
-                    source_id: facility_id,
-                    name: inspection
-                        .facility_name
-                        .or_else(|| inv.and_then(|record| record.facility_name.clone()))
-                        .unwrap_or_else(|| "Unknown Facility".to_owned()),
-                    address: inspection
-                        .facility_address
-                        .or_else(|| inv.and_then(|record| record.facility_address.clone()))
-                        .unwrap_or_default(),
-                    city: inspection
-                        .facility_city
-                        .or_else(|| inv.and_then(|record| record.facility_city.clone()))
-                        .unwrap_or_else(|| "Los Angeles".to_owned()),
-                    state: inspection
-                        .facility_state
-                        .or_else(|| inv.and_then(|record| record.facility_state.clone()))
-                        .unwrap_or_else(|| "CA".to_owned()),
-                    postal_code: inspection
-                        .facility_zip
-                        .or_else(|| inv.and_then(|record| record.facility_zip.clone()))
-                        .unwrap_or_default(),
-                    latitude,
-                    longitude,
-                    jurisdiction: Jurisdiction::LosAngelesCounty,
-                    inspected_at,
-                    raw_score: inspection.score.map(|score| score as f32),
-                    letter_grade: inspection.grade,
-                    placard_status: None,
-                    violations: Vec::new(),
-                })
-            })
-            .collect::<Vec<_>>();
-
-        Ok(facilities)
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        vec![Jurisdiction::LosAngelesCounty]
     }
-}
-
-#[derive(Debug, Deserialize)]
-struct ArcGisResponse<T> {
-    #[serde(rename = "exceededTransferLimit")]
-    exceeded_transfer_limit: Option<bool>,
-    features: Vec<ArcGisFeature<T>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ArcGisFeature<T> {
-    attributes: T,
-}
 
-#[derive(Debug, Deserialize)]
-struct LaInspectionAttrs {
-    #[serde(rename = "ACTIVITY_DATE")]
-    activity_date: Option<i64>,
-    #[serde(rename = "FACILITY_ID")]
-    facility_id: Option<String>,
-    #[serde(rename = "FACILITY_NAME")]
-    facility_name: Option<String>,
-    #[serde(rename = "FACILITY_ADDRESS")]
-    facility_address: Option<String>,
-    #[serde(rename = "FACILITY_CITY")]
-    facility_city: Option<String>,
-    #[serde(rename = "FACILITY_STATE")]
-    facility_state: Option<String>,
-    #[serde(rename = "FACILITY_ZIP")]
-    facility_zip: Option<String>,
-    #[serde(rename = "SCORE")]
-    score: Option<f64>,
-    #[serde(rename = "GRADE")]
-    grade: Option<String>,
-}
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
 
-#[derive(Debug, Deserialize)]
-struct LaInventoryAttrs {
-    #[serde(rename = "FACILITY_ID")]
-    facility_id: Option<String>,
-    #[serde(rename = "FACILITY_NAME")]
-    facility_name: Option<String>,
-    #[serde(rename = "FACILITY_ADDRESS")]
-    facility_address: Option<String>,
-    #[serde(rename = "FACILITY_CITY")]
-    facility_city: Option<String>,
-    #[serde(rename = "FACILITY__STATE")]
-    facility_state: Option<String>,
-    #[serde(rename = "FACILITY_ZIP")]
-    facility_zip: Option<String>,
-    #[serde(rename = "FACILITY_LATITUDE")]
-    facility_latitude: Option<f64>,
-    #[serde(rename = "FACILITY_LONGITUDE")]
-    facility_longitude: Option<f64>,
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
+        self.source.fetch(&self.client, context).await
+    }
 }
@@ -0,0 +1,544 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use reqwest::Client;
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::{Jurisdiction, Violation},
+    infrastructure::connectors::{
+        FetchContext, ResponseStatusExt,
+        export_files::{ExportFile, SheetOptions, parse_export},
+        mapping::{FieldMap, FieldNames, RecordMapper, attr_number, attr_text},
+    },
+};
+
+const BUSINESSES: &str = "businesses.csv";
+const INSPECTIONS: &str = "inspections.csv";
+const VIOLATIONS: &str = "violations.csv";
+const FEED_INFO: &str = "feed_info.csv";
+/// Code given to violations published without one.
+const DEFAULT_VIOLATION_CODE: &str = "LIVES";
+
+/// A batch in the LIVES open standard: a ZIP of `businesses.csv`, `inspections.csv`,
+/// `violations.csv` and optionally `feed_info.csv`, read from a URL or a local path.
+pub(super) struct LivesBatch {
+    location: String,
+    jurisdiction: Jurisdiction,
+    id_prefix: String,
+}
+
+impl LivesBatch {
+    /// `location` is an http(s) URL or a local file path; `id_prefix` labels its page.
+    pub(super) fn new(location: &str, jurisdiction: Jurisdiction, id_prefix: &str) -> Self {
+        Self {
+            location: location.to_owned(),
+            jurisdiction,
+            id_prefix: id_prefix.to_owned(),
+        }
+    }
+
+    pub(super) fn jurisdiction(&self) -> &Jurisdiction {
+        &self.jurisdiction
+    }
+
+    pub(super) async fn fetch(
+        &self,
+        client: &Client,
+        context: &FetchContext,
+    ) -> Result<Vec<SourceFacilityInput>> {
+        let label = self.jurisdiction.label();
+        let body = context
+            .fetch_bytes(&format!("{} batch", self.id_prefix), || async {
+                if !is_url(&self.location) {
+                    return tokio::fs::read(&self.location)
+                        .await
+                        .with_context(|| format!("unable to read {label} LIVES batch"));
+                }
+                client
+                    .get(&self.location)
+                    .send()
+                    .await
+                    .with_context(|| format!("{label} LIVES batch request failed"))?
+                    .ensure_success()
+                    .with_context(|| format!("{label} LIVES batch returned non-success status"))?
+                    .bytes()
+                    .await
+                    .map(Vec::from)
+                    .with_context(|| format!("{label} LIVES batch body read failed"))
+            })
+            .await?;
+
+        let feed = parse_feed(&body, &self.jurisdiction)
+            .with_context(|| format!("{label} LIVES batch parse failed"))?;
+        if let Some(feed_info) = &feed.info {
+            info!(
+                jurisdiction = self.jurisdiction.code(),
+                municipality = feed_info.municipality_name.as_deref().unwrap_or_default(),
+                feed_date = feed_info.feed_date.as_deref().unwrap_or_default(),
+                feed_version = feed_info.feed_version.as_deref().unwrap_or_default(),
+                records = feed.records.len(),
+                "Read LIVES batch"
+            );
+        }
+        Ok(feed.records)
+    }
+}
+
+/// A batch location given as a URL rather than a local path.
+pub(super) fn is_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// The publisher's description of a batch, from `feed_info.csv`.
+#[derive(Debug, Default)]
+pub(super) struct FeedInfo {
+    pub feed_date: Option<String>,
+    pub feed_version: Option<String>,
+    pub municipality_name: Option<String>,
+}
+
+#[derive(Debug)]
+pub(super) struct LivesFeed {
+    pub info: Option<FeedInfo>,
+    /// One record per inspection, carrying its business's details and violations.
+    pub records: Vec<SourceFacilityInput>,
+}
+
+/// Joins a LIVES batch into inspection records. Inspections and violations are tied to
+/// a business by `business_id` and to each other by `date` (`YYYYMMDD`), since LIVES
+/// has no inspection ID, plus `type` when the violation row has one. A violation without
+/// a type on a day with several visits goes to the scored visit, else the routine one,
+/// else the first, and is counted as ambiguous. Violations matching no published
+/// inspection become an unscored visit of their own, and a business with no inspections
+/// is passed on undated. Rows naming an unknown business are skipped.
+pub(super) fn parse_feed(body: &[u8], jurisdiction: &Jurisdiction) -> Result<LivesFeed> {
+    let mut files = parse_export(body, |_| SheetOptions::default())?;
+    let mut take = |file: &str| {
+        let index = files.iter().position(|export| {
+            export.name.as_deref().is_some_and(|name| {
+                name.rsplit('/')
+                    .next()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(file))
+            })
+        })?;
+        Some(files.swap_remove(index))
+    };
+    let missing = |file: &str| {
+        format!(
+            "LIVES batch has no {file}; expected a ZIP of {BUSINESSES}, {INSPECTIONS} and {VIOLATIONS}"
+        )
+    };
+    let businesses = take(BUSINESSES).with_context(|| missing(BUSINESSES))?;
+    let inspections = take(INSPECTIONS).with_context(|| missing(INSPECTIONS))?;
+    let violations = take(VIOLATIONS).unwrap_or(ExportFile {
+        name: None,
+        records: Vec::new(),
+    });
+    let info = take(FEED_INFO)
+        .and_then(|file| file.records.into_iter().next())
+        .map(|row| FeedInfo {
+            feed_date: attr_text(&row, "feed_date"),
+            feed_version: attr_text(&row, "feed_version"),
+            municipality_name: attr_text(&row, "municipality_name"),
+        });
+
+    let fields = lives_fields();
+    let mapper = RecordMapper {
+        jurisdiction,
+        fields: &fields,
+        id_prefix: None,
+        default_name: None,
+        default_city: None,
+        default_state: "CA",
+        grade_from_score: true,
+    };
+    let by_id = businesses
+        .records
+        .iter()
+        .filter_map(|business| Some((attr_text(business, "business_id")?, business)))
+        .collect::<HashMap<_, _>>();
+
+    let mut skipped = 0usize;
+    let mut dated_violations = BTreeMap::<VisitKey, Vec<Violation>>::new();
+    for row in &violations.records {
+        match attr_text(row, "business_id").zip(visit_date(row)) {
+            Some((business_id, date)) if by_id.contains_key(&business_id) => {
+                let kind = attr_text(row, "type").map(|value| inspection_type(&value));
+                dated_violations
+                    .entry((business_id, date, kind))
+                    .or_default()
+                    .push(violation(row));
+            }
+            _ => skipped += 1,
+        }
+    }
+
+    let mut visits = Vec::with_capacity(inspections.records.len());
+    for row in &inspections.records {
+        let Some((business_id, business)) = attr_text(row, "business_id").and_then(|id| {
+            by_id
+                .get_key_value(&id)
+                .map(|(id, business)| (id.clone(), *business))
+        }) else {
+            skipped += 1;
+            continue;
+        };
+        let date = visit_date(row);
+        let inspection_type = attr_text(row, "type").map(|value| inspection_type(&value));
+        let mut row = row.clone();
+        if let Some(date) = &date {
+            row.insert("date".to_owned(), Value::String(date.clone()));
+        }
+        // LIVES leaves follow-up and complaint visits unscored, and some exporters
+        // write that blank as 0; a 0 there would otherwise read as a failing score.
+        if matches!(inspection_type.as_deref(), Some("followup" | "complaint"))
+            && attr_number(&row, "score") == Some(0.0)
+        {
+            row.remove("score");
+        }
+        visits.push(Visit {
+            business_id,
+            business,
+            date,
+            inspection_type,
+            row,
+        });
+    }
+
+    // Untyped violations go to one visit per business and day, picked on purpose so
+    // the result does not depend on row order.
+    let mut same_day = HashMap::<(&str, &str), Vec<usize>>::new();
+    for (index, visit) in visits.iter().enumerate() {
+        if let Some(date) = &visit.date {
+            same_day
+                .entry((&visit.business_id, date))
+                .or_default()
+                .push(index);
+        }
+    }
+    let mut untyped_owner = HashMap::new();
+    let mut ambiguous_days = HashSet::new();
+    for (day, indexes) in &same_day {
+        let owner = indexes
+            .iter()
+            .copied()
+            .find(|&index| attr_number(&visits[index].row, "score").is_some())
+            .or_else(|| {
+                indexes
+                    .iter()
+                    .copied()
+                    .find(|&index| visits[index].inspection_type.as_deref() == Some("routine"))
+            })
+            .unwrap_or(indexes[0]);
+        untyped_owner.insert(owner, (day.0.to_owned(), day.1.to_owned()));
+        if indexes.len() > 1 {
+            ambiguous_days.insert(owner);
+        }
+    }
+
+    let mut ambiguous = 0usize;
+    let mut records = Vec::new();
+    let mut inspected = HashSet::new();
+    for (index, visit) in visits.iter().enumerate() {
+        let Visit {
+            business_id,
+            business,
+            date,
+            inspection_type,
+            row,
+        } = visit;
+        let mut record = mapper.map(records.len(), &[row, *business], None);
+        record.inspection_id = date
+            .as_deref()
+            .map(|date| visit_id(business_id, date, inspection_type.as_deref()));
+        record.inspection_type = inspection_type.clone();
+        if let (Some(date), Some(kind)) = (date, inspection_type) {
+            record.violations = dated_violations
+                .remove(&(business_id.clone(), date.clone(), Some(kind.clone())))
+                .unwrap_or_default();
+        }
+        if let Some((business_id, date)) = untyped_owner.remove(&index) {
+            let untyped = dated_violations
+                .remove(&(business_id, date, None))
+                .unwrap_or_default();
+            if ambiguous_days.contains(&index) {
+                ambiguous += untyped.len();
+            }
+            record.violations.extend(untyped);
+        }
+        record.source_file = inspections.name.clone();
+        inspected.insert(business_id.clone());
+        records.push(record);
+    }
+
+    for ((business_id, date, kind), violations_on_date) in dated_violations {
+        let visit = Map::from_iter([("date".to_owned(), Value::String(date.clone()))]);
+        let mut record = mapper.map(records.len(), &[&visit, by_id[&business_id]], None);
+        record.inspection_id = Some(visit_id(&business_id, &date, kind.as_deref()));
+        record.inspection_type = kind;
+        record.violations = violations_on_date;
+        record.source_file = violations.name.clone();
+        inspected.insert(business_id);
+        records.push(record);
+    }
+
+    // Businesses without an ID are passed on so record validation rejects them visibly.
+    for business in &businesses.records {
+        let known = attr_text(business, "business_id").is_some_and(|id| inspected.contains(&id));
+        if !known {
+            let mut record = mapper.map(records.len(), &[business], None);
+            record.source_file = businesses.name.clone();
+            records.push(record);
+        }
+    }
+
+    if skipped > 0 {
+        warn!(
+            jurisdiction = jurisdiction.code(),
+            rows = skipped,
+            "LIVES inspection or violation rows name an unknown business or lack a date; skipped"
+        );
+    }
+    if ambiguous > 0 {
+        warn!(
+            jurisdiction = jurisdiction.code(),
+            rows = ambiguous,
+            "LIVES violation rows without a type fall on a day with several inspections; attached to the scored or routine one"
+        );
+    }
+    Ok(LivesFeed { info, records })
+}
+
+/// A violation's business, `YYYY-MM-DD` date and visit type when the row gives one.
+type VisitKey = (String, String, Option<String>);
+
+/// An inspection row tied to its business, with its date and type normalized.
+struct Visit<'a> {
+    business_id: String,
+    business: &'a Map<String, Value>,
+    date: Option<String>,
+    inspection_type: Option<String>,
+    row: Map<String, Value>,
+}
+
+/// LIVES column names. `score` is on a 0-100 scale and blank when a visit was not
+/// scored; record validation drops anything outside that range.
+fn lives_fields() -> FieldMap {
+    FieldMap {
+        source_id: FieldNames::of(&["business_id"]),
+        name: FieldNames::of(&["name"]),
+        address: FieldNames::of(&["address"]),
+        city: FieldNames::of(&["city"]),
+        state: FieldNames::of(&["state"]),
+        postal_code: FieldNames::of(&["postal_code"]),
+        latitude: FieldNames::of(&["latitude"]),
+        longitude: FieldNames::of(&["longitude"]),
+        inspected_at: FieldNames::of(&["date"]),
+        score: FieldNames::of(&["score"]),
+        ..FieldMap::default()
+    }
+}
+
+/// A row's `date` as `YYYY-MM-DD`. LIVES dates are `YYYYMMDD`, which would otherwise
+/// read as an epoch; other spellings are kept for the usual date parser.
+fn visit_date(row: &Map<String, Value>) -> Option<String> {
+    let date = attr_text(row, "date")?;
+    Some(match NaiveDate::parse_from_str(&date, "%Y%m%d") {
+        Ok(parsed) => parsed.format("%Y-%m-%d").to_string(),
+        Err(_) => date,
+    })
+}
+
+/// Spec values are `initial`, `routine`, `followup` and `complaint`; feeds also spell
+/// the follow-up `follow-up`, `Follow Up` or `reinspection`.
+fn inspection_type(value: &str) -> String {
+    let value = value.trim().to_ascii_lowercase();
+    match value.replace(['-', '_', ' '], "").as_str() {
+        "followup" | "reinspection" => "followup".to_owned(),
+        _ => value,
+    }
+}
+
+/// A stable ID for the visit, kept apart from another kind of visit on the same day.
+fn visit_id(business_id: &str, date: &str, inspection_type: Option<&str>) -> String {
+    let date = date.replace('-', "");
+    match inspection_type {
+        Some(kind) => format!("{business_id}-{date}-{kind}"),
+        None => format!("{business_id}-{date}"),
+    }
+}
+
+/// LIVES violations carry a code and description only; a `risk_category` column, as
+/// some publishers add, marks high-risk violations critical.
+fn violation(row: &Map<String, Value>) -> Violation {
+    let risk = attr_text(row, "risk_category")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    Violation {
+        code: attr_text(row, "code").unwrap_or_else(|| DEFAULT_VIOLATION_CODE.to_owned()),
+        description: attr_text(row, "description").unwrap_or_default(),
+        points: 0,
+        critical: risk.contains("high") || risk.contains("critical"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::parse_feed;
+    use crate::domain::entities::{DatePrecision, Jurisdiction, ValidationReason};
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(body.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn joins_businesses_inspections_and_violations() {
+        let batch = zip(&[
+            (
+                "riv_lives/businesses.csv",
+                "business_id,name,address,city,state,postal_code,latitude,longitude\n\
+                 101,Taco Spot,1 Main St,Riverside,CA,92501,33.98,-117.37\n\
+                 102,Noodle Bar,2 Main St,Riverside,CA,92501,,\n",
+            ),
+            (
+                "riv_lives/inspections.csv",
+                "business_id,score,date,type\n\
+                 101,92,20240301,routine\n\
+                 101,0,20240315,Follow-Up\n\
+                 999,80,20240301,routine\n",
+            ),
+            (
+                "riv_lives/violations.csv",
+                "business_id,date,code,description,risk_category\n\
+                 101,20240301,F023,Hand sink blocked,High Risk\n\
+                 101,20240301,F044,Floors dirty,Low Risk\n\
+                 101,20240402,,Pests observed,\n",
+            ),
+            (
+                "riv_lives/feed_info.csv",
+                "feed_date,feed_version,municipality_name\n20240405,1.0,Riverside County\n",
+            ),
+        ]);
+
+        let feed = parse_feed(&batch, &Jurisdiction::RiversideCounty).unwrap();
+        assert_eq!(
+            feed.info.unwrap().municipality_name.as_deref(),
+            Some("Riverside County")
+        );
+        let records = feed.records;
+        let ids = records
+            .iter()
+            .map(|record| (record.source_id.as_str(), record.inspection_id.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                ("101", Some("101-20240301-routine")),
+                ("101", Some("101-20240315-followup")),
+                ("101", Some("101-20240402")),
+                ("102", None),
+            ]
+        );
+
+        let routine = &records[0];
+        assert_eq!(routine.name, "Taco Spot");
+        assert_eq!(routine.raw_score, Some(92.0));
+        assert_eq!(routine.letter_grade.as_deref(), Some("A"));
+        assert_eq!(routine.inspection_type.as_deref(), Some("routine"));
+        assert_eq!(routine.inspected_at_precision, Some(DatePrecision::Date));
+        assert_eq!(routine.violations.len(), 2);
+        assert!(routine.violations[0].critical && !routine.violations[1].critical);
+        assert_eq!(
+            routine.source_file.as_deref(),
+            Some("riv_lives/inspections.csv")
+        );
+
+        // The follow-up's 0 is LIVES for "not scored".
+        assert_eq!(records[1].raw_score, None);
+        assert_eq!(records[1].letter_grade, None);
+        assert_eq!(records[2].violations[0].code, "LIVES");
+        assert_eq!(records[2].raw_score, None);
+        assert!(
+            records[3]
+                .repairs
+                .contains(&ValidationReason::InspectionDateMissing)
+        );
+
+        let missing = parse_feed(
+            &zip(&[("businesses.csv", "business_id,name\n")]),
+            &Jurisdiction::RiversideCounty,
+        )
+        .unwrap_err();
+        assert!(format!("{missing:#}").contains("LIVES batch has no inspections.csv"));
+    }
+
+    #[test]
+    fn splits_same_day_violations_by_type_and_gives_untyped_ones_to_the_scored_visit() {
+        let batch = zip(&[
+            (
+                "businesses.csv",
+                "business_id,name,address,city,state,postal_code
+                 101,Taco Spot,1 Main St,Riverside,CA,92501
+",
+            ),
+            // The follow-up is listed first, so row order alone would hand it everything.
+            (
+                "inspections.csv",
+                "business_id,score,date,type
+                 101,,20240301,followup
+                 101,92,20240301,routine
+",
+            ),
+            (
+                "violations.csv",
+                "business_id,date,code,description,type
+                 101,20240301,F023,Hand sink blocked,
+                 101,20240301,F044,Floors dirty,
+                 101,20240301,F100,Sink still blocked,Follow-Up
+                 101,20240301,F200,Rodent report,complaint
+",
+            ),
+        ]);
+
+        let records = parse_feed(&batch, &Jurisdiction::RiversideCounty)
+            .unwrap()
+            .records;
+        let visits = records
+            .iter()
+            .map(|record| {
+                (
+                    record.inspection_id.as_deref().unwrap(),
+                    record
+                        .violations
+                        .iter()
+                        .map(|violation| violation.code.as_str())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            visits,
+            vec![
+                ("101-20240301-followup", vec!["F100"]),
+                ("101-20240301-routine", vec!["F023", "F044"]),
+                ("101-20240301-complaint", vec!["F200"]),
+            ]
+        );
+        assert_eq!(records[2].inspection_type.as_deref(), Some("complaint"));
+    }
+}
@@ -1,14 +1,20 @@
-use std::{env, time::Duration};
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
-use chrono::{NaiveDate, TimeZone, Utc};
 use reqwest::Client;
 use scraper::{Html, Selector};
+use serde::Deserialize;
 
 use crate::{
-    application::dto::SourceFacilityInput, domain::entities::Jurisdiction,
-    infrastructure::connectors::HealthDataConnector,
+    application::dto::SourceFacilityInput,
+    domain::entities::{Jurisdiction, LocationPrecision, ValidationReason},
+    infrastructure::connectors::{
+        FetchContext, HealthDataConnector, ResponseStatusExt, RetryPolicy,
+        config::{ConnectorSettings, check_positive, check_url, override_parsed},
+        dates::{self, SOURCE_TIME_ZONE},
+        retry::RetrySettings,
+    },
 };
 
 const DEFAULT_CLOSURES_URL: &str =
@@ -16,32 +22,73 @@ const DEFAULT_CLOSURES_URL: &str =
 const DEFAULT_CLOSURES_URL_FALLBACK: &str =
     "https://longbeach.gov/health/inspections-and-reporting/inspections/restaurant-closures/";
 const DEFAULT_LIMIT: usize = 2_000;
+const DEFAULT_TIMEOUT_SECS: u64 = 20;
 
 #[derive(Clone)]
 pub struct LongBeachConnector {
     client: Client,
     closures_url: String,
     limit: usize,
+    retry_policy: RetryPolicy,
+}
+
+/// `[connectors.<name>]` keys for `type = "long_beach"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LongBeachSettings {
+    pub closures_url: String,
+    /// Most closures kept from the page.
+    pub limit: usize,
+    pub timeout_secs: u64,
+    pub retry: RetrySettings,
+}
+
+impl Default for LongBeachSettings {
+    fn default() -> Self {
+        Self {
+            closures_url: DEFAULT_CLOSURES_URL.to_owned(),
+            limit: DEFAULT_LIMIT,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            retry: RetrySettings::default(),
+        }
+    }
+}
+
+impl ConnectorSettings for LongBeachSettings {
+    fn apply_env(&mut self) -> Result<()> {
+        override_parsed(
+            &mut self.closures_url,
+            "CLEANPLATED_LONG_BEACH_CLOSURES_URL",
+        )?;
+        override_parsed(&mut self.limit, "CLEANPLATED_LONG_BEACH_LIMIT")?;
+        override_parsed(
+            &mut self.timeout_secs,
+            "CLEANPLATED_LONG_BEACH_TIMEOUT_SECS",
+        )?;
+        self.retry.apply_env("CLEANPLATED_LONG_BEACH")
+    }
+
+    fn validate(&self) -> Result<()> {
+        check_url("closures_url", &self.closures_url)?;
+        check_positive("timeout_secs", self.timeout_secs)?;
+        self.retry.validate()
+    }
 }
 
 impl Default for LongBeachConnector {
     fn default() -> Self {
-        Self::from_env()
+        Self::new(LongBeachSettings::default())
     }
 }
 
 impl LongBeachConnector {
-    pub fn from_env() -> Self {
-        let closures_url = env::var("CLEANPLATED_LONG_BEACH_CLOSURES_URL")
-            .unwrap_or_else(|_| DEFAULT_CLOSURES_URL.to_owned());
-        let limit = env::var("CLEANPLATED_LONG_BEACH_LIMIT")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_LIMIT);
-        let timeout_secs = env::var("CLEANPLATED_LONG_BEACH_TIMEOUT_SECS")
-            .ok()
-            .and_then(|value| value.parse::<u64>().ok())
-            .unwrap_or(20);
+    pub fn new(settings: LongBeachSettings) -> Self {
+        let LongBeachSettings {
+            closures_url,
+            limit,
+            timeout_secs,
+            retry,
+        } = settings;
 
         let client = Client::builder()
             .timeout(Duration::from_secs(timeout_secs))
@@ -53,18 +100,31 @@ impl LongBeachConnector {
             client,
             closures_url,
             limit,
+            retry_policy: retry.policy(),
         }
     }
 }
 
 #[async_trait]
 impl HealthDataConnector for LongBeachConnector {
-    fn source_name(&self) -> &'static str {
+    fn source_name(&self) -> &str {
         "long_beach_closures_page"
     }
 
-    async fn fetch_facilities(&self) -> Result<Vec<SourceFacilityInput>> {
-        let html = fetch_long_beach_html(&self.client, &self.closures_url).await?;
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        vec![Jurisdiction::LongBeach]
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
+        let html = context
+            .fetch_text("closures", || {
+                fetch_long_beach_html(&self.client, &self.closures_url)
+            })
+            .await?;
 
         let document = Html::parse_document(&html);
         let row_selector = Selector::parse("table tr").expect("valid row selector");
@@ -112,7 +172,12 @@ impl HealthDataConnector for LongBeachConnector {
             let date_reopened = cells[2].join(" ");
             let reason = cells[3].join(" ");
 
-            let inspected_at = parse_long_beach_date(&date_closed).unwrap_or_else(Utc::now);
+            // The closures page has no coordinates; every row gets the city hall point.
+            let mut repairs = vec![ValidationReason::CoordinatesDefaulted];
+            let inspection_date = dates::parse(&date_closed);
+            if inspection_date.is_none() {
+                repairs.push(ValidationReason::InspectionDateMissing);
+            }
             let is_currently_closed = date_reopened.trim().is_empty();
 
             let (raw_score, letter_grade, placard_status) = if is_currently_closed {
@@ -124,7 +189,14 @@ impl HealthDataConnector for LongBeachConnector {
             facilities.push(SourceFacilityInput {
                 source_id: format!(
                     "lb-closure-{}-{}",
-                    inspected_at.date_naive(),
+                    inspection_date.map_or_else(
+                        || "undated".to_owned(),
+                        |date| date
+                            .at
+                            .with_timezone(&SOURCE_TIME_ZONE)
+                            .date_naive()
+                            .to_string()
+                    ),
                     slugify(&name)
                 ),
                 name,
@@ -134,8 +206,12 @@ impl HealthDataConnector for LongBeachConnector {
                 postal_code: String::new(),
                 latitude: 33.7701,
                 longitude: -118.1937,
+                location_precision: LocationPrecision::JurisdictionDefault,
                 jurisdiction: Jurisdiction::LongBeach,
-                inspected_at,
+                inspection_id: None,
+                inspected_at: inspection_date.map(|date| date.at),
+                inspected_at_precision: inspection_date.map(|date| date.precision),
+                inspection_type: None,
                 raw_score,
                 letter_grade,
                 placard_status,
@@ -145,6 +221,8 @@ impl HealthDataConnector for LongBeachConnector {
                     points: 0,
                     critical: true,
                 }],
+                repairs,
+                source_file: None,
             });
 
             if facilities.len() >= self.limit {
@@ -169,46 +247,45 @@ async fn fetch_long_beach_html(client: &Client, primary_url: &str) -> Result<Str
     }
 
     let mut errors = Vec::new();
+    let mut last_error = None;
     for url in urls {
-        match client
-            .get(&url)
-            .header(
-                reqwest::header::ACCEPT,
-                "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
-            )
-            .send()
-            .await
-        {
-            Ok(response) => match response.error_for_status() {
-                Ok(ok) => match ok.text().await {
-                    Ok(body) if !body.trim().is_empty() => return Ok(body),
-                    Ok(_) => errors.push(format!("{url}: empty response body")),
-                    Err(error) => errors.push(format!("{url}: body read failed: {error}")),
-                },
-                Err(error) => errors.push(format!("{url}: non-success status: {error}")),
-            },
-            Err(error) => errors.push(format!("{url}: request failed: {error}")),
+        let result = async {
+            let body = client
+                .get(&url)
+                .header(
+                    reqwest::header::ACCEPT,
+                    "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+                )
+                .send()
+                .await
+                .context("request failed")?
+                .ensure_success()
+                .context("non-success status")?
+                .text()
+                .await
+                .context("body read failed")?;
+            if body.trim().is_empty() {
+                anyhow::bail!("empty response body");
+            }
+            Ok(body)
+        }
+        .await;
+
+        match result {
+            Ok(body) => return Ok(body),
+            Err(error) => {
+                errors.push(format!("{url}: {error:#}"));
+                last_error = Some(error);
+            }
         }
     }
 
-    anyhow::bail!(
+    // Keep the last underlying error in the chain so the retry policy can classify it.
+    let error = last_error.expect("at least one closures URL is always tried");
+    Err(error.context(format!(
         "Long Beach closures page request failed across all endpoints: {}",
         errors.join(" | ")
-    );
-}
-
-fn parse_long_beach_date(value: &str) -> Option<chrono::DateTime<Utc>> {
-    let cleaned = value.trim();
-    if cleaned.is_empty() {
-        return None;
-    }
-
-    let date = NaiveDate::parse_from_str(cleaned, "%m/%d/%Y")
-        .ok()
-        .or_else(|| NaiveDate::parse_from_str(cleaned, "%m/%d/%y").ok())
-        .or_else(|| NaiveDate::parse_from_str(cleaned, "%m-%d-%Y").ok())?;
-    let naive = date.and_hms_opt(0, 0, 0)?;
-    Some(Utc.from_utc_datetime(&naive))
+    )))
 }
 
 fn slugify(value: &str) -> String {
@@ -0,0 +1,230 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::{Jurisdiction, LocationPrecision, ValidationReason},
+    infrastructure::connectors::dates::{self, SourceDate},
+};
+
+/// Source attribute names read for each facility field, tried in order across a
+/// primary record and then any joined records.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldMap {
+    pub source_id: FieldNames,
+    pub name: FieldNames,
+    pub address: FieldNames,
+    pub city: FieldNames,
+    pub state: FieldNames,
+    pub postal_code: FieldNames,
+    pub latitude: FieldNames,
+    pub longitude: FieldNames,
+    /// A point column (GeoJSON `coordinates` or a `latitude`/`longitude` object), read
+    /// when the latitude/longitude fields are unmapped or empty.
+    pub location: FieldNames,
+    pub inspection_id: FieldNames,
+    pub inspected_at: FieldNames,
+    pub score: FieldNames,
+    pub grade: FieldNames,
+    pub placard_status: FieldNames,
+}
+
+/// One attribute name or a list of candidates.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(from = "OneOrMany")]
+pub struct FieldNames(Vec<String>);
+
+impl FieldNames {
+    pub fn of(names: &[&str]) -> Self {
+        Self(names.iter().map(|name| (*name).to_owned()).collect())
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(super) fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for FieldNames {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(name) => Self(vec![name]),
+            OneOrMany::Many(names) => Self(names),
+        }
+    }
+}
+
+/// Turns mapped source records into facility inputs, substituting the configured
+/// defaults for missing values and recording each substitution as a repair.
+pub(super) struct RecordMapper<'a> {
+    pub jurisdiction: &'a Jurisdiction,
+    pub fields: &'a FieldMap,
+    /// Prefix for a generated `{prefix}-{row}` ID when a record has no source ID.
+    pub id_prefix: Option<&'a str>,
+    pub default_name: Option<&'a str>,
+    /// Defaults to the jurisdiction label.
+    pub default_city: Option<&'a str>,
+    pub default_state: &'a str,
+    pub grade_from_score: bool,
+}
+
+impl RecordMapper<'_> {
+    /// `records` holds the primary record followed by any joined records; `point` is a
+    /// source geometry used when no coordinate field is mapped or filled.
+    pub(super) fn map(
+        &self,
+        index: usize,
+        records: &[&Map<String, Value>],
+        point: Option<(f64, f64)>,
+    ) -> SourceFacilityInput {
+        let fields = self.fields;
+        let mut repairs = Vec::new();
+
+        let text = |names: &FieldNames| first(records, names, attr_text);
+
+        // Records without a source ID are passed on with an empty ID so record
+        // validation rejects them visibly instead of dropping them here.
+        let source_id = text(&fields.source_id)
+            .or_else(|| self.id_prefix.map(|prefix| format!("{prefix}-{index}")))
+            .unwrap_or_default();
+
+        let name = text(&fields.name)
+            .or_else(|| {
+                let name = self.default_name?;
+                repairs.push(ValidationReason::NameDefaulted);
+                Some(name.to_owned())
+            })
+            .unwrap_or_default();
+
+        let coordinates = records
+            .iter()
+            .find_map(|record| {
+                let latitude = fields
+                    .latitude
+                    .names()
+                    .find_map(|name| attr_number(record, name));
+                let longitude = fields
+                    .longitude
+                    .names()
+                    .find_map(|name| attr_number(record, name));
+                latitude.zip(longitude)
+            })
+            .or_else(|| first(records, &fields.location, attr_point))
+            .or(point);
+        let ((latitude, longitude), location_precision) = coordinates
+            .map(|coordinates| (coordinates, LocationPrecision::Rooftop))
+            .unwrap_or_else(|| {
+                repairs.push(ValidationReason::CoordinatesDefaulted);
+                (
+                    self.jurisdiction.default_coordinates(),
+                    LocationPrecision::JurisdictionDefault,
+                )
+            });
+
+        let inspection_date = first(records, &fields.inspected_at, attr_date);
+        if inspection_date.is_none() {
+            repairs.push(ValidationReason::InspectionDateMissing);
+        }
+
+        let raw_score = first(records, &fields.score, attr_number).map(|score| score as f32);
+        let letter_grade = text(&fields.grade).or_else(|| {
+            self.grade_from_score
+                .then_some(raw_score)
+                .flatten()
+                .and_then(score_to_grade)
+        });
+
+        SourceFacilityInput {
+            source_id,
+            name,
+            address: text(&fields.address).unwrap_or_default(),
+            city: text(&fields.city)
+                .or_else(|| self.default_city.map(str::to_owned))
+                .unwrap_or_else(|| self.jurisdiction.label().to_owned()),
+            state: text(&fields.state).unwrap_or_else(|| self.default_state.to_owned()),
+            postal_code: text(&fields.postal_code).unwrap_or_default(),
+            latitude,
+            longitude,
+            location_precision,
+            jurisdiction: self.jurisdiction.clone(),
+            inspection_id: text(&fields.inspection_id),
+            inspected_at: inspection_date.map(|date| date.at),
+            inspected_at_precision: inspection_date.map(|date| date.precision),
+            inspection_type: None,
+            raw_score,
+            letter_grade,
+            placard_status: text(&fields.placard_status),
+            violations: Vec::new(),
+            repairs,
+            source_file: None,
+        }
+    }
+}
+
+/// The first value `read` finds, trying every name on a record before the next record.
+fn first<T>(
+    records: &[&Map<String, Value>],
+    names: &FieldNames,
+    read: fn(&Map<String, Value>, &str) -> Option<T>,
+) -> Option<T> {
+    records
+        .iter()
+        .find_map(|record| names.names().find_map(|name| read(record, name)))
+}
+
+/// A trimmed, non-empty string or number attribute as text.
+pub(super) fn attr_text(record: &Map<String, Value>, name: &str) -> Option<String> {
+    match record.get(name)? {
+        Value::String(text) => Some(text.trim().to_owned()).filter(|text| !text.is_empty()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+pub(super) fn attr_number(record: &Map<String, Value>, name: &str) -> Option<f64> {
+    match record.get(name)? {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(super) fn attr_date(record: &Map<String, Value>, name: &str) -> Option<SourceDate> {
+    record.get(name).and_then(dates::from_json)
+}
+
+/// Reads a point column as `(latitude, longitude)`.
+fn attr_point(record: &Map<String, Value>, name: &str) -> Option<(f64, f64)> {
+    let point = record.get(name)?.as_object()?;
+    if let Some(coordinates) = point.get("coordinates").and_then(Value::as_array) {
+        let longitude = coordinates.first()?.as_f64()?;
+        let latitude = coordinates.get(1)?.as_f64()?;
+        return Some((latitude, longitude));
+    }
+    attr_number(point, "latitude").zip(attr_number(point, "longitude"))
+}
+
+fn score_to_grade(score: f32) -> Option<String> {
+    if !(0.0..=100.0).contains(&score) {
+        return None;
+    }
+
+    if score >= 90.0 {
+        Some("A".to_owned())
+    } else if score >= 80.0 {
+        Some("B".to_owned())
+    } else {
+        Some("C".to_owned())
+    }
+}
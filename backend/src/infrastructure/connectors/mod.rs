@@ -1,34 +1,256 @@
+mod arcgis_connector;
+mod config;
 mod cpra_connector;
+mod cpra_profile;
+mod dates;
+mod drop_folder_connector;
+mod export_files;
 mod la_county_connector;
 mod lives_batch_connector;
+mod lives_feed;
 mod long_beach_connector;
+mod mapping;
+mod retry;
 mod san_diego_connector;
+mod socrata_connector;
 
-use std::sync::Arc;
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 
-use crate::application::dto::SourceFacilityInput;
+use tokio::time::sleep;
+use tracing::warn;
 
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::{ConnectorSyncState, Jurisdiction},
+    infrastructure::archive::{ArchiveRun, ReplaySnapshot},
+};
+
+pub use arcgis_connector::ArcGisConnector;
+pub use config::load_connectors;
 pub use cpra_connector::CpraConnector;
+pub use dates::SOURCE_TIME_ZONE;
+pub use drop_folder_connector::DropFolderConnector;
 pub use la_county_connector::LaCountyConnector;
 pub use lives_batch_connector::LivesBatchConnector;
 pub use long_beach_connector::LongBeachConnector;
+pub use retry::{ResponseStatusExt, RetryPolicy};
 pub use san_diego_connector::SanDiegoConnector;
+pub use socrata_connector::SocrataConnector;
 
 #[async_trait]
 pub trait HealthDataConnector: Send + Sync {
-    fn source_name(&self) -> &'static str;
-    async fn fetch_facilities(&self) -> Result<Vec<SourceFacilityInput>>;
+    fn source_name(&self) -> &str;
+    /// Jurisdictions whose stored facilities are replaced when this connector succeeds.
+    fn jurisdictions(&self) -> Vec<Jurisdiction>;
+    /// Retry policy applied to each page request the connector makes.
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default()
+    }
+    /// Minimum time between fetches; a refresh skips the connector until that long
+    /// after its last success. `None` fetches on every refresh.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>>;
+}
+
+/// Per-run state handed to a connector fetch. A live context calls the source and
+/// optionally archives every response page; a replay context serves the pages a past
+/// run archived and never touches the network.
+#[derive(Clone, Default)]
+pub struct FetchContext {
+    source: Arc<str>,
+    retry: RetryPolicy,
+    archive: Option<ArchiveRun>,
+    replay: Option<Arc<ReplaySnapshot>>,
+    sync: Arc<SyncSlot>,
+    failed_jurisdictions: Arc<Mutex<Vec<(Jurisdiction, String)>>>,
+}
+
+/// Sync state in and out of one fetch: the state stored after the source's last
+/// successful fetch, the state the connector wants stored if this one succeeds, and
+/// the state it wants stored whatever the outcome.
+#[derive(Default)]
+struct SyncSlot {
+    stored: Option<ConnectorSyncState>,
+    updated: Mutex<Option<ConnectorSyncState>>,
+    committed: Mutex<Option<ConnectorSyncState>>,
 }
 
-pub fn default_connectors() -> Vec<Arc<dyn HealthDataConnector>> {
-    vec![
-        Arc::new(LaCountyConnector::from_env()),
-        Arc::new(SanDiegoConnector::from_env()),
-        Arc::new(LongBeachConnector::from_env()),
-        Arc::new(LivesBatchConnector::from_env()),
-        Arc::new(CpraConnector::from_env()),
-    ]
+impl FetchContext {
+    pub fn live(source: &str, retry: RetryPolicy, archive: Option<ArchiveRun>) -> Self {
+        Self {
+            source: Arc::from(source),
+            retry,
+            archive,
+            replay: None,
+            sync: Arc::default(),
+            failed_jurisdictions: Arc::default(),
+        }
+    }
+
+    pub fn replay(source: &str, snapshot: Arc<ReplaySnapshot>) -> Self {
+        Self {
+            source: Arc::from(source),
+            retry: RetryPolicy::default(),
+            archive: None,
+            replay: Some(snapshot),
+            sync: Arc::default(),
+            failed_jurisdictions: Arc::default(),
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Hands an incremental connector the sync state stored after its last success.
+    pub fn with_sync_state(mut self, stored: Option<ConnectorSyncState>) -> Self {
+        self.sync = Arc::new(SyncSlot {
+            stored,
+            ..SyncSlot::default()
+        });
+        self
+    }
+
+    /// Returns the sync state to resume from: the stored state when `resume` accepts
+    /// it, otherwise `None` for a full pull. The choice is archived as the
+    /// `sync state` page, so a replay resumes from the same rows without consulting
+    /// `resume`.
+    pub async fn resume_sync<F>(&self, resume: F) -> Result<Option<ConnectorSyncState>>
+    where
+        F: FnOnce(&ConnectorSyncState) -> bool,
+    {
+        let resumed = self.sync.stored.as_ref().filter(|state| resume(state));
+        let body = serde_json::to_string(&resumed)?;
+        let body = self
+            .fetch_text("sync state", || std::future::ready(Ok(body.clone())))
+            .await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Records the sync state to store once this fetch succeeds. Replays never store.
+    pub fn save_sync_state(&self, state: ConnectorSyncState) {
+        if !self.is_replay() {
+            *self.sync.updated.lock().expect("sync slot lock poisoned") = Some(state);
+        }
+    }
+
+    /// Records sync state to store even if this fetch later fails or runs out of its
+    /// budget, for state that tracks changes the connector already made outside the
+    /// source, such as files it moved. Replays never store.
+    pub fn commit_sync_state(&self, state: ConnectorSyncState) {
+        if !self.is_replay() {
+            *self.sync.committed.lock().expect("sync slot lock poisoned") = Some(state);
+        }
+    }
+
+    /// Takes the state to store after a successful fetch: the one recorded by
+    /// `save_sync_state`, else the one recorded by `commit_sync_state`.
+    pub fn take_sync_state(&self) -> Option<ConnectorSyncState> {
+        self.sync
+            .updated
+            .lock()
+            .expect("sync slot lock poisoned")
+            .take()
+            .or_else(|| self.take_committed_sync_state())
+    }
+
+    /// Takes the state to store after a failed fetch, recorded by `commit_sync_state`.
+    pub fn take_committed_sync_state(&self) -> Option<ConnectorSyncState> {
+        self.sync
+            .committed
+            .lock()
+            .expect("sync slot lock poisoned")
+            .take()
+    }
+
+    /// Records that one of the connector's jurisdictions could not be fetched while
+    /// the fetch as a whole goes on. The refresh then keeps that jurisdiction's
+    /// stored facilities and replaces only the ones the connector did fetch.
+    pub fn fail_jurisdiction(&self, jurisdiction: Jurisdiction, error: &anyhow::Error) {
+        self.failed_jurisdictions
+            .lock()
+            .expect("failed jurisdictions lock poisoned")
+            .push((jurisdiction, format!("{error:#}")));
+    }
+
+    /// Takes the jurisdictions recorded by `fail_jurisdiction`, with their errors.
+    pub fn take_failed_jurisdictions(&self) -> Vec<(Jurisdiction, String)> {
+        std::mem::take(
+            &mut *self
+                .failed_jurisdictions
+                .lock()
+                .expect("failed jurisdictions lock poisoned"),
+        )
+    }
+
+    /// Returns the raw body of one source request. `label` names the request
+    /// (endpoint, offset, search term) and must be deterministic: live fetches archive
+    /// the body under it, and replays look the archived body up by it instead of
+    /// calling `fetch`. Live fetches retry the page itself under the connector's
+    /// retry policy. Archive failures are logged and never fail the fetch.
+    pub async fn fetch_text<F, Fut>(&self, label: &str, fetch: F) -> Result<String>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let body = self
+            .fetch_bytes(label, || {
+                let page = fetch();
+                async move { page.await.map(String::into_bytes) }
+            })
+            .await?;
+        String::from_utf8(body)
+            .with_context(|| format!("page '{label}' for {} is not UTF-8", self.source))
+    }
+
+    /// [`fetch_text`](Self::fetch_text) for binary bodies such as workbooks and ZIPs.
+    pub async fn fetch_bytes<F, Fut>(&self, label: &str, fetch: F) -> Result<Vec<u8>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Vec<u8>>>,
+    {
+        if let Some(snapshot) = &self.replay {
+            return snapshot.read_page(&self.source, label).await;
+        }
+
+        let source = &*self.source;
+        let mut attempt = 0usize;
+        let body = loop {
+            attempt += 1;
+            match fetch().await {
+                Ok(body) => break body,
+                Err(error) => {
+                    let Some(delay) = self.retry.next_delay(attempt, &error) else {
+                        return Err(error);
+                    };
+                    warn!(
+                        source,
+                        label,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %format!("{error:#}"),
+                        "Page fetch failed, retrying"
+                    );
+                    sleep(delay).await;
+                }
+            }
+        };
+
+        if let Some(run) = &self.archive
+            && let Err(error) = run.store_page(source, label, &body).await
+        {
+            warn!(source, label, error = %format!("{error:#}"), "Unable to archive source payload");
+        }
+
+        Ok(body)
+    }
 }
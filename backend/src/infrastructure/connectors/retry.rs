@@ -0,0 +1,289 @@
+use std::{fmt, time::Duration};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::{Response, StatusCode, header::RETRY_AFTER};
+use serde::Deserialize;
+
+use crate::infrastructure::connectors::config::{check_positive, override_parsed};
+
+const DEFAULT_MAX_ATTEMPTS: usize = 3;
+const DEFAULT_BASE_DELAY_MS: u64 = 1_000;
+const DEFAULT_MAX_DELAY_SECS: u64 = 30;
+
+/// How a connector retries a failed page request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per page, including the first one.
+    pub max_attempts: usize,
+    pub base_delay: Duration,
+    /// Upper bound for backoff. A `Retry-After` longer than this gives up instead.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(DEFAULT_BASE_DELAY_MS),
+            max_delay: Duration::from_secs(DEFAULT_MAX_DELAY_SECS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait before retrying after `attempt` (1-based) failed
+    /// with `error`, or `None` when the error is permanent or attempts are used up.
+    pub fn next_delay(&self, attempt: usize, error: &anyhow::Error) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        let ErrorClass::Transient { retry_after } = classify(error) else {
+            return None;
+        };
+        let backoff = self.backoff(attempt);
+        match retry_after {
+            Some(wait) if wait > self.max_delay => None,
+            Some(wait) => Some(wait.max(backoff)),
+            None => Some(backoff),
+        }
+    }
+
+    /// Exponential backoff capped at `max_delay`, with half of it randomized so
+    /// concurrent crawls hitting the same upstream don't retry in lockstep.
+    fn backoff(&self, attempt: usize) -> Duration {
+        let exponent = u32::try_from(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX)
+            .min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let half = delay / 2;
+
+        half + half.mul_f64(fastrand::f64())
+    }
+}
+
+/// `retry` table of a connector entry, e.g. `retry = { max_attempts = 5 }`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetrySettings {
+    /// Attempts per page, including the first one.
+    pub max_attempts: usize,
+    pub base_delay_ms: u64,
+    /// Upper bound for backoff. A `Retry-After` longer than this gives up instead.
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay_ms: DEFAULT_BASE_DELAY_MS,
+            max_delay_secs: DEFAULT_MAX_DELAY_SECS,
+        }
+    }
+}
+
+impl RetrySettings {
+    /// Overrides from `{prefix}_RETRY_MAX_ATTEMPTS`, `{prefix}_RETRY_BASE_DELAY_MS` and
+    /// `{prefix}_RETRY_MAX_DELAY_SECS`.
+    pub fn apply_env(&mut self, prefix: &str) -> Result<()> {
+        override_parsed(
+            &mut self.max_attempts,
+            &format!("{prefix}_RETRY_MAX_ATTEMPTS"),
+        )?;
+        override_parsed(
+            &mut self.base_delay_ms,
+            &format!("{prefix}_RETRY_BASE_DELAY_MS"),
+        )?;
+        override_parsed(
+            &mut self.max_delay_secs,
+            &format!("{prefix}_RETRY_MAX_DELAY_SECS"),
+        )
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        check_positive("retry.max_attempts", self.max_attempts as u64)
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            base_delay: Duration::from_millis(self.base_delay_ms),
+            max_delay: Duration::from_secs(self.max_delay_secs),
+        }
+    }
+}
+
+/// Non-success HTTP response. Unlike `reqwest::Response::error_for_status`, keeps the
+/// `Retry-After` hint so the retry policy can honor it.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    status: StatusCode,
+    retry_after: Option<Duration>,
+}
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP status {}", self.status)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+pub trait ResponseStatusExt: Sized {
+    fn ensure_success(self) -> Result<Self, HttpStatusError>;
+}
+
+impl ResponseStatusExt for Response {
+    fn ensure_success(self) -> Result<Self, HttpStatusError> {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self);
+        }
+
+        let retry_after = self
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, Utc::now()));
+        Err(HttpStatusError {
+            status,
+            retry_after,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ErrorClass {
+    Transient { retry_after: Option<Duration> },
+    Permanent,
+}
+
+/// Timeouts, connection failures, 408, 429 and 5xx are transient; other 4xx
+/// statuses and undecodable payloads are permanent. Unrecognized errors are
+/// treated as transient.
+fn classify(error: &anyhow::Error) -> ErrorClass {
+    for cause in error.chain() {
+        if let Some(status_error) = cause.downcast_ref::<HttpStatusError>() {
+            return classify_status(status_error.status, status_error.retry_after);
+        }
+        if let Some(request_error) = cause.downcast_ref::<reqwest::Error>() {
+            if let Some(status) = request_error.status() {
+                return classify_status(status, None);
+            }
+            if request_error.is_decode() || request_error.is_builder() {
+                return ErrorClass::Permanent;
+            }
+            return ErrorClass::Transient { retry_after: None };
+        }
+        if cause.is::<serde_json::Error>() || cause.is::<csv::Error>() {
+            return ErrorClass::Permanent;
+        }
+    }
+
+    ErrorClass::Transient { retry_after: None }
+}
+
+fn classify_status(status: StatusCode, retry_after: Option<Duration>) -> ErrorClass {
+    if status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+    {
+        ErrorClass::Transient { retry_after }
+    } else {
+        ErrorClass::Permanent
+    }
+}
+
+/// Parses `Retry-After` as delta-seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::Context;
+    use chrono::{TimeZone, Utc};
+    use reqwest::StatusCode;
+
+    use super::{ErrorClass, HttpStatusError, RetryPolicy, classify, parse_retry_after};
+
+    fn status_error(status: StatusCode, retry_after: Option<Duration>) -> anyhow::Error {
+        anyhow::Error::new(HttpStatusError {
+            status,
+            retry_after,
+        })
+        .context("page request returned non-success status")
+    }
+
+    #[test]
+    fn separates_transient_from_permanent_errors() {
+        assert_eq!(
+            classify(&status_error(StatusCode::SERVICE_UNAVAILABLE, None)),
+            ErrorClass::Transient { retry_after: None }
+        );
+        assert_eq!(
+            classify(&status_error(StatusCode::NOT_FOUND, None)),
+            ErrorClass::Permanent
+        );
+
+        let parse_error = serde_json::from_str::<Vec<u8>>("<html>")
+            .context("response could not be parsed")
+            .unwrap_err();
+        assert_eq!(classify(&parse_error), ErrorClass::Permanent);
+    }
+
+    #[test]
+    fn honors_retry_after_within_the_policy_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+        };
+        let throttled = status_error(StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(12)));
+        assert_eq!(
+            policy.next_delay(1, &throttled),
+            Some(Duration::from_secs(12))
+        );
+        assert_eq!(policy.next_delay(3, &throttled), None);
+
+        let too_long = status_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(120)),
+        );
+        assert_eq!(policy.next_delay(1, &too_long), None);
+
+        let backoff = policy
+            .next_delay(2, &status_error(StatusCode::BAD_GATEWAY, None))
+            .unwrap();
+        assert!(backoff >= Duration::from_millis(100) && backoff <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_http_dates() {
+        let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 27, 0).unwrap();
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+}
@@ -1,15 +1,21 @@
-use std::{env, time::Duration};
-
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use chrono::{DateTime, NaiveDateTime, Utc};
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::Deserialize;
+use serde_json::Value;
 
 use crate::{
     application::dto::SourceFacilityInput,
-    domain::entities::{Jurisdiction, Violation},
-    infrastructure::connectors::HealthDataConnector,
+    domain::entities::{Jurisdiction, LocationPrecision, ValidationReason, Violation},
+    infrastructure::connectors::{
+        FetchContext, HealthDataConnector, RetryPolicy,
+        config::{
+            ConnectorSettings, check_positive, check_url, env_string, override_bool,
+            override_limit, override_parsed,
+        },
+        dates,
+        retry::RetrySettings,
+        socrata_connector::{SodaQuery, check_app_token, dataset_endpoint, soda_client},
+    },
 };
 
 const DEFAULT_BASE_URL: &str = "https://internal-sandiegocounty.data.socrata.com";
@@ -25,64 +31,95 @@ pub struct SanDiegoConnector {
     page_size: usize,
     max_records: Option<usize>,
     active_only: bool,
+    retry_policy: RetryPolicy,
 }
 
-impl Default for SanDiegoConnector {
+/// `[connectors.<name>]` keys for `type = "san_diego"`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SanDiegoSettings {
+    pub base_url: String,
+    pub dataset_id: String,
+    pub page_size: usize,
+    pub max_records: Option<usize>,
+    pub active_only: bool,
+    pub timeout_secs: u64,
+    /// Socrata app token, sent as `X-App-Token`; usually `"${VAR}"`.
+    pub app_token: Option<String>,
+    pub retry: RetrySettings,
+}
+
+impl Default for SanDiegoSettings {
     fn default() -> Self {
-        Self::from_env()
+        Self {
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            dataset_id: DEFAULT_DATASET_ID.to_owned(),
+            page_size: DEFAULT_PAGE_SIZE,
+            max_records: None,
+            active_only: true,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            app_token: None,
+            retry: RetrySettings::default(),
+        }
     }
 }
 
-impl SanDiegoConnector {
-    pub fn from_env() -> Self {
-        let base_url = env::var("CLEANPLATED_SD_SOCRATA_BASE_URL")
-            .unwrap_or_else(|_| DEFAULT_BASE_URL.to_owned())
-            .trim_end_matches('/')
-            .to_owned();
-        let dataset_id = env::var("CLEANPLATED_SD_SOCRATA_DATASET_ID")
-            .unwrap_or_else(|_| DEFAULT_DATASET_ID.to_owned());
-        let page_size = env::var("CLEANPLATED_SD_SOCRATA_PAGE_SIZE")
-            .ok()
-            .or_else(|| env::var("CLEANPLATED_SD_SOCRATA_LIMIT").ok())
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_PAGE_SIZE)
-            .max(1);
-        let max_records = env::var("CLEANPLATED_SD_SOCRATA_MAX_RECORDS")
-            .ok()
-            .and_then(|value| value.parse::<usize>().ok())
-            .filter(|value| *value > 0);
-        let active_only = env::var("CLEANPLATED_SD_SOCRATA_ACTIVE_ONLY")
-            .ok()
-            .map(|value| matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
-            .unwrap_or(true);
-        let timeout_secs = env::var("CLEANPLATED_SD_SOCRATA_TIMEOUT_SECS")
-            .ok()
-            .and_then(|value| value.parse::<u64>().ok())
-            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+impl ConnectorSettings for SanDiegoSettings {
+    fn apply_env(&mut self) -> Result<()> {
+        override_parsed(&mut self.base_url, "CLEANPLATED_SD_SOCRATA_BASE_URL")?;
+        override_parsed(&mut self.dataset_id, "CLEANPLATED_SD_SOCRATA_DATASET_ID")?;
+        override_parsed(&mut self.page_size, "CLEANPLATED_SD_SOCRATA_LIMIT")?;
+        override_parsed(&mut self.page_size, "CLEANPLATED_SD_SOCRATA_PAGE_SIZE")?;
+        override_limit(&mut self.max_records, "CLEANPLATED_SD_SOCRATA_MAX_RECORDS")?;
+        override_bool(&mut self.active_only, "CLEANPLATED_SD_SOCRATA_ACTIVE_ONLY")?;
+        override_parsed(
+            &mut self.timeout_secs,
+            "CLEANPLATED_SD_SOCRATA_TIMEOUT_SECS",
+        )?;
+        if let Some(app_token) = env_string("CLEANPLATED_SD_SOCRATA_APP_TOKEN") {
+            self.app_token = Some(app_token);
+        }
+        self.retry.apply_env("CLEANPLATED_SD_SOCRATA")
+    }
 
-        let mut headers = HeaderMap::new();
-        if let Ok(app_token) = env::var("CLEANPLATED_SD_SOCRATA_APP_TOKEN") {
-            let trimmed = app_token.trim();
-            if !trimmed.is_empty() {
-                if let Ok(value) = HeaderValue::from_str(trimmed) {
-                    headers.insert(HeaderName::from_static("x-app-token"), value);
-                }
-            }
+    fn validate(&self) -> Result<()> {
+        check_url("base_url", &self.base_url)?;
+        if self.dataset_id.trim().is_empty() {
+            anyhow::bail!("`dataset_id` must not be empty");
         }
+        check_positive("page_size", self.page_size as u64)?;
+        check_positive("timeout_secs", self.timeout_secs)?;
+        check_app_token(self.app_token.as_deref())?;
+        self.retry.validate()
+    }
+}
 
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(timeout_secs))
-            .default_headers(headers)
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+impl Default for SanDiegoConnector {
+    fn default() -> Self {
+        Self::new(SanDiegoSettings::default())
+    }
+}
 
+impl SanDiegoConnector {
+    pub fn new(settings: SanDiegoSettings) -> Self {
+        let SanDiegoSettings {
+            base_url,
+            dataset_id,
+            page_size,
+            max_records,
+            active_only,
+            timeout_secs,
+            app_token,
+            retry,
+        } = settings;
         Self {
-            client,
+            client: soda_client(timeout_secs, app_token.as_deref()),
             base_url,
             dataset_id,
             page_size,
             max_records,
             active_only,
+            retry_policy: retry.policy(),
         }
     }
 }
@@ -104,103 +141,84 @@ struct SanDiegoPermitRow {
 
 #[async_trait]
 impl HealthDataConnector for SanDiegoConnector {
-    fn source_name(&self) -> &'static str {
+    fn source_name(&self) -> &str {
         "san_diego_socrata"
     }
 
-    async fn fetch_facilities(&self) -> Result<Vec<SourceFacilityInput>> {
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        vec![Jurisdiction::SanDiegoCounty]
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
         // Source reference:
         // docs/research/socal-food-safety-data-strategy.md
         // The strategic framework documents San Diego as Socrata/SODA-first.
-        let endpoint = format!("{}/resource/{}.json", self.base_url, self.dataset_id);
         let where_clause = if self.active_only {
             "record_id IS NOT NULL AND record_name IS NOT NULL AND active_permit = true"
         } else {
             "record_id IS NOT NULL AND record_name IS NOT NULL"
         };
 
-        let mut offset = 0usize;
-        let mut rows = Vec::<SanDiegoPermitRow>::new();
-
-        loop {
-            let query = vec![
-                (
-                    "$select".to_owned(),
-                    "record_id,record_name,address,city,state,zip,latitude,longitude,last_updated,permit_status,active_permit".to_owned(),
-                ),
-                ("$where".to_owned(), where_clause.to_owned()),
-                ("$order".to_owned(), "last_updated DESC".to_owned()),
-                ("$limit".to_owned(), self.page_size.to_string()),
-                ("$offset".to_owned(), offset.to_string()),
-            ];
-
-            let page = self
-                .client
-                .get(&endpoint)
-                .query(&query)
-                .send()
-                .await
-                .context("San Diego Socrata request failed")?
-                .error_for_status()
-                .context("San Diego Socrata request returned non-success status")?
-                .json::<Vec<SanDiegoPermitRow>>()
-                .await
-                .context("San Diego Socrata response could not be parsed")?;
-
-            let page_count = page.len();
-            rows.extend(page);
-
-            if let Some(max_records) = self.max_records {
-                if rows.len() >= max_records {
-                    rows.truncate(max_records);
-                    break;
-                }
-            }
-
-            if page_count == 0 || page_count < self.page_size {
-                break;
-            }
-
-            offset = offset.saturating_add(page_count);
+        let rows = SodaQuery {
+            endpoint: &dataset_endpoint(&self.base_url, &self.dataset_id),
+            select: "record_id,record_name,address,city,state,zip,latitude,longitude,last_updated,permit_status,active_permit",
+            where_clause: Some(where_clause),
+            order: "last_updated DESC",
+            page_size: self.page_size,
+            max_records: self.max_records,
         }
+        .fetch(&self.client, context, None)
+        .await
+        .context("San Diego Socrata request failed")?
+        .into_iter()
+        .map(|row| serde_json::from_value::<SanDiegoPermitRow>(Value::Object(row)))
+        .collect::<Result<Vec<_>, _>>()
+        .context("San Diego Socrata response could not be parsed")?;
 
         let facilities = rows
             .into_iter()
-            .filter_map(map_row_to_source_input)
+            .map(map_row_to_source_input)
             .collect::<Vec<_>>();
 
         Ok(facilities)
     }
 }
 
-fn map_row_to_source_input(row: SanDiegoPermitRow) -> Option<SourceFacilityInput> {
-    let source_id = row.record_id?;
-    let name = row.record_name?;
+/// Missing IDs and names are left empty for record validation to reject.
+fn map_row_to_source_input(row: SanDiegoPermitRow) -> SourceFacilityInput {
+    let source_id = row.record_id.unwrap_or_default();
+    let name = row.record_name.unwrap_or_default();
     let city = row.city.unwrap_or_else(|| "San Diego".to_owned());
+    let mut repairs = Vec::new();
 
     // Most current records in c5ez-ufrd omit coordinates. Use city-level fallback
     // to keep the directory searchable until the full graded inspection feed is wired.
-    let latitude = row
+    let coordinates = row
         .latitude
         .and_then(|value| value.parse::<f64>().ok())
-        .unwrap_or_else(|| city_fallback_coordinates(&city).0);
-    let longitude = row
-        .longitude
-        .and_then(|value| value.parse::<f64>().ok())
-        .unwrap_or_else(|| city_fallback_coordinates(&city).1);
-
-    let inspected_at = row
-        .last_updated
-        .as_deref()
-        .and_then(parse_socrata_datetime)
-        .unwrap_or_else(Utc::now);
+        .zip(row.longitude.and_then(|value| value.parse::<f64>().ok()));
+    let ((latitude, longitude), location_precision) = coordinates
+        .map(|coordinates| (coordinates, LocationPrecision::Rooftop))
+        .unwrap_or_else(|| {
+            repairs.push(ValidationReason::CoordinatesDefaulted);
+            city_fallback_coordinates(&city)
+        });
+
+    let inspection_date = row.last_updated.as_deref().and_then(dates::parse);
+    if inspection_date.is_none() {
+        repairs.push(ValidationReason::InspectionDateMissing);
+    }
 
     let (raw_score, letter_grade, placard_status) = derive_scoring_signals(
         row.permit_status.as_deref(),
         row.active_permit.unwrap_or(true),
     );
 
-    Some(SourceFacilityInput {
+    SourceFacilityInput {
         source_id,
         name,
         address: row.address.unwrap_or_default(),
@@ -209,8 +227,12 @@ fn map_row_to_source_input(row: SanDiegoPermitRow) -> Option<SourceFacilityInput
         postal_code: row.zip.unwrap_or_default(),
         latitude,
         longitude,
+        location_precision,
         jurisdiction: Jurisdiction::SanDiegoCounty,
-        inspected_at,
+        inspection_id: None,
+        inspected_at: inspection_date.map(|date| date.at),
+        inspected_at_precision: inspection_date.map(|date| date.precision),
+        inspection_type: None,
         raw_score,
         letter_grade,
         placard_status,
@@ -221,23 +243,9 @@ fn map_row_to_source_input(row: SanDiegoPermitRow) -> Option<SourceFacilityInput
             points: 0,
             critical: false,
         }],
-    })
-}
-
-fn parse_socrata_datetime(value: &str) -> Option<DateTime<Utc>> {
-    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
-        return Some(parsed.with_timezone(&Utc));
-    }
-
-    if let Ok(parsed) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.3f") {
-        return Some(DateTime::from_naive_utc_and_offset(parsed, Utc));
+        repairs,
+        source_file: None,
     }
-
-    if let Ok(parsed) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
-        return Some(DateTime::from_naive_utc_and_offset(parsed, Utc));
-    }
-
-    None
 }
 
 fn derive_scoring_signals(
@@ -265,8 +273,10 @@ fn derive_scoring_signals(
     (None, None, None)
 }
 
-fn city_fallback_coordinates(city: &str) -> (f64, f64) {
-    match city.trim().to_ascii_uppercase().as_str() {
+/// City centroids for records without coordinates; unknown cities get the county's
+/// default point.
+fn city_fallback_coordinates(city: &str) -> ((f64, f64), LocationPrecision) {
+    let coordinates = match city.trim().to_ascii_uppercase().as_str() {
         "SAN DIEGO" => (32.7157, -117.1611),
         "CHULA VISTA" => (32.6401, -117.0842),
         "ESCONDIDO" => (33.1192, -117.0864),
@@ -282,6 +292,8 @@ fn city_fallback_coordinates(city: &str) -> (f64, f64) {
         "POWAY" => (32.9628, -117.0359),
         "IMPERIAL BEACH" => (32.5839, -117.1131),
         "LEMON GROVE" => (32.7426, -117.0317),
-        _ => (32.7157, -117.1611),
-    }
+        _ => return ((32.7157, -117.1611), LocationPrecision::JurisdictionDefault),
+    };
+
+    (coordinates, LocationPrecision::CityCentroid)
 }
@@ -0,0 +1,521 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{
+    Client,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    application::dto::SourceFacilityInput,
+    domain::entities::{ConnectorSyncState, Jurisdiction},
+    infrastructure::connectors::{
+        FetchContext, HealthDataConnector, ResponseStatusExt, RetryPolicy,
+        config::{
+            ConnectorSettings, check_positive, check_source_name, check_url, parse_jurisdiction,
+        },
+        mapping::{FieldMap, FieldNames, RecordMapper, attr_text},
+        retry::RetrySettings,
+    },
+};
+
+const DEFAULT_PAGE_SIZE: usize = 5_000;
+const DEFAULT_TIMEOUT_SECS: u64 = 20;
+const DEFAULT_FULL_SYNC_HOURS: u64 = 168;
+/// Every user column plus the system `:id` and `:updated_at` columns.
+const DEFAULT_SELECT: &str = ":*, *";
+const ROW_ID: &str = ":id";
+const UPDATED_AT: &str = ":updated_at";
+
+/// A source declared in the connectors file as `type = "socrata"`: one SODA dataset
+/// mapped onto facility inputs by config, optionally synced incrementally.
+pub struct SocrataConnector {
+    source_name: String,
+    client: Client,
+    jurisdiction: Jurisdiction,
+    settings: SocrataSettings,
+    retry_policy: RetryPolicy,
+}
+
+/// `[connectors.<name>]` keys for `type = "socrata"`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SocrataSettings {
+    /// Source name for statuses and archives; defaults to the entry name.
+    pub source: Option<String>,
+    /// Jurisdiction code (`lac`, `sdc`, ...) whose facilities this dataset replaces.
+    pub jurisdiction: String,
+    /// Portal root, such as `https://data.example.gov`.
+    pub base_url: String,
+    /// Four-by-four dataset ID, such as `c5ez-ufrd`.
+    pub dataset_id: String,
+    pub select: String,
+    #[serde(rename = "where")]
+    pub where_clause: Option<String>,
+    pub page_size: usize,
+    pub max_records: Option<usize>,
+    pub timeout_secs: u64,
+    /// Socrata app token, sent as `X-App-Token`; usually `"${VAR}"`.
+    pub app_token: Option<String>,
+    /// Fetches only rows with `:updated_at` past the last fetch's newest row, merged
+    /// into a stored mirror of the dataset.
+    pub incremental: bool,
+    /// Incremental syncs rebuild the mirror with a full pull this often, which is
+    /// how rows deleted at the source drop out.
+    pub full_sync_hours: u64,
+    /// Prefix for a generated `{prefix}-{row}` ID when a row has no source ID.
+    pub id_prefix: Option<String>,
+    /// Name used, and recorded as a repair, when a row has none.
+    pub default_name: Option<String>,
+    /// City used when a row has none; defaults to the jurisdiction label.
+    pub default_city: Option<String>,
+    pub default_state: String,
+    /// Derives an A/B/C grade from the score when no grade column is mapped or set.
+    pub grade_from_score: bool,
+    pub fields: FieldMap,
+    pub retry: RetrySettings,
+}
+
+impl Default for SocrataSettings {
+    fn default() -> Self {
+        Self {
+            source: None,
+            jurisdiction: String::new(),
+            base_url: String::new(),
+            dataset_id: String::new(),
+            select: DEFAULT_SELECT.to_owned(),
+            where_clause: None,
+            page_size: DEFAULT_PAGE_SIZE,
+            max_records: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            app_token: None,
+            incremental: false,
+            full_sync_hours: DEFAULT_FULL_SYNC_HOURS,
+            id_prefix: None,
+            default_name: None,
+            default_city: None,
+            default_state: "CA".to_owned(),
+            grade_from_score: false,
+            fields: FieldMap::default(),
+            retry: RetrySettings::default(),
+        }
+    }
+}
+
+impl ConnectorSettings for SocrataSettings {
+    /// Generic datasets share only the `CLEANPLATED_SOCRATA_RETRY_*` variables; use
+    /// `${VAR}` references for anything else.
+    fn apply_env(&mut self) -> Result<()> {
+        self.retry.apply_env("CLEANPLATED_SOCRATA")
+    }
+
+    fn validate(&self) -> Result<()> {
+        parse_jurisdiction(&self.jurisdiction)?;
+        if let Some(source) = &self.source {
+            check_source_name("source", source)?;
+        }
+        check_url("base_url", &self.base_url)?;
+        if self.dataset_id.trim().is_empty() {
+            bail!("`dataset_id` must not be empty");
+        }
+        check_positive("page_size", self.page_size as u64)?;
+        check_positive("timeout_secs", self.timeout_secs)?;
+        check_app_token(self.app_token.as_deref())?;
+        if self.fields.source_id.is_empty() && self.id_prefix.is_none() {
+            bail!("map `fields.source_id` or set `id_prefix`");
+        }
+        self.retry.validate()?;
+        if self.incremental {
+            check_positive("full_sync_hours", self.full_sync_hours)?;
+            if self.id_prefix.is_some() && self.fields.source_id.is_empty() {
+                bail!("incremental sync needs a mapped `fields.source_id`, not `id_prefix`");
+            }
+            let select = self.select.replace(' ', "");
+            let selects_system_columns = select.split(',').any(|column| column == ":*")
+                || (select.contains(ROW_ID) && select.contains(UPDATED_AT));
+            if !selects_system_columns {
+                bail!("incremental sync needs `{ROW_ID}` and `{UPDATED_AT}` in `select`");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SocrataConnector {
+    pub fn new(name: &str, settings: SocrataSettings) -> Result<Self> {
+        let jurisdiction = parse_jurisdiction(&settings.jurisdiction)?;
+        let source_name = settings.source.clone().unwrap_or_else(|| name.to_owned());
+        check_source_name("source", &source_name)?;
+
+        Ok(Self {
+            source_name,
+            client: soda_client(settings.timeout_secs, settings.app_token.as_deref()),
+            jurisdiction,
+            retry_policy: settings.retry.policy(),
+            settings,
+        })
+    }
+
+    fn endpoint(&self) -> String {
+        dataset_endpoint(&self.settings.base_url, &self.settings.dataset_id)
+    }
+
+    fn map_row(&self, index: usize, row: &Map<String, Value>) -> SourceFacilityInput {
+        let settings = &self.settings;
+        RecordMapper {
+            jurisdiction: &self.jurisdiction,
+            fields: &settings.fields,
+            id_prefix: settings.id_prefix.as_deref(),
+            default_name: settings.default_name.as_deref(),
+            default_city: settings.default_city.as_deref(),
+            default_state: &settings.default_state,
+            grade_from_score: settings.grade_from_score,
+        }
+        .map(index, &[row], None)
+    }
+
+    async fn fetch_full(&self, context: &FetchContext) -> Result<Vec<Map<String, Value>>> {
+        SodaQuery {
+            endpoint: &self.endpoint(),
+            select: &self.settings.select,
+            where_clause: self.settings.where_clause.as_deref(),
+            order: ROW_ID,
+            page_size: self.settings.page_size,
+            max_records: self.settings.max_records,
+        }
+        .fetch(&self.client, context, None)
+        .await
+    }
+
+    /// Pulls rows changed since the stored watermark and merges them into the stored
+    /// mirror, returning every row and the new sync state.
+    async fn fetch_incremental(
+        &self,
+        context: &FetchContext,
+    ) -> Result<(Vec<Map<String, Value>>, ConnectorSyncState)> {
+        let now = Utc::now();
+        let full_sync_every = chrono::Duration::hours(
+            i64::try_from(self.settings.full_sync_hours).unwrap_or(i64::MAX / 3_600),
+        );
+        let resumed = context
+            .resume_sync(|state| {
+                state.watermark.is_some() && now - state.full_sync_at < full_sync_every
+            })
+            .await?;
+
+        let mut where_clause = self.settings.where_clause.clone();
+        if let Some(watermark) = resumed
+            .as_ref()
+            .and_then(|state| state.watermark.as_deref())
+        {
+            let changed = format!("{UPDATED_AT} > '{}'", watermark.replace('\'', "''"));
+            where_clause = Some(match where_clause {
+                Some(filter) => format!("({filter}) AND {changed}"),
+                None => changed,
+            });
+        }
+
+        // Ordered by `:updated_at` so a capped pull stops at a consistent watermark
+        // and the next sync picks up where it ended.
+        let changed = SodaQuery {
+            endpoint: &self.endpoint(),
+            select: &self.settings.select,
+            where_clause: where_clause.as_deref(),
+            order: &format!("{UPDATED_AT}, {ROW_ID}"),
+            page_size: self.settings.page_size,
+            max_records: self.settings.max_records,
+        }
+        .fetch(&self.client, context, resumed.as_ref().map(|_| "changes"))
+        .await?;
+
+        let watermark = changed
+            .iter()
+            .filter_map(|row| attr_text(row, UPDATED_AT))
+            .max()
+            .or_else(|| resumed.as_ref().and_then(|state| state.watermark.clone()));
+        let full_sync_at = resumed.as_ref().map_or(now, |state| state.full_sync_at);
+        let base = match resumed {
+            Some(state) => serde_json::from_value::<Vec<Map<String, Value>>>(state.rows)
+                .context("stored Socrata sync rows are invalid")?,
+            None => Vec::new(),
+        };
+        let rows = merge_rows(base, changed)
+            .into_iter()
+            .map(|row| self.mirrored(row))
+            .collect::<Vec<_>>();
+
+        let state = ConnectorSyncState {
+            source: self.source_name.clone(),
+            watermark,
+            rows: Value::Array(rows.iter().cloned().map(Value::Object).collect()),
+            full_sync_at,
+            synced_at: now,
+        };
+        Ok((rows, state))
+    }
+
+    /// Keeps only the columns the mapping reads, so the stored mirror stays small.
+    fn mirrored(&self, mut row: Map<String, Value>) -> Map<String, Value> {
+        let fields = &self.settings.fields;
+        let mut keep = [
+            &fields.source_id,
+            &fields.name,
+            &fields.address,
+            &fields.city,
+            &fields.state,
+            &fields.postal_code,
+            &fields.latitude,
+            &fields.longitude,
+            &fields.location,
+            &fields.inspection_id,
+            &fields.inspected_at,
+            &fields.score,
+            &fields.grade,
+            &fields.placard_status,
+        ]
+        .into_iter()
+        .flat_map(FieldNames::names)
+        .collect::<Vec<_>>();
+        keep.extend([ROW_ID, UPDATED_AT]);
+        row.retain(|column, _| keep.contains(&column.as_str()));
+        row
+    }
+}
+
+#[async_trait]
+impl HealthDataConnector for SocrataConnector {
+    fn source_name(&self) -> &str {
+        &self.source_name
+    }
+
+    fn jurisdictions(&self) -> Vec<Jurisdiction> {
+        vec![self.jurisdiction.clone()]
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    async fn fetch_facilities(&self, context: &FetchContext) -> Result<Vec<SourceFacilityInput>> {
+        let rows = if self.settings.incremental {
+            let (rows, state) = self.fetch_incremental(context).await?;
+            context.save_sync_state(state);
+            rows
+        } else {
+            self.fetch_full(context).await?
+        };
+
+        Ok(rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| self.map_row(index, row))
+            .collect())
+    }
+}
+
+/// Overlays changed rows on the mirror by `:id`: changed rows replace their stored
+/// version in place and new rows are appended.
+fn merge_rows(
+    base: Vec<Map<String, Value>>,
+    changed: Vec<Map<String, Value>>,
+) -> Vec<Map<String, Value>> {
+    let mut rows = base;
+    let mut positions = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(index, row)| attr_text(row, ROW_ID).map(|id| (id, index)))
+        .collect::<HashMap<_, _>>();
+    for row in changed {
+        match attr_text(&row, ROW_ID).and_then(|id| positions.get(&id).copied()) {
+            Some(index) => rows[index] = row,
+            None => {
+                if let Some(id) = attr_text(&row, ROW_ID) {
+                    positions.insert(id, rows.len());
+                }
+                rows.push(row);
+            }
+        }
+    }
+    rows
+}
+
+pub(super) fn dataset_endpoint(base_url: &str, dataset_id: &str) -> String {
+    format!(
+        "{}/resource/{}.json",
+        base_url.trim_end_matches('/'),
+        dataset_id.trim()
+    )
+}
+
+pub(super) fn check_app_token(app_token: Option<&str>) -> Result<()> {
+    if let Some(app_token) = app_token
+        && HeaderValue::from_str(app_token.trim()).is_err()
+    {
+        bail!("`app_token` is not a valid header value");
+    }
+    Ok(())
+}
+
+/// A client that sends the app token, when there is one, on every request.
+pub(super) fn soda_client(timeout_secs: u64, app_token: Option<&str>) -> Client {
+    let mut headers = HeaderMap::new();
+    if let Some(app_token) = app_token {
+        let trimmed = app_token.trim();
+        if !trimmed.is_empty()
+            && let Ok(value) = HeaderValue::from_str(trimmed)
+        {
+            headers.insert(HeaderName::from_static("x-app-token"), value);
+        }
+    }
+
+    Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .default_headers(headers)
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+/// A SoQL query paged with `$offset`/`$limit`. `$order` must be total for paging to be
+/// stable, so it should end in a unique column such as `:id`.
+pub(super) struct SodaQuery<'a> {
+    pub endpoint: &'a str,
+    pub select: &'a str,
+    pub where_clause: Option<&'a str>,
+    pub order: &'a str,
+    pub page_size: usize,
+    pub max_records: Option<usize>,
+}
+
+impl SodaQuery<'_> {
+    /// Pages through the query. Pages are archived as `offset=<n>`, or
+    /// `{label} offset=<n>` when a label is given.
+    pub(super) async fn fetch(
+        &self,
+        client: &Client,
+        context: &FetchContext,
+        label: Option<&str>,
+    ) -> Result<Vec<Map<String, Value>>> {
+        let target = self.max_records.unwrap_or(usize::MAX);
+        let mut rows = Vec::new();
+        let mut offset = 0usize;
+
+        while offset < target {
+            let limit = self.page_size.min(target - offset);
+            let mut query = vec![
+                ("$select", self.select.to_owned()),
+                ("$order", self.order.to_owned()),
+                ("$limit", limit.to_string()),
+                ("$offset", offset.to_string()),
+            ];
+            if let Some(where_clause) = self.where_clause {
+                query.push(("$where", where_clause.to_owned()));
+            }
+
+            let page_label = match label {
+                Some(label) => format!("{label} offset={offset}"),
+                None => format!("offset={offset}"),
+            };
+            let body = context
+                .fetch_text(&page_label, || async {
+                    client
+                        .get(self.endpoint)
+                        .query(&query)
+                        .send()
+                        .await
+                        .context("Socrata request failed")?
+                        .ensure_success()
+                        .context("Socrata request returned non-success status")?
+                        .text()
+                        .await
+                        .context("Socrata response body read failed")
+                })
+                .await?;
+            let page = serde_json::from_str::<Vec<Map<String, Value>>>(&body)
+                .with_context(|| format!("Socrata page at offset {offset} could not be parsed"))?;
+
+            let page_count = page.len();
+            rows.extend(page);
+            if page_count < limit {
+                break;
+            }
+            offset = offset.saturating_add(page_count);
+        }
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value, json};
+
+    use super::{SocrataConnector, SocrataSettings, merge_rows};
+
+    fn row(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn merges_changed_rows_into_the_mirror_by_row_id() {
+        let base = vec![
+            row(json!({ ":id": "row-1", "name": "Old Diner" })),
+            row(json!({ ":id": "row-2", "name": "Cafe" })),
+        ];
+        let changed = vec![
+            row(json!({ ":id": "row-1", "name": "New Diner" })),
+            row(json!({ ":id": "row-3", "name": "Bakery" })),
+        ];
+
+        let names = merge_rows(base, changed)
+            .iter()
+            .map(|row| row["name"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["New Diner", "Cafe", "Bakery"]);
+    }
+
+    #[test]
+    fn maps_columns_and_keeps_only_mapped_columns_in_the_mirror() {
+        let settings: SocrataSettings = toml::from_str(
+            r#"
+            jurisdiction = "lb"
+            base_url = "https://data.example.test"
+            dataset_id = "abcd-1234"
+            incremental = true
+
+            [fields]
+            source_id = "permit_id"
+            name = ["dba_name", "business_name"]
+            location = "location"
+            inspected_at = "inspection_date"
+            score = "score"
+            "#,
+        )
+        .unwrap();
+        let connector = SocrataConnector::new("lb_inspections", settings).unwrap();
+
+        let source = row(json!({
+            ":id": "row-9",
+            ":updated_at": "2024-07-05T01:02:03.000Z",
+            "permit_id": "PR-9",
+            "business_name": "Taco Stand",
+            "location": { "type": "Point", "coordinates": [-118.19, 33.77] },
+            "inspection_date": "2024-07-04T00:00:00.000",
+            "score": "96",
+            "inspector_notes": "not mapped"
+        }));
+        let mapped = connector.map_row(0, &source);
+        assert_eq!(mapped.source_id, "PR-9");
+        assert_eq!(mapped.name, "Taco Stand");
+        assert_eq!((mapped.latitude, mapped.longitude), (33.77, -118.19));
+        assert_eq!(mapped.raw_score, Some(96.0));
+        assert!(mapped.inspected_at.is_some());
+
+        let mirrored = connector.mirrored(source);
+        assert!(!mirrored.contains_key("inspector_notes"));
+        assert_eq!(mirrored.len(), 7);
+    }
+}
@@ -0,0 +1,345 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::PathBuf,
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use crate::domain::{address::PostalAddress, entities::GeocodeMatch};
+
+/// Largest gap between the house numbers an address is interpolated between.
+const MAX_INTERPOLATION_GAP: u32 = 200;
+
+const LONGITUDE_COLUMNS: &[&str] = &["lon", "longitude", "x"];
+const LATITUDE_COLUMNS: &[&str] = &["lat", "latitude", "y"];
+const NUMBER_COLUMNS: &[&str] = &["number", "house_number", "address_number"];
+const STREET_COLUMNS: &[&str] = &["street", "street_name"];
+const CITY_COLUMNS: &[&str] = &["city"];
+const POSTCODE_COLUMNS: &[&str] = &["postcode", "zip", "zipcode", "postal_code"];
+
+#[derive(Clone, Debug)]
+pub struct GazetteerMatch {
+    pub quality: GeocodeMatch,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub matched_address: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct AddressPoint {
+    number: u32,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Address points loaded from local CSV files, for geocoding without a live service.
+///
+/// Files use the OpenAddresses layout: `LON`, `LAT`, `NUMBER` and `STREET` columns, plus
+/// optional `CITY` and `POSTCODE` (header names are matched case-insensitively, so TIGER
+/// address points exported to the same columns load too). Points are indexed by street
+/// and ZIP, or by street and city when a row has no ZIP.
+#[derive(Default)]
+pub struct Gazetteer {
+    fingerprint: String,
+    streets: HashMap<String, Vec<AddressPoint>>,
+    zip_totals: HashMap<String, (f64, f64, usize)>,
+}
+
+impl Gazetteer {
+    /// Reads every file into one index. Blocking; run it off the async runtime.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut fingerprint = Sha256::new();
+        let mut gazetteer = Self::default();
+        for path in paths {
+            let metadata = std::fs::metadata(path)
+                .with_context(|| format!("unable to read gazetteer file {}", path.display()))?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |modified| modified.as_secs());
+            fingerprint.update(format!(
+                "{}|{}|{modified}\n",
+                path.display(),
+                metadata.len()
+            ));
+
+            let file = File::open(path)
+                .with_context(|| format!("unable to open gazetteer file {}", path.display()))?;
+            gazetteer
+                .index_csv(BufReader::new(file))
+                .with_context(|| format!("unable to parse gazetteer file {}", path.display()))?;
+        }
+
+        gazetteer.fingerprint = hex::encode(fingerprint.finalize());
+        gazetteer.finish();
+        Ok(gazetteer)
+    }
+
+    /// Identifies the loaded files, so cached results from other files can be redone.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn address_count(&self) -> usize {
+        self.streets.values().map(Vec::len).sum()
+    }
+
+    pub fn lookup(&self, address: &PostalAddress) -> GazetteerMatch {
+        let number = address.number.as_deref().and_then(house_number);
+        if let (Some(number), Some(street)) = (number, address.street_key()) {
+            let localities = [address.zip.as_ref(), address.city.as_ref()];
+            for locality in localities.into_iter().flatten() {
+                let Some(points) = self.streets.get(&street_index_key(&street, locality)) else {
+                    continue;
+                };
+                if let Some(found) = locate(points, number) {
+                    let matched_address = (found.quality == GeocodeMatch::Exact)
+                        .then(|| format!("{number} {street}, {locality}"));
+                    return GazetteerMatch {
+                        matched_address,
+                        ..found
+                    };
+                }
+            }
+        }
+
+        if let Some(zip) = address.zip.as_ref()
+            && let Some(&(latitude, longitude, count)) = self.zip_totals.get(zip)
+        {
+            return GazetteerMatch {
+                quality: GeocodeMatch::ZipCentroid,
+                latitude: Some(latitude / count as f64),
+                longitude: Some(longitude / count as f64),
+                matched_address: None,
+            };
+        }
+
+        GazetteerMatch {
+            quality: GeocodeMatch::Unmatched,
+            latitude: None,
+            longitude: None,
+            matched_address: None,
+        }
+    }
+
+    fn index_csv(&mut self, reader: impl Read) -> Result<()> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = reader
+            .headers()
+            .context("missing header row")?
+            .iter()
+            .map(|header| header.trim().to_ascii_lowercase())
+            .collect::<Vec<_>>();
+        let column = |names: &[&str]| {
+            headers
+                .iter()
+                .position(|header| names.contains(&header.as_str()))
+        };
+        let (
+            Some(longitude_column),
+            Some(latitude_column),
+            Some(number_column),
+            Some(street_column),
+        ) = (
+            column(LONGITUDE_COLUMNS),
+            column(LATITUDE_COLUMNS),
+            column(NUMBER_COLUMNS),
+            column(STREET_COLUMNS),
+        )
+        else {
+            anyhow::bail!("expected LON, LAT, NUMBER and STREET columns");
+        };
+        let city_column = column(CITY_COLUMNS);
+        let postcode_column = column(POSTCODE_COLUMNS);
+
+        for row in reader.records() {
+            // One malformed row should not discard a multi-million-row file.
+            let Ok(row) = row else {
+                continue;
+            };
+            let field = |index: usize| row.get(index).map(str::trim).unwrap_or_default();
+            let coordinates = field(latitude_column)
+                .parse::<f64>()
+                .ok()
+                .zip(field(longitude_column).parse::<f64>().ok())
+                .filter(|(latitude, longitude)| {
+                    (-90.0..=90.0).contains(latitude)
+                        && (-180.0..=180.0).contains(longitude)
+                        && !(*latitude == 0.0 && *longitude == 0.0)
+                });
+            let Some((latitude, longitude)) = coordinates else {
+                continue;
+            };
+
+            let zip = postcode_column
+                .map(field)
+                .map(|postcode| postcode.chars().take(5).collect::<String>())
+                .filter(|zip| {
+                    zip.len() == 5 && zip.chars().all(|character| character.is_ascii_digit())
+                });
+            if let Some(zip) = zip.as_ref() {
+                let totals = self.zip_totals.entry(zip.clone()).or_insert((0.0, 0.0, 0));
+                totals.0 += latitude;
+                totals.1 += longitude;
+                totals.2 += 1;
+            }
+
+            let Some(number) = house_number(field(number_column)) else {
+                continue;
+            };
+            let parsed = PostalAddress::parse(&format!("{number} {}", field(street_column)));
+            let Some(street) = parsed.street_key() else {
+                continue;
+            };
+            // Cities are uppercased like `PostalAddress::city`.
+            let locality = zip.or_else(|| {
+                city_column
+                    .map(|index| {
+                        field(index)
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                            .to_ascii_uppercase()
+                    })
+                    .filter(|city| !city.is_empty())
+            });
+            let Some(locality) = locality else {
+                continue;
+            };
+
+            self.streets
+                .entry(street_index_key(&street, &locality))
+                .or_default()
+                .push(AddressPoint {
+                    number,
+                    latitude,
+                    longitude,
+                });
+        }
+
+        Ok(())
+    }
+
+    /// Sorts each street by house number, keeping the first point per number.
+    fn finish(&mut self) {
+        for points in self.streets.values_mut() {
+            points.sort_by_key(|point| point.number);
+            points.dedup_by_key(|point| point.number);
+        }
+    }
+}
+
+/// The exact point for `number`, or one interpolated between the nearest numbers on
+/// the same side of the street.
+fn locate(points: &[AddressPoint], number: u32) -> Option<GazetteerMatch> {
+    let position = match points.binary_search_by_key(&number, |point| point.number) {
+        Ok(position) => {
+            let point = points[position];
+            return Some(GazetteerMatch {
+                quality: GeocodeMatch::Exact,
+                latitude: Some(point.latitude),
+                longitude: Some(point.longitude),
+                matched_address: None,
+            });
+        }
+        Err(position) => position,
+    };
+
+    let same_side = |point: &&AddressPoint| point.number % 2 == number % 2;
+    let lower = points[..position].iter().rev().find(same_side)?;
+    let upper = points[position..].iter().find(same_side)?;
+    if upper.number - lower.number > MAX_INTERPOLATION_GAP {
+        return None;
+    }
+
+    let fraction = f64::from(number - lower.number) / f64::from(upper.number - lower.number);
+    Some(GazetteerMatch {
+        quality: GeocodeMatch::Interpolated,
+        latitude: Some(lower.latitude + fraction * (upper.latitude - lower.latitude)),
+        longitude: Some(lower.longitude + fraction * (upper.longitude - lower.longitude)),
+        matched_address: None,
+    })
+}
+
+fn street_index_key(street: &str, locality: &str) -> String {
+    format!("{street}|{locality}")
+}
+
+/// Leading digits of a house number, so `123A` and `123-125` both index as 123.
+fn house_number(value: &str) -> Option<u32> {
+    let digits = value
+        .trim()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Gazetteer;
+    use crate::domain::{address::PostalAddress, entities::GeocodeMatch};
+
+    const CSV: &str = "LON,LAT,NUMBER,STREET,UNIT,CITY,DISTRICT,REGION,POSTCODE,ID,HASH
+-118.2000,34.0000,100,West Main Street,,Los Angeles,,CA,90012,,
+-118.2100,34.0000,120,W Main St,,Los Angeles,,CA,90012,,
+-118.2200,34.0100,101,W MAIN ST,,Los Angeles,,CA,90012,,
+-118.3000,34.1000,5,Pine Ave,,Glendale,,CA,,,
+";
+
+    fn gazetteer() -> Gazetteer {
+        let mut gazetteer = Gazetteer::default();
+        gazetteer.index_csv(CSV.as_bytes()).unwrap();
+        gazetteer.finish();
+        gazetteer
+    }
+
+    #[test]
+    fn matches_exact_and_interpolated_addresses() {
+        let gazetteer = gazetteer();
+        assert_eq!(gazetteer.address_count(), 4);
+
+        let exact = gazetteer.lookup(&PostalAddress::parse(
+            "120 West Main Street, Los Angeles, CA 90012",
+        ));
+        assert_eq!(exact.quality, GeocodeMatch::Exact);
+        assert_eq!(exact.longitude, Some(-118.21));
+        assert_eq!(
+            exact.matched_address.as_deref(),
+            Some("120 W MAIN ST, 90012")
+        );
+
+        let between =
+            gazetteer.lookup(&PostalAddress::from_parts("110 W Main St", "", "", "90012"));
+        assert_eq!(between.quality, GeocodeMatch::Interpolated);
+        assert!((between.longitude.unwrap() + 118.205).abs() < 1e-9);
+
+        let by_city = gazetteer.lookup(&PostalAddress::from_parts(
+            "5 Pine Avenue",
+            "Glendale",
+            "CA",
+            "",
+        ));
+        assert_eq!(by_city.quality, GeocodeMatch::Exact);
+    }
+
+    #[test]
+    fn falls_back_to_zip_centroids() {
+        let gazetteer = gazetteer();
+
+        // 105 is odd and only one odd number is known, so it cannot be interpolated.
+        let centroid =
+            gazetteer.lookup(&PostalAddress::from_parts("105 W Main St", "", "", "90012"));
+        assert_eq!(centroid.quality, GeocodeMatch::ZipCentroid);
+        assert!((centroid.latitude.unwrap() - 34.003_333).abs() < 1e-5);
+
+        let unmatched = gazetteer.lookup(&PostalAddress::from_parts("1 Elm St", "", "", "94105"));
+        assert_eq!(unmatched.quality, GeocodeMatch::Unmatched);
+        assert!(unmatched.latitude.is_none());
+    }
+}
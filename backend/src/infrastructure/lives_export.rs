@@ -0,0 +1,234 @@
+use std::io::{Cursor, Write};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::{domain::entities::Facility, infrastructure::connectors::SOURCE_TIME_ZONE};
+
+/// What `feed_info.csv` says about an export.
+#[derive(Clone, Debug)]
+pub struct LivesFeedInfo {
+    /// When the exported facility set was published.
+    pub snapshot_at: DateTime<Utc>,
+    /// Connector sources whose records make up the exported facilities.
+    pub sources: Vec<String>,
+}
+
+/// Writes facilities as a LIVES batch: a ZIP of `businesses.csv`, `inspections.csv`,
+/// `violations.csv` and `feed_info.csv`. `business_id` is the canonical facility ID and
+/// `businesses.csv` adds a `trust_score` column. Inspections keep their own score,
+/// blank when unscored; undated inspections have no LIVES date and are left out, along
+/// with their violations. Coordinates are left blank unless they locate the premises, so
+/// a city centroid or jurisdiction default is never read as a real location.
+pub fn write_lives_zip(facilities: &[Facility], feed: &LivesFeedInfo) -> Result<Vec<u8>> {
+    let mut businesses = csv_writer(&[
+        "business_id",
+        "name",
+        "address",
+        "city",
+        "state",
+        "postal_code",
+        "latitude",
+        "longitude",
+        "trust_score",
+    ])?;
+    let mut inspections = csv_writer(&["business_id", "score", "date", "type"])?;
+    let mut violations = csv_writer(&[
+        "business_id",
+        "date",
+        "code",
+        "description",
+        "risk_category",
+    ])?;
+
+    for facility in facilities {
+        let (latitude, longitude) = if facility.location_precision.is_precise() {
+            (
+                facility.latitude.to_string(),
+                facility.longitude.to_string(),
+            )
+        } else {
+            Default::default()
+        };
+        businesses.write_record([
+            facility.id.as_str(),
+            &facility.name,
+            &facility.address,
+            &facility.city,
+            &facility.state,
+            &facility.postal_code,
+            &latitude,
+            &longitude,
+            &facility.trust_score.to_string(),
+        ])?;
+
+        // Oldest first, as LIVES publishers list them.
+        for inspection in facility.inspections.iter().rev() {
+            let Some(inspected_at) = inspection.inspected_at else {
+                continue;
+            };
+            let date = inspected_at
+                .with_timezone(&SOURCE_TIME_ZONE)
+                .format("%Y%m%d")
+                .to_string();
+            inspections.write_record([
+                facility.id.as_str(),
+                &inspection
+                    .raw_score
+                    .map(|score| score.to_string())
+                    .unwrap_or_default(),
+                &date,
+                inspection.inspection_type.as_deref().unwrap_or_default(),
+            ])?;
+            for violation in &inspection.violations {
+                violations.write_record([
+                    facility.id.as_str(),
+                    &date,
+                    &violation.code,
+                    &violation.description,
+                    if violation.critical { "High Risk" } else { "" },
+                ])?;
+            }
+        }
+    }
+
+    let mut feed_info = csv_writer(&["feed_date", "feed_version", "snapshot_at", "sources"])?;
+    feed_info.write_record([
+        feed.snapshot_at
+            .with_timezone(&SOURCE_TIME_ZONE)
+            .format("%Y%m%d")
+            .to_string(),
+        feed.snapshot_at.format("%Y%m%dT%H%M%SZ").to_string(),
+        feed.snapshot_at.to_rfc3339(),
+        feed.sources.join(";"),
+    ])?;
+
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, file) in [
+        ("businesses.csv", businesses),
+        ("inspections.csv", inspections),
+        ("violations.csv", violations),
+        ("feed_info.csv", feed_info),
+    ] {
+        let body = file
+            .into_inner()
+            .with_context(|| format!("unable to write {name}"))?;
+        archive
+            .start_file(name, SimpleFileOptions::default())
+            .with_context(|| format!("unable to add {name} to the LIVES export"))?;
+        archive.write_all(&body)?;
+    }
+    Ok(archive.finish()?.into_inner())
+}
+
+fn csv_writer(headers: &[&str]) -> Result<csv::Writer<Vec<u8>>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(headers)?;
+    Ok(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use chrono::{TimeZone, Utc};
+    use zip::ZipArchive;
+
+    use super::{LivesFeedInfo, write_lives_zip};
+    use crate::domain::entities::{
+        DatePrecision, Facility, Inspection, Jurisdiction, LocationPrecision, Violation,
+    };
+
+    fn file(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+        let mut body = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut body)
+            .unwrap();
+        body
+    }
+
+    #[test]
+    fn writes_facilities_as_a_lives_batch() {
+        let visit = |day: u32, raw_score: Option<f32>, inspection_type: &str| Inspection {
+            inspection_id: format!("riv-101-{day}"),
+            inspected_at: Some(Utc.with_ymd_and_hms(2024, 3, day, 18, 0, 0).unwrap()),
+            inspected_at_precision: Some(DatePrecision::Date),
+            inspection_type: Some(inspection_type.to_owned()),
+            raw_score,
+            letter_grade: None,
+            placard_status: None,
+            violations: Vec::new(),
+        };
+        let mut routine = visit(1, Some(92.0), "routine");
+        routine.violations.push(Violation {
+            code: "F023".to_owned(),
+            description: "Hand sink blocked, no soap".to_owned(),
+            points: 4,
+            critical: true,
+        });
+        let undated = Inspection {
+            inspected_at: None,
+            ..visit(2, Some(80.0), "routine")
+        };
+        let facility = Facility {
+            source_id: "101".to_owned(),
+            name: "Taco Spot".to_owned(),
+            city: "Riverside".to_owned(),
+            postal_code: "92501".to_owned(),
+            latitude: 33.98,
+            longitude: -117.37,
+            jurisdiction: Jurisdiction::RiversideCounty,
+            trust_score: 92,
+            inspections: vec![visit(15, None, "followup"), undated, routine],
+            ..Facility::sample("riv::101")
+        };
+
+        let placeholder = Facility {
+            id: "riv::102".to_owned(),
+            source_id: "102".to_owned(),
+            name: "Desert Diner".to_owned(),
+            address: String::new(),
+            latitude: 33.95,
+            longitude: -117.40,
+            location_precision: LocationPrecision::JurisdictionDefault,
+            inspections: Vec::new(),
+            ..facility.clone()
+        };
+
+        let body = write_lives_zip(
+            &[facility, placeholder],
+            &LivesFeedInfo {
+                snapshot_at: Utc.with_ymd_and_hms(2024, 4, 5, 7, 30, 0).unwrap(),
+                sources: vec!["lives_batch_riv_sbc".to_owned(), "cpra".to_owned()],
+            },
+        )
+        .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(body)).unwrap();
+
+        assert_eq!(
+            file(&mut archive, "businesses.csv"),
+            "business_id,name,address,city,state,postal_code,latitude,longitude,trust_score\n\
+             riv::101,Taco Spot,1 Main St,Riverside,CA,92501,33.98,-117.37,92\n\
+             riv::102,Desert Diner,,Riverside,CA,92501,,,92\n"
+        );
+        assert_eq!(
+            file(&mut archive, "inspections.csv"),
+            "business_id,score,date,type\n\
+             riv::101,92,20240301,routine\n\
+             riv::101,,20240315,followup\n"
+        );
+        assert_eq!(
+            file(&mut archive, "violations.csv"),
+            "business_id,date,code,description,risk_category\n\
+             riv::101,20240301,F023,\"Hand sink blocked, no soap\",High Risk\n"
+        );
+        assert_eq!(
+            file(&mut archive, "feed_info.csv"),
+            "feed_date,feed_version,snapshot_at,sources\n\
+             20240405,20240405T073000Z,2024-04-05T07:30:00+00:00,lives_batch_riv_sbc;cpra\n"
+        );
+    }
+}
@@ -1,3 +1,7 @@
+pub mod archive;
 pub mod connectors;
+pub mod gazetteer;
+pub mod lives_export;
 pub mod repositories;
 pub mod scheduler;
+pub mod webhooks;
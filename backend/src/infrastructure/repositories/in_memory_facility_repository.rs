@@ -1,18 +1,33 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use tokio::sync::RwLock;
 
 use crate::domain::{
-    entities::{Facility, FacilityVoteSummary, SystemIngestionStatus, VoteValue},
+    entities::{
+        ConnectorCircuit, ConnectorSyncState, DeadLetterRecord, Facility, FacilityEvent,
+        FacilityVoteSummary, GeocodeCacheEntry, IngestionRun, IngestionRunOutcome,
+        SourceRecordLink, SystemIngestionStatus, VoteValue, WebhookDelivery, WebhookDeliveryStatus,
+        WebhookSubscription,
+    },
     errors::RepositoryError,
-    repositories::FacilityRepository,
+    repositories::{DeadLetterQuery, FacilityEventQuery, FacilityRepository, WebhookDeliveryQuery},
 };
 
 #[derive(Default)]
 pub struct InMemoryFacilityRepository {
     facilities: RwLock<Vec<Facility>>,
     ingestion_status: RwLock<Option<SystemIngestionStatus>>,
+    ingestion_runs: RwLock<Vec<IngestionRun>>,
+    circuits: RwLock<HashMap<String, ConnectorCircuit>>,
+    sync_states: RwLock<HashMap<String, ConnectorSyncState>>,
+    dead_letters: RwLock<Vec<DeadLetterRecord>>,
+    facility_events: RwLock<Vec<FacilityEvent>>,
+    source_links: RwLock<HashMap<(String, String), SourceRecordLink>>,
+    geocodes: RwLock<HashMap<String, GeocodeCacheEntry>>,
+    webhook_subscriptions: RwLock<Vec<WebhookSubscription>>,
+    webhook_deliveries: RwLock<Vec<WebhookDelivery>>,
     votes: RwLock<HashMap<(String, String), VoteValue>>,
 }
 
@@ -24,9 +39,14 @@ impl InMemoryFacilityRepository {
 
 #[async_trait]
 impl FacilityRepository for InMemoryFacilityRepository {
-    async fn replace_all(&self, facilities: Vec<Facility>) -> Result<(), RepositoryError> {
+    async fn replace_facilities(
+        &self,
+        removed_ids: &[String],
+        facilities: Vec<Facility>,
+    ) -> Result<(), RepositoryError> {
         let mut write_guard = self.facilities.write().await;
-        *write_guard = facilities;
+        write_guard.retain(|facility| !removed_ids.contains(&facility.id));
+        write_guard.extend(facilities);
         Ok(())
     }
 
@@ -61,6 +81,315 @@ impl FacilityRepository for InMemoryFacilityRepository {
         Ok(self.ingestion_status.read().await.clone())
     }
 
+    async fn record_ingestion_run(&self, run: IngestionRun) -> Result<(), RepositoryError> {
+        let mut write_guard = self.ingestion_runs.write().await;
+        match write_guard.iter_mut().find(|current| current.id == run.id) {
+            Some(current) => *current = run,
+            None => write_guard.push(run),
+        }
+        Ok(())
+    }
+
+    async fn abandon_running_ingestion_runs(
+        &self,
+        finished_at: DateTime<Utc>,
+        message: &str,
+    ) -> Result<usize, RepositoryError> {
+        let mut write_guard = self.ingestion_runs.write().await;
+        let mut abandoned = 0;
+        for run in write_guard
+            .iter_mut()
+            .filter(|run| run.outcome == IngestionRunOutcome::Running)
+        {
+            run.outcome = IngestionRunOutcome::Abandoned;
+            run.finished_at = Some(finished_at);
+            run.message = Some(message.to_owned());
+            abandoned += 1;
+        }
+        Ok(abandoned)
+    }
+
+    async fn list_ingestion_runs(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<IngestionRun>, RepositoryError> {
+        let mut runs = self.ingestion_runs.read().await.clone();
+        runs.sort_by_key(|run| std::cmp::Reverse(run.started_at));
+        runs.truncate(limit);
+        Ok(runs)
+    }
+
+    async fn list_connector_circuits(&self) -> Result<Vec<ConnectorCircuit>, RepositoryError> {
+        let mut circuits = self
+            .circuits
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        circuits.sort_by(|left, right| left.source.cmp(&right.source));
+        Ok(circuits)
+    }
+
+    async fn upsert_connector_circuit(
+        &self,
+        circuit: ConnectorCircuit,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.circuits.write().await;
+        write_guard.insert(circuit.source.clone(), circuit);
+        Ok(())
+    }
+
+    async fn replace_dead_letters(
+        &self,
+        source: &str,
+        records: Vec<DeadLetterRecord>,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.dead_letters.write().await;
+        write_guard.retain(|record| record.source != source);
+        write_guard.extend(records);
+        Ok(())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        query: &DeadLetterQuery,
+    ) -> Result<Vec<DeadLetterRecord>, RepositoryError> {
+        let mut records = self
+            .dead_letters
+            .read()
+            .await
+            .iter()
+            .filter(|record| {
+                query
+                    .source
+                    .as_ref()
+                    .is_none_or(|source| &record.source == source)
+                    && query
+                        .run_id
+                        .as_ref()
+                        .is_none_or(|run_id| &record.run_id == run_id)
+                    && query
+                        .disposition
+                        .is_none_or(|disposition| record.disposition == disposition)
+                    && query
+                        .reason
+                        .is_none_or(|reason| record.reasons.contains(&reason))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        records.sort_by_key(|record| std::cmp::Reverse(record.recorded_at));
+        records.truncate(query.limit);
+        Ok(records)
+    }
+
+    async fn list_source_links(
+        &self,
+        canonical_id: Option<&str>,
+    ) -> Result<Vec<SourceRecordLink>, RepositoryError> {
+        let mut links = self
+            .source_links
+            .read()
+            .await
+            .values()
+            .filter(|link| canonical_id.is_none_or(|id| link.canonical_id == id))
+            .cloned()
+            .collect::<Vec<_>>();
+        links.sort_by(|left, right| {
+            (&left.source, &left.source_id).cmp(&(&right.source, &right.source_id))
+        });
+        Ok(links)
+    }
+
+    async fn upsert_source_links(
+        &self,
+        links: Vec<SourceRecordLink>,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.source_links.write().await;
+        for link in links {
+            write_guard.insert((link.source.clone(), link.source_id.clone()), link);
+        }
+        Ok(())
+    }
+
+    async fn get_sync_state(
+        &self,
+        source: &str,
+    ) -> Result<Option<ConnectorSyncState>, RepositoryError> {
+        Ok(self.sync_states.read().await.get(source).cloned())
+    }
+
+    async fn put_sync_state(&self, state: ConnectorSyncState) -> Result<(), RepositoryError> {
+        self.sync_states
+            .write()
+            .await
+            .insert(state.source.clone(), state);
+        Ok(())
+    }
+
+    async fn get_geocodes(
+        &self,
+        address_keys: &[String],
+    ) -> Result<HashMap<String, GeocodeCacheEntry>, RepositoryError> {
+        let read_guard = self.geocodes.read().await;
+        Ok(address_keys
+            .iter()
+            .filter_map(|key| {
+                read_guard
+                    .get(key)
+                    .map(|entry| (key.clone(), entry.clone()))
+            })
+            .collect())
+    }
+
+    async fn upsert_geocodes(
+        &self,
+        entries: Vec<GeocodeCacheEntry>,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.geocodes.write().await;
+        for entry in entries {
+            write_guard.insert(entry.address_key.clone(), entry);
+        }
+        Ok(())
+    }
+
+    async fn append_facility_events(
+        &self,
+        events: Vec<FacilityEvent>,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.facility_events.write().await;
+        write_guard.extend(events);
+        Ok(())
+    }
+
+    async fn list_facility_events(
+        &self,
+        query: &FacilityEventQuery,
+    ) -> Result<Vec<FacilityEvent>, RepositoryError> {
+        let mut events = self
+            .facility_events
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|event| {
+                query
+                    .facility_id
+                    .as_ref()
+                    .is_none_or(|facility_id| &event.facility_id == facility_id)
+                    && query
+                        .jurisdiction
+                        .as_ref()
+                        .is_none_or(|jurisdiction| &event.jurisdiction == jurisdiction)
+                    && query.kind.is_none_or(|kind| event.change.kind() == kind)
+                    && query.since.is_none_or(|since| event.detected_at > since)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| std::cmp::Reverse(event.detected_at));
+        events.truncate(query.limit);
+        Ok(events)
+    }
+
+    async fn create_webhook_subscription(
+        &self,
+        subscription: WebhookSubscription,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.webhook_subscriptions.write().await;
+        write_guard.push(subscription);
+        Ok(())
+    }
+
+    async fn list_webhook_subscriptions(
+        &self,
+    ) -> Result<Vec<WebhookSubscription>, RepositoryError> {
+        Ok(self.webhook_subscriptions.read().await.clone())
+    }
+
+    async fn delete_webhook_subscription(&self, id: &str) -> Result<bool, RepositoryError> {
+        let mut write_guard = self.webhook_subscriptions.write().await;
+        let before = write_guard.len();
+        write_guard.retain(|subscription| subscription.id != id);
+        Ok(write_guard.len() != before)
+    }
+
+    async fn enqueue_webhook_deliveries(
+        &self,
+        deliveries: Vec<WebhookDelivery>,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.webhook_deliveries.write().await;
+        write_guard.extend(deliveries);
+        Ok(())
+    }
+
+    async fn claim_due_webhook_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        let mut write_guard = self.webhook_deliveries.write().await;
+        let mut due = write_guard
+            .iter_mut()
+            .filter(|delivery| {
+                delivery.status == WebhookDeliveryStatus::Pending && delivery.next_attempt_at <= now
+            })
+            .collect::<Vec<_>>();
+        due.sort_by_key(|delivery| delivery.next_attempt_at);
+
+        Ok(due
+            .into_iter()
+            .take(limit)
+            .map(|delivery| {
+                let claimed = delivery.clone();
+                delivery.next_attempt_at = lease_until;
+                claimed
+            })
+            .collect())
+    }
+
+    async fn update_webhook_delivery(
+        &self,
+        delivery: WebhookDelivery,
+    ) -> Result<(), RepositoryError> {
+        let mut write_guard = self.webhook_deliveries.write().await;
+        if let Some(stored) = write_guard
+            .iter_mut()
+            .find(|stored| stored.id == delivery.id)
+        {
+            *stored = delivery;
+        }
+        Ok(())
+    }
+
+    async fn list_webhook_deliveries(
+        &self,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        let mut deliveries = self
+            .webhook_deliveries
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|delivery| {
+                query
+                    .subscription_id
+                    .as_ref()
+                    .is_none_or(|subscription_id| &delivery.subscription_id == subscription_id)
+                    && query
+                        .event_id
+                        .as_ref()
+                        .is_none_or(|event_id| &delivery.event_id == event_id)
+                    && query.status.is_none_or(|status| delivery.status == status)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        deliveries.sort_by_key(|delivery| std::cmp::Reverse(delivery.created_at));
+        deliveries.truncate(query.limit);
+        Ok(deliveries)
+    }
+
     async fn upsert_facility_vote(
         &self,
         facility_id: &str,
@@ -96,7 +425,10 @@ impl FacilityRepository for InMemoryFacilityRepository {
             return Ok(HashMap::new());
         }
 
-        let id_set = facility_ids.iter().cloned().collect::<std::collections::HashSet<_>>();
+        let id_set = facility_ids
+            .iter()
+            .cloned()
+            .collect::<std::collections::HashSet<_>>();
         let all_votes = self.votes.read().await;
         let mut summaries: HashMap<String, FacilityVoteSummary> = HashMap::new();
 
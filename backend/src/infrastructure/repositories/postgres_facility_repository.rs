@@ -1,15 +1,20 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Postgres, QueryBuilder, Row, postgres::PgPoolOptions};
 
 use crate::domain::{
     entities::{
-        ConnectorIngestionStatus, Facility, FacilityVoteSummary, Inspection, Jurisdiction,
-        SystemIngestionStatus, VoteValue,
+        CircuitState, ConnectorCircuit, ConnectorIngestionStatus, ConnectorSyncState,
+        DeadLetterRecord, Facility, FacilityChange, FacilityEvent, FacilityEventKind,
+        FacilityVoteSummary, GeocodeCacheEntry, GeocodeMatch, IngestionRun, IngestionRunOutcome,
+        IngestionTrigger, Inspection, Jurisdiction, LocationPrecision, RecordDisposition,
+        SourceRecordLink, SystemIngestionStatus, ValidationReason, VoteValue, WebhookDelivery,
+        WebhookDeliveryStatus, WebhookSubscription,
     },
     errors::RepositoryError,
-    repositories::FacilityRepository,
+    repositories::{DeadLetterQuery, FacilityEventQuery, FacilityRepository, WebhookDeliveryQuery},
 };
 
 pub struct PostgresFacilityRepository {
@@ -43,6 +48,7 @@ impl PostgresFacilityRepository {
                 postal_code TEXT NOT NULL,
                 latitude DOUBLE PRECISION NOT NULL,
                 longitude DOUBLE PRECISION NOT NULL,
+                location_precision TEXT NOT NULL DEFAULT 'jurisdiction_default',
                 jurisdiction TEXT NOT NULL,
                 trust_score SMALLINT NOT NULL,
                 inspections JSONB NOT NULL,
@@ -54,6 +60,18 @@ impl PostgresFacilityRepository {
         .await
         .map_err(to_repository_error)?;
 
+        // Rows stored before precision was tracked may sit on a fallback point, so they
+        // count as imprecise until their next refresh derives the real precision.
+        sqlx::query(
+            r#"
+            ALTER TABLE facilities
+            ADD COLUMN IF NOT EXISTS location_precision TEXT NOT NULL DEFAULT 'jurisdiction_default'
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
         sqlx::query(
             r#"
             CREATE INDEX IF NOT EXISTS idx_facilities_postal_code
@@ -64,6 +82,16 @@ impl PostgresFacilityRepository {
         .await
         .map_err(to_repository_error)?;
 
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_facilities_jurisdiction
+            ON facilities (jurisdiction)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS system_ingestion_status (
@@ -78,6 +106,246 @@ impl PostgresFacilityRepository {
         .await
         .map_err(to_repository_error)?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ingestion_runs (
+                id TEXT PRIMARY KEY,
+                trigger TEXT NOT NULL,
+                started_at TIMESTAMPTZ NOT NULL,
+                finished_at TIMESTAMPTZ,
+                outcome TEXT NOT NULL,
+                unique_facilities BIGINT,
+                connector_stats JSONB NOT NULL,
+                message TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_ingestion_runs_started_at
+            ON ingestion_runs (started_at DESC)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS connector_circuits (
+                source TEXT PRIMARY KEY,
+                state TEXT NOT NULL,
+                consecutive_failures INTEGER NOT NULL,
+                opened_at TIMESTAMPTZ,
+                last_failure_at TIMESTAMPTZ,
+                last_error TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS connector_sync_state (
+                source TEXT PRIMARY KEY,
+                watermark TEXT,
+                rows JSONB NOT NULL,
+                full_sync_at TIMESTAMPTZ NOT NULL,
+                synced_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS dead_letter_records (
+                id TEXT PRIMARY KEY,
+                run_id TEXT NOT NULL,
+                source TEXT NOT NULL,
+                source_id TEXT,
+                disposition TEXT NOT NULL,
+                reasons JSONB NOT NULL,
+                payload JSONB NOT NULL,
+                recorded_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_dead_letter_records_source
+            ON dead_letter_records (source, recorded_at DESC)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS source_record_links (
+                source TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                canonical_id TEXT NOT NULL,
+                jurisdiction TEXT NOT NULL,
+                resolved_at TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (source, source_id)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            ALTER TABLE source_record_links
+            ADD COLUMN IF NOT EXISTS source_file TEXT
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_source_record_links_canonical
+            ON source_record_links (canonical_id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS geocode_cache (
+                address_key TEXT PRIMARY KEY,
+                gazetteer TEXT NOT NULL,
+                quality TEXT NOT NULL,
+                latitude DOUBLE PRECISION,
+                longitude DOUBLE PRECISION,
+                matched_address TEXT,
+                geocoded_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS facility_events (
+                seq BIGSERIAL PRIMARY KEY,
+                id TEXT NOT NULL UNIQUE,
+                run_id TEXT NOT NULL,
+                facility_id TEXT NOT NULL,
+                facility_name TEXT NOT NULL,
+                jurisdiction TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                change JSONB NOT NULL,
+                detected_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_facility_events_facility
+            ON facility_events (facility_id, seq DESC)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_facility_events_detected_at
+            ON facility_events (detected_at DESC)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+                id TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                event_types JSONB NOT NULL,
+                jurisdictions JSONB NOT NULL,
+                facility_ids JSONB NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id TEXT PRIMARY KEY,
+                subscription_id TEXT NOT NULL,
+                event_id TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                payload JSONB NOT NULL,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL,
+                next_attempt_at TIMESTAMPTZ NOT NULL,
+                last_attempt_at TIMESTAMPTZ,
+                last_response_status INTEGER,
+                last_error TEXT,
+                created_at TIMESTAMPTZ NOT NULL,
+                delivered_at TIMESTAMPTZ
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+            ON webhook_deliveries (next_attempt_at)
+            WHERE status = 'pending'
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription
+            ON webhook_deliveries (subscription_id, created_at DESC)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS facility_votes (
@@ -109,10 +377,15 @@ impl PostgresFacilityRepository {
 
 #[async_trait]
 impl FacilityRepository for PostgresFacilityRepository {
-    async fn replace_all(&self, facilities: Vec<Facility>) -> Result<(), RepositoryError> {
+    async fn replace_facilities(
+        &self,
+        removed_ids: &[String],
+        facilities: Vec<Facility>,
+    ) -> Result<(), RepositoryError> {
         let mut transaction = self.pool.begin().await.map_err(to_repository_error)?;
 
-        sqlx::query("TRUNCATE TABLE facilities")
+        sqlx::query("DELETE FROM facilities WHERE id = ANY($1)")
+            .bind(removed_ids)
             .execute(&mut *transaction)
             .await
             .map_err(to_repository_error)?;
@@ -120,7 +393,7 @@ impl FacilityRepository for PostgresFacilityRepository {
         if !facilities.is_empty() {
             for chunk in facilities.chunks(1_000) {
                 let mut builder = QueryBuilder::<Postgres>::new(
-                    "INSERT INTO facilities (id, source_id, name, address, city, state, postal_code, latitude, longitude, jurisdiction, trust_score, inspections, updated_at) ",
+                    "INSERT INTO facilities (id, source_id, name, address, city, state, postal_code, latitude, longitude, location_precision, jurisdiction, trust_score, inspections, updated_at) ",
                 );
 
                 builder.push_values(chunk.iter(), |mut row, facility| {
@@ -136,6 +409,7 @@ impl FacilityRepository for PostgresFacilityRepository {
                         .push_bind(&facility.postal_code)
                         .push_bind(facility.latitude)
                         .push_bind(facility.longitude)
+                        .push_bind(facility.location_precision.code())
                         .push_bind(facility.jurisdiction.code())
                         .push_bind(i16::from(facility.trust_score))
                         .push_bind(inspections)
@@ -156,7 +430,7 @@ impl FacilityRepository for PostgresFacilityRepository {
 
     async fn list(&self) -> Result<Vec<Facility>, RepositoryError> {
         let rows = sqlx::query(
-            "SELECT id, source_id, name, address, city, state, postal_code, latitude, longitude, jurisdiction, trust_score, inspections, updated_at FROM facilities",
+            "SELECT id, source_id, name, address, city, state, postal_code, latitude, longitude, location_precision, jurisdiction, trust_score, inspections, updated_at FROM facilities",
         )
         .fetch_all(&self.pool)
         .await
@@ -167,7 +441,7 @@ impl FacilityRepository for PostgresFacilityRepository {
 
     async fn get_by_id(&self, id: &str) -> Result<Option<Facility>, RepositoryError> {
         let maybe_row = sqlx::query(
-            "SELECT id, source_id, name, address, city, state, postal_code, latitude, longitude, jurisdiction, trust_score, inspections, updated_at FROM facilities WHERE id = $1",
+            "SELECT id, source_id, name, address, city, state, postal_code, latitude, longitude, location_precision, jurisdiction, trust_score, inspections, updated_at FROM facilities WHERE id = $1",
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -235,6 +509,621 @@ impl FacilityRepository for PostgresFacilityRepository {
             .transpose()
     }
 
+    async fn record_ingestion_run(&self, run: IngestionRun) -> Result<(), RepositoryError> {
+        let connector_stats = serde_json::to_value(&run.connector_stats)
+            .map_err(|error| RepositoryError::message(error.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ingestion_runs (id, trigger, started_at, finished_at, outcome, unique_facilities, connector_stats, message)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id)
+            DO UPDATE SET
+                finished_at = EXCLUDED.finished_at,
+                outcome = EXCLUDED.outcome,
+                unique_facilities = EXCLUDED.unique_facilities,
+                connector_stats = EXCLUDED.connector_stats,
+                message = EXCLUDED.message
+            "#,
+        )
+        .bind(&run.id)
+        .bind(run.trigger.code())
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .bind(run.outcome.code())
+        .bind(run.unique_facilities.map(|value| value as i64))
+        .bind(connector_stats)
+        .bind(&run.message)
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        Ok(())
+    }
+
+    async fn abandon_running_ingestion_runs(
+        &self,
+        finished_at: DateTime<Utc>,
+        message: &str,
+    ) -> Result<usize, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE ingestion_runs SET outcome = $1, finished_at = $2, message = $3 WHERE outcome = $4",
+        )
+        .bind(IngestionRunOutcome::Abandoned.code())
+        .bind(finished_at)
+        .bind(message)
+        .bind(IngestionRunOutcome::Running.code())
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn list_ingestion_runs(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<IngestionRun>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, trigger, started_at, finished_at, outcome, unique_facilities, connector_stats, message FROM ingestion_runs ORDER BY started_at DESC LIMIT $1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        rows.into_iter().map(map_ingestion_run_row).collect()
+    }
+
+    async fn list_connector_circuits(&self) -> Result<Vec<ConnectorCircuit>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT source, state, consecutive_failures, opened_at, last_failure_at, last_error FROM connector_circuits ORDER BY source",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        rows.into_iter().map(map_connector_circuit_row).collect()
+    }
+
+    async fn upsert_connector_circuit(
+        &self,
+        circuit: ConnectorCircuit,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO connector_circuits (source, state, consecutive_failures, opened_at, last_failure_at, last_error)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (source)
+            DO UPDATE SET
+                state = EXCLUDED.state,
+                consecutive_failures = EXCLUDED.consecutive_failures,
+                opened_at = EXCLUDED.opened_at,
+                last_failure_at = EXCLUDED.last_failure_at,
+                last_error = EXCLUDED.last_error
+            "#,
+        )
+        .bind(&circuit.source)
+        .bind(circuit.state.code())
+        .bind(i32::try_from(circuit.consecutive_failures).unwrap_or(i32::MAX))
+        .bind(circuit.opened_at)
+        .bind(circuit.last_failure_at)
+        .bind(&circuit.last_error)
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        Ok(())
+    }
+
+    async fn get_sync_state(
+        &self,
+        source: &str,
+    ) -> Result<Option<ConnectorSyncState>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT source, watermark, rows, full_sync_at, synced_at FROM connector_sync_state WHERE source = $1",
+        )
+        .bind(source)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        Ok(row.map(|row| ConnectorSyncState {
+            source: row.get("source"),
+            watermark: row.get("watermark"),
+            rows: row.get("rows"),
+            full_sync_at: row.get("full_sync_at"),
+            synced_at: row.get("synced_at"),
+        }))
+    }
+
+    async fn put_sync_state(&self, state: ConnectorSyncState) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO connector_sync_state (source, watermark, rows, full_sync_at, synced_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (source)
+            DO UPDATE SET
+                watermark = EXCLUDED.watermark,
+                rows = EXCLUDED.rows,
+                full_sync_at = EXCLUDED.full_sync_at,
+                synced_at = EXCLUDED.synced_at
+            "#,
+        )
+        .bind(&state.source)
+        .bind(&state.watermark)
+        .bind(&state.rows)
+        .bind(state.full_sync_at)
+        .bind(state.synced_at)
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        Ok(())
+    }
+
+    async fn replace_dead_letters(
+        &self,
+        source: &str,
+        records: Vec<DeadLetterRecord>,
+    ) -> Result<(), RepositoryError> {
+        let mut transaction = self.pool.begin().await.map_err(to_repository_error)?;
+
+        sqlx::query("DELETE FROM dead_letter_records WHERE source = $1")
+            .bind(source)
+            .execute(&mut *transaction)
+            .await
+            .map_err(to_repository_error)?;
+
+        for chunk in records.chunks(1_000) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO dead_letter_records (id, run_id, source, source_id, disposition, reasons, payload, recorded_at) ",
+            );
+
+            builder.push_values(chunk.iter(), |mut row, record| {
+                let reasons =
+                    serde_json::to_value(&record.reasons).unwrap_or_else(|_| serde_json::json!([]));
+
+                row.push_bind(&record.id)
+                    .push_bind(&record.run_id)
+                    .push_bind(&record.source)
+                    .push_bind(&record.source_id)
+                    .push_bind(record.disposition.code())
+                    .push_bind(reasons)
+                    .push_bind(&record.payload)
+                    .push_bind(record.recorded_at);
+            });
+
+            builder
+                .build()
+                .execute(&mut *transaction)
+                .await
+                .map_err(to_repository_error)?;
+        }
+
+        transaction.commit().await.map_err(to_repository_error)?;
+        Ok(())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        query: &DeadLetterQuery,
+    ) -> Result<Vec<DeadLetterRecord>, RepositoryError> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, run_id, source, source_id, disposition, reasons, payload, recorded_at FROM dead_letter_records WHERE TRUE",
+        );
+        if let Some(source) = &query.source {
+            builder.push(" AND source = ").push_bind(source);
+        }
+        if let Some(run_id) = &query.run_id {
+            builder.push(" AND run_id = ").push_bind(run_id);
+        }
+        if let Some(disposition) = query.disposition {
+            builder
+                .push(" AND disposition = ")
+                .push_bind(disposition.code());
+        }
+        if let Some(reason) = query.reason {
+            builder.push(" AND reasons ? ").push_bind(reason.code());
+        }
+        builder
+            .push(" ORDER BY recorded_at DESC LIMIT ")
+            .push_bind(query.limit as i64);
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(to_repository_error)?;
+
+        rows.into_iter().map(map_dead_letter_row).collect()
+    }
+
+    async fn list_source_links(
+        &self,
+        canonical_id: Option<&str>,
+    ) -> Result<Vec<SourceRecordLink>, RepositoryError> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT source, source_id, canonical_id, jurisdiction, source_file, resolved_at FROM source_record_links WHERE TRUE",
+        );
+        if let Some(canonical_id) = canonical_id {
+            builder.push(" AND canonical_id = ").push_bind(canonical_id);
+        }
+        builder.push(" ORDER BY source, source_id");
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(to_repository_error)?;
+
+        rows.into_iter().map(map_source_link_row).collect()
+    }
+
+    async fn upsert_source_links(
+        &self,
+        links: Vec<SourceRecordLink>,
+    ) -> Result<(), RepositoryError> {
+        let mut transaction = self.pool.begin().await.map_err(to_repository_error)?;
+
+        for chunk in links.chunks(1_000) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO source_record_links (source, source_id, canonical_id, jurisdiction, source_file, resolved_at) ",
+            );
+
+            builder.push_values(chunk.iter(), |mut row, link| {
+                row.push_bind(&link.source)
+                    .push_bind(&link.source_id)
+                    .push_bind(&link.canonical_id)
+                    .push_bind(link.jurisdiction.code())
+                    .push_bind(&link.source_file)
+                    .push_bind(link.resolved_at);
+            });
+            builder.push(
+                " ON CONFLICT (source, source_id) DO UPDATE SET canonical_id = EXCLUDED.canonical_id, jurisdiction = EXCLUDED.jurisdiction, source_file = EXCLUDED.source_file, resolved_at = EXCLUDED.resolved_at",
+            );
+
+            builder
+                .build()
+                .execute(&mut *transaction)
+                .await
+                .map_err(to_repository_error)?;
+        }
+
+        transaction.commit().await.map_err(to_repository_error)?;
+        Ok(())
+    }
+
+    async fn get_geocodes(
+        &self,
+        address_keys: &[String],
+    ) -> Result<HashMap<String, GeocodeCacheEntry>, RepositoryError> {
+        let mut entries = HashMap::with_capacity(address_keys.len());
+        for chunk in address_keys.chunks(1_000) {
+            let rows = sqlx::query(
+                "SELECT address_key, gazetteer, quality, latitude, longitude, matched_address, geocoded_at FROM geocode_cache WHERE address_key = ANY($1)",
+            )
+            .bind(chunk)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(to_repository_error)?;
+
+            for row in rows {
+                let entry = map_geocode_row(row)?;
+                entries.insert(entry.address_key.clone(), entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    async fn upsert_geocodes(
+        &self,
+        entries: Vec<GeocodeCacheEntry>,
+    ) -> Result<(), RepositoryError> {
+        let mut transaction = self.pool.begin().await.map_err(to_repository_error)?;
+
+        for chunk in entries.chunks(1_000) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO geocode_cache (address_key, gazetteer, quality, latitude, longitude, matched_address, geocoded_at) ",
+            );
+
+            builder.push_values(chunk.iter(), |mut row, entry| {
+                row.push_bind(&entry.address_key)
+                    .push_bind(&entry.gazetteer)
+                    .push_bind(entry.quality.code())
+                    .push_bind(entry.latitude)
+                    .push_bind(entry.longitude)
+                    .push_bind(&entry.matched_address)
+                    .push_bind(entry.geocoded_at);
+            });
+            builder.push(
+                " ON CONFLICT (address_key) DO UPDATE SET gazetteer = EXCLUDED.gazetteer, quality = EXCLUDED.quality, latitude = EXCLUDED.latitude, longitude = EXCLUDED.longitude, matched_address = EXCLUDED.matched_address, geocoded_at = EXCLUDED.geocoded_at",
+            );
+
+            builder
+                .build()
+                .execute(&mut *transaction)
+                .await
+                .map_err(to_repository_error)?;
+        }
+
+        transaction.commit().await.map_err(to_repository_error)?;
+        Ok(())
+    }
+
+    async fn append_facility_events(
+        &self,
+        events: Vec<FacilityEvent>,
+    ) -> Result<(), RepositoryError> {
+        let mut transaction = self.pool.begin().await.map_err(to_repository_error)?;
+
+        for chunk in events.chunks(1_000) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO facility_events (id, run_id, facility_id, facility_name, jurisdiction, kind, change, detected_at) ",
+            );
+
+            builder.push_values(chunk.iter(), |mut row, event| {
+                let change = serde_json::to_value(&event.change).unwrap_or(serde_json::Value::Null);
+
+                row.push_bind(&event.id)
+                    .push_bind(&event.run_id)
+                    .push_bind(&event.facility_id)
+                    .push_bind(&event.facility_name)
+                    .push_bind(event.jurisdiction.code())
+                    .push_bind(event.change.kind().code())
+                    .push_bind(change)
+                    .push_bind(event.detected_at);
+            });
+
+            builder
+                .build()
+                .execute(&mut *transaction)
+                .await
+                .map_err(to_repository_error)?;
+        }
+
+        transaction.commit().await.map_err(to_repository_error)?;
+        Ok(())
+    }
+
+    async fn list_facility_events(
+        &self,
+        query: &FacilityEventQuery,
+    ) -> Result<Vec<FacilityEvent>, RepositoryError> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, run_id, facility_id, facility_name, jurisdiction, change, detected_at FROM facility_events WHERE TRUE",
+        );
+        if let Some(facility_id) = &query.facility_id {
+            builder.push(" AND facility_id = ").push_bind(facility_id);
+        }
+        if let Some(jurisdiction) = &query.jurisdiction {
+            builder
+                .push(" AND jurisdiction = ")
+                .push_bind(jurisdiction.code());
+        }
+        if let Some(kind) = query.kind {
+            builder.push(" AND kind = ").push_bind(kind.code());
+        }
+        if let Some(since) = query.since {
+            builder.push(" AND detected_at > ").push_bind(since);
+        }
+        builder
+            .push(" ORDER BY detected_at DESC, seq DESC LIMIT ")
+            .push_bind(query.limit as i64);
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(to_repository_error)?;
+
+        rows.into_iter().map(map_facility_event_row).collect()
+    }
+
+    async fn create_webhook_subscription(
+        &self,
+        subscription: WebhookSubscription,
+    ) -> Result<(), RepositoryError> {
+        let event_types = serde_json::to_value(&subscription.event_types)
+            .unwrap_or_else(|_| serde_json::json!([]));
+        let jurisdictions = serde_json::json!(
+            subscription
+                .jurisdictions
+                .iter()
+                .map(Jurisdiction::code)
+                .collect::<Vec<_>>()
+        );
+        let facility_ids = serde_json::json!(subscription.facility_ids);
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_subscriptions (
+                id, url, secret, event_types, jurisdictions, facility_ids, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&subscription.id)
+        .bind(&subscription.url)
+        .bind(&subscription.secret)
+        .bind(event_types)
+        .bind(jurisdictions)
+        .bind(facility_ids)
+        .bind(subscription.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        Ok(())
+    }
+
+    async fn list_webhook_subscriptions(
+        &self,
+    ) -> Result<Vec<WebhookSubscription>, RepositoryError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, url, secret, event_types, jurisdictions, facility_ids, created_at
+            FROM webhook_subscriptions
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        rows.into_iter().map(map_webhook_subscription_row).collect()
+    }
+
+    async fn delete_webhook_subscription(&self, id: &str) -> Result<bool, RepositoryError> {
+        let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(to_repository_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn enqueue_webhook_deliveries(
+        &self,
+        deliveries: Vec<WebhookDelivery>,
+    ) -> Result<(), RepositoryError> {
+        let mut transaction = self.pool.begin().await.map_err(to_repository_error)?;
+
+        for chunk in deliveries.chunks(1_000) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO webhook_deliveries (id, subscription_id, event_id, event_type, payload, status, attempts, next_attempt_at, last_attempt_at, last_response_status, last_error, created_at, delivered_at) ",
+            );
+
+            builder.push_values(chunk.iter(), |mut row, delivery| {
+                row.push_bind(&delivery.id)
+                    .push_bind(&delivery.subscription_id)
+                    .push_bind(&delivery.event_id)
+                    .push_bind(delivery.event_type.code())
+                    .push_bind(&delivery.payload)
+                    .push_bind(delivery.status.code())
+                    .push_bind(i32::try_from(delivery.attempts).unwrap_or(i32::MAX))
+                    .push_bind(delivery.next_attempt_at)
+                    .push_bind(delivery.last_attempt_at)
+                    .push_bind(delivery.last_response_status.map(i32::from))
+                    .push_bind(&delivery.last_error)
+                    .push_bind(delivery.created_at)
+                    .push_bind(delivery.delivered_at);
+            });
+
+            builder
+                .build()
+                .execute(&mut *transaction)
+                .await
+                .map_err(to_repository_error)?;
+        }
+
+        transaction.commit().await.map_err(to_repository_error)?;
+        Ok(())
+    }
+
+    async fn claim_due_webhook_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        let rows = sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET next_attempt_at = $2
+            WHERE id IN (
+                SELECT id
+                FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= $1
+                ORDER BY next_attempt_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, subscription_id, event_id, event_type, payload, status, attempts,
+                next_attempt_at, last_attempt_at, last_response_status, last_error, created_at,
+                delivered_at
+            "#,
+        )
+        .bind(now)
+        .bind(lease_until)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        let mut deliveries = rows
+            .into_iter()
+            .map(map_webhook_delivery_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        deliveries.sort_by_key(|delivery| delivery.created_at);
+        Ok(deliveries)
+    }
+
+    async fn update_webhook_delivery(
+        &self,
+        delivery: WebhookDelivery,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $2,
+                attempts = $3,
+                next_attempt_at = $4,
+                last_attempt_at = $5,
+                last_response_status = $6,
+                last_error = $7,
+                delivered_at = $8
+            WHERE id = $1
+            "#,
+        )
+        .bind(&delivery.id)
+        .bind(delivery.status.code())
+        .bind(i32::try_from(delivery.attempts).unwrap_or(i32::MAX))
+        .bind(delivery.next_attempt_at)
+        .bind(delivery.last_attempt_at)
+        .bind(delivery.last_response_status.map(i32::from))
+        .bind(&delivery.last_error)
+        .bind(delivery.delivered_at)
+        .execute(&self.pool)
+        .await
+        .map_err(to_repository_error)?;
+
+        Ok(())
+    }
+
+    async fn list_webhook_deliveries(
+        &self,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, subscription_id, event_id, event_type, payload, status, attempts, next_attempt_at, last_attempt_at, last_response_status, last_error, created_at, delivered_at FROM webhook_deliveries WHERE TRUE",
+        );
+        if let Some(subscription_id) = &query.subscription_id {
+            builder
+                .push(" AND subscription_id = ")
+                .push_bind(subscription_id);
+        }
+        if let Some(event_id) = &query.event_id {
+            builder.push(" AND event_id = ").push_bind(event_id);
+        }
+        if let Some(status) = query.status {
+            builder.push(" AND status = ").push_bind(status.code());
+        }
+        builder
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(query.limit as i64);
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(to_repository_error)?;
+
+        rows.into_iter().map(map_webhook_delivery_row).collect()
+    }
+
     async fn upsert_facility_vote(
         &self,
         facility_id: &str,
@@ -310,10 +1199,14 @@ fn map_facility_row(row: sqlx::postgres::PgRow) -> Result<Facility, RepositoryEr
     let jurisdiction_code: String = row.get("jurisdiction");
     let inspections_json: serde_json::Value = row.get("inspections");
     let trust_score_raw: i16 = row.get("trust_score");
+    let precision_code: String = row.get("location_precision");
 
     let jurisdiction = Jurisdiction::from_code(&jurisdiction_code).ok_or_else(|| {
         RepositoryError::message(format!("unknown jurisdiction code: {jurisdiction_code}"))
     })?;
+    let location_precision = LocationPrecision::from_code(&precision_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown location precision: {precision_code}"))
+    })?;
 
     let inspections: Vec<Inspection> =
         serde_json::from_value(inspections_json).map_err(|error| {
@@ -330,6 +1223,7 @@ fn map_facility_row(row: sqlx::postgres::PgRow) -> Result<Facility, RepositoryEr
         postal_code: row.get("postal_code"),
         latitude: row.get("latitude"),
         longitude: row.get("longitude"),
+        location_precision,
         jurisdiction,
         trust_score: u8::try_from(trust_score_raw).unwrap_or(0),
         inspections,
@@ -337,6 +1231,203 @@ fn map_facility_row(row: sqlx::postgres::PgRow) -> Result<Facility, RepositoryEr
     })
 }
 
+fn map_ingestion_run_row(row: sqlx::postgres::PgRow) -> Result<IngestionRun, RepositoryError> {
+    let trigger_code: String = row.get("trigger");
+    let outcome_code: String = row.get("outcome");
+    let connector_stats_json: serde_json::Value = row.get("connector_stats");
+    let unique_facilities_raw: Option<i64> = row.get("unique_facilities");
+
+    let trigger = IngestionTrigger::from_code(&trigger_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown ingestion trigger: {trigger_code}"))
+    })?;
+    let outcome = IngestionRunOutcome::from_code(&outcome_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown ingestion outcome: {outcome_code}"))
+    })?;
+    let connector_stats: Vec<ConnectorIngestionStatus> =
+        serde_json::from_value(connector_stats_json).map_err(|error| {
+            RepositoryError::message(format!("unable to decode connector stats: {error}"))
+        })?;
+
+    Ok(IngestionRun {
+        id: row.get("id"),
+        trigger,
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+        outcome,
+        unique_facilities: unique_facilities_raw.and_then(|value| usize::try_from(value).ok()),
+        connector_stats,
+        message: row.get("message"),
+    })
+}
+
+fn map_connector_circuit_row(
+    row: sqlx::postgres::PgRow,
+) -> Result<ConnectorCircuit, RepositoryError> {
+    let state_code: String = row.get("state");
+    let consecutive_failures: i32 = row.get("consecutive_failures");
+
+    let state = CircuitState::from_code(&state_code)
+        .ok_or_else(|| RepositoryError::message(format!("unknown circuit state: {state_code}")))?;
+
+    Ok(ConnectorCircuit {
+        source: row.get("source"),
+        state,
+        consecutive_failures: u32::try_from(consecutive_failures).unwrap_or(0),
+        opened_at: row.get("opened_at"),
+        last_failure_at: row.get("last_failure_at"),
+        last_error: row.get("last_error"),
+    })
+}
+
+fn map_dead_letter_row(row: sqlx::postgres::PgRow) -> Result<DeadLetterRecord, RepositoryError> {
+    let disposition_code: String = row.get("disposition");
+    let reasons_json: serde_json::Value = row.get("reasons");
+
+    let disposition = RecordDisposition::from_code(&disposition_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown record disposition: {disposition_code}"))
+    })?;
+    let reasons: Vec<ValidationReason> = serde_json::from_value(reasons_json).map_err(|error| {
+        RepositoryError::message(format!("unable to decode validation reasons: {error}"))
+    })?;
+
+    Ok(DeadLetterRecord {
+        id: row.get("id"),
+        run_id: row.get("run_id"),
+        source: row.get("source"),
+        source_id: row.get("source_id"),
+        disposition,
+        reasons,
+        payload: row.get("payload"),
+        recorded_at: row.get("recorded_at"),
+    })
+}
+
+fn map_source_link_row(row: sqlx::postgres::PgRow) -> Result<SourceRecordLink, RepositoryError> {
+    let jurisdiction_code: String = row.get("jurisdiction");
+
+    let jurisdiction = Jurisdiction::from_code(&jurisdiction_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown jurisdiction code: {jurisdiction_code}"))
+    })?;
+
+    Ok(SourceRecordLink {
+        source: row.get("source"),
+        source_id: row.get("source_id"),
+        canonical_id: row.get("canonical_id"),
+        jurisdiction,
+        source_file: row.get("source_file"),
+        resolved_at: row.get("resolved_at"),
+    })
+}
+
+fn map_geocode_row(row: sqlx::postgres::PgRow) -> Result<GeocodeCacheEntry, RepositoryError> {
+    let quality_code: String = row.get("quality");
+
+    let quality = GeocodeMatch::from_code(&quality_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown geocode match quality: {quality_code}"))
+    })?;
+
+    Ok(GeocodeCacheEntry {
+        address_key: row.get("address_key"),
+        gazetteer: row.get("gazetteer"),
+        quality,
+        latitude: row.get("latitude"),
+        longitude: row.get("longitude"),
+        matched_address: row.get("matched_address"),
+        geocoded_at: row.get("geocoded_at"),
+    })
+}
+
+fn map_facility_event_row(row: sqlx::postgres::PgRow) -> Result<FacilityEvent, RepositoryError> {
+    let jurisdiction_code: String = row.get("jurisdiction");
+    let change_json: serde_json::Value = row.get("change");
+
+    let jurisdiction = Jurisdiction::from_code(&jurisdiction_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown jurisdiction code: {jurisdiction_code}"))
+    })?;
+    let change: FacilityChange = serde_json::from_value(change_json).map_err(|error| {
+        RepositoryError::message(format!("unable to decode facility change: {error}"))
+    })?;
+
+    Ok(FacilityEvent {
+        id: row.get("id"),
+        run_id: row.get("run_id"),
+        facility_id: row.get("facility_id"),
+        facility_name: row.get("facility_name"),
+        jurisdiction,
+        change,
+        detected_at: row.get("detected_at"),
+    })
+}
+
+fn map_webhook_subscription_row(
+    row: sqlx::postgres::PgRow,
+) -> Result<WebhookSubscription, RepositoryError> {
+    let event_types_json: serde_json::Value = row.get("event_types");
+    let jurisdictions_json: serde_json::Value = row.get("jurisdictions");
+    let facility_ids_json: serde_json::Value = row.get("facility_ids");
+
+    let event_types = serde_json::from_value(event_types_json).map_err(|error| {
+        RepositoryError::message(format!("unable to decode webhook event types: {error}"))
+    })?;
+    let jurisdiction_codes: Vec<String> =
+        serde_json::from_value(jurisdictions_json).map_err(|error| {
+            RepositoryError::message(format!("unable to decode webhook jurisdictions: {error}"))
+        })?;
+    let jurisdictions = jurisdiction_codes
+        .iter()
+        .map(|code| {
+            Jurisdiction::from_code(code).ok_or_else(|| {
+                RepositoryError::message(format!("unknown jurisdiction code: {code}"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let facility_ids = serde_json::from_value(facility_ids_json).map_err(|error| {
+        RepositoryError::message(format!("unable to decode webhook facility ids: {error}"))
+    })?;
+
+    Ok(WebhookSubscription {
+        id: row.get("id"),
+        url: row.get("url"),
+        secret: row.get("secret"),
+        event_types,
+        jurisdictions,
+        facility_ids,
+        created_at: row.get("created_at"),
+    })
+}
+
+fn map_webhook_delivery_row(
+    row: sqlx::postgres::PgRow,
+) -> Result<WebhookDelivery, RepositoryError> {
+    let event_type_code: String = row.get("event_type");
+    let status_code: String = row.get("status");
+    let attempts: i32 = row.get("attempts");
+    let last_response_status: Option<i32> = row.get("last_response_status");
+
+    let event_type = FacilityEventKind::from_code(&event_type_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown facility event type: {event_type_code}"))
+    })?;
+    let status = WebhookDeliveryStatus::from_code(&status_code).ok_or_else(|| {
+        RepositoryError::message(format!("unknown webhook delivery status: {status_code}"))
+    })?;
+
+    Ok(WebhookDelivery {
+        id: row.get("id"),
+        subscription_id: row.get("subscription_id"),
+        event_id: row.get("event_id"),
+        event_type,
+        payload: row.get("payload"),
+        status,
+        attempts: u32::try_from(attempts).unwrap_or(0),
+        next_attempt_at: row.get("next_attempt_at"),
+        last_attempt_at: row.get("last_attempt_at"),
+        last_response_status: last_response_status.and_then(|value| u16::try_from(value).ok()),
+        last_error: row.get("last_error"),
+        created_at: row.get("created_at"),
+        delivered_at: row.get("delivered_at"),
+    })
+}
+
 fn to_repository_error(error: impl std::fmt::Display) -> RepositoryError {
     RepositoryError::message(error.to_string())
 }
@@ -3,7 +3,10 @@ use std::{sync::Arc, time::Duration};
 use tokio::time;
 use tracing::{error, info};
 
-use crate::application::services::IngestionService;
+use crate::{
+    application::services::{IngestionService, WebhookService},
+    domain::entities::IngestionTrigger,
+};
 
 pub async fn run(ingestion_service: Arc<IngestionService>, interval_hours: u64) {
     let mut interval = time::interval(Duration::from_secs(interval_hours.max(1) * 60 * 60));
@@ -14,7 +17,7 @@ pub async fn run(ingestion_service: Arc<IngestionService>, interval_hours: u64)
     loop {
         interval.tick().await;
 
-        if let Err(error) = ingestion_service.refresh().await {
+        if let Err(error) = ingestion_service.refresh(IngestionTrigger::Scheduler).await {
             error!(%error, "Scheduled ingestion failed");
             continue;
         }
@@ -22,3 +25,19 @@ pub async fn run(ingestion_service: Arc<IngestionService>, interval_hours: u64)
         info!("Scheduled ingestion completed");
     }
 }
+
+/// Drains the webhook outbox every `interval_secs`.
+pub async fn run_webhook_dispatcher(webhook_service: Arc<WebhookService>, interval_secs: u64) {
+    let mut interval = time::interval(Duration::from_secs(interval_secs.max(1)));
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        match webhook_service.dispatch_due().await {
+            Ok(0) => {}
+            Ok(attempted) => info!(attempted, "Webhook dispatch pass completed"),
+            Err(error) => error!(%error, "Webhook dispatch failed"),
+        }
+    }
+}
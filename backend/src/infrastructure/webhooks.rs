@@ -0,0 +1,268 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use hmac::{Hmac, Mac};
+use reqwest::{
+    Client, Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect::Policy,
+};
+use sha2::Sha256;
+
+pub const SIGNATURE_HEADER: &str = "X-Cleanplated-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Cleanplated-Timestamp";
+pub const EVENT_HEADER: &str = "X-Cleanplated-Event";
+pub const DELIVERY_HEADER: &str = "X-Cleanplated-Delivery";
+
+const USER_AGENT: &str = "cleanplated-webhooks/1.0";
+
+/// Signs `<timestamp>.<body>` with HMAC-SHA256, formatted as `sha256=<hex>`.
+/// Receivers recompute it with their secret and should reject stale timestamps.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Result of one delivery attempt.
+#[derive(Clone, Debug)]
+pub struct WebhookAttempt {
+    /// Response status, when the receiver answered at all.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl WebhookAttempt {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// One signed delivery request.
+pub struct WebhookRequest<'a> {
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub delivery_id: &'a str,
+    pub event_type: &'a str,
+    pub timestamp: i64,
+    pub body: &'a [u8],
+}
+
+#[derive(Clone)]
+pub struct WebhookClient {
+    client: Client,
+    /// Whether receivers on loopback, private or link-local addresses are allowed.
+    /// Off by default, since subscribers choose where the server POSTs.
+    allow_private_targets: bool,
+}
+
+impl WebhookClient {
+    /// Redirects are never followed, so a receiver cannot bounce a delivery onto
+    /// another host.
+    pub fn new(timeout: Duration, allow_private_targets: bool) -> Self {
+        let mut builder = Client::builder()
+            .timeout(timeout)
+            .user_agent(USER_AGENT)
+            .redirect(Policy::none());
+        if !allow_private_targets {
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = builder.build().unwrap_or_else(|_| Client::new());
+
+        Self {
+            client,
+            allow_private_targets,
+        }
+    }
+
+    /// Checks that `url` is an absolute http(s) URL whose host only resolves to public
+    /// addresses, unless private targets are allowed.
+    pub async fn check_target(&self, url: &str) -> Result<(), String> {
+        let url = Url::parse(url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| "url must be an absolute http or https URL".to_owned())?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| "url must name a host".to_owned())?;
+        if self.allow_private_targets {
+            return Ok(());
+        }
+
+        let literal = host.trim_start_matches('[').trim_end_matches(']');
+        let addresses = match literal.parse::<IpAddr>() {
+            Ok(ip) => vec![ip],
+            Err(_) => tokio::net::lookup_host((host, url.port_or_known_default().unwrap_or(443)))
+                .await
+                .map_err(|error| format!("unable to resolve {host}: {error}"))?
+                .map(|address| address.ip())
+                .collect(),
+        };
+        match addresses.iter().find(|ip| !is_public_address(**ip)) {
+            _ if addresses.is_empty() => Err(format!("{host} does not resolve")),
+            Some(ip) => Err(format!(
+                "{host} resolves to {ip}, which is not a public address"
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// POSTs the body as JSON. Any 2xx response counts as delivered.
+    pub async fn send(&self, request: WebhookRequest<'_>) -> WebhookAttempt {
+        // The resolver filters private addresses, but IP literals never reach it.
+        if let Err(error) = self.check_target(request.url).await {
+            return WebhookAttempt {
+                status: None,
+                error: Some(error),
+            };
+        }
+
+        let response = self
+            .client
+            .post(request.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                sign(request.secret, request.timestamp, request.body),
+            )
+            .header(TIMESTAMP_HEADER, request.timestamp.to_string())
+            .header(EVENT_HEADER, request.event_type)
+            .header(DELIVERY_HEADER, request.delivery_id)
+            .body(request.body.to_vec())
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => WebhookAttempt {
+                status: Some(response.status().as_u16()),
+                error: None,
+            },
+            Ok(response) => WebhookAttempt {
+                status: Some(response.status().as_u16()),
+                error: Some(format!("HTTP status {}", response.status())),
+            },
+            Err(error) => WebhookAttempt {
+                status: None,
+                error: Some(error.to_string()),
+            },
+        }
+    }
+}
+
+/// Resolves webhook hosts to their public addresses only, so a host cannot be
+/// re-pointed at an internal address after its registration was checked.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_owned();
+            let public = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| is_public_address(address.ip()))
+                .collect::<Vec<SocketAddr>>();
+            if public.is_empty() {
+                return Err(format!("{host} has no public address").into());
+            }
+            Ok(Box::new(public.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is a globally routable unicast address: not loopback, private,
+/// link-local, shared (CGNAT), documentation, benchmarking, multicast or reserved.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_v4(mapped),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || (first & 0xffc0) == 0xfec0
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8)
+        || ip.segments()[..6] == [0, 0, 0, 0, 0, 0])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{WebhookClient, is_public_address};
+
+    #[test]
+    fn only_public_unicast_addresses_are_public() {
+        for private in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(!is_public_address(private.parse().unwrap()), "{private}");
+        }
+        for public in ["93.184.216.34", "8.8.8.8", "2606:4700::1111"] {
+            assert!(is_public_address(public.parse().unwrap()), "{public}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_non_public_targets_unless_allowed() {
+        let strict = WebhookClient::new(Duration::from_secs(1), false);
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/hook",
+            "http://localhost/hook",
+            "ftp://example.com/hook",
+        ] {
+            assert!(strict.check_target(url).await.is_err(), "{url}");
+        }
+
+        let permissive = WebhookClient::new(Duration::from_secs(1), true);
+        assert!(
+            permissive
+                .check_target("http://127.0.0.1:8080/hook")
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -6,14 +6,20 @@ mod presentation;
 
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 
-use application::services::{DirectoryService, IngestionService, TrustScoreService, VoteService};
+use application::services::{
+    CircuitBreakerPolicy, ConnectorFetchLimits, DirectoryService, GeocodingService,
+    IngestionService, TrustScoreService, VoteService, WebhookDeliveryPolicy, WebhookService,
+};
 use axum::Router;
 use config::{RunMode, Settings};
-use domain::repositories::FacilityRepository;
+use domain::{entities::IngestionTrigger, repositories::FacilityRepository};
 use infrastructure::{
-    connectors::default_connectors,
+    archive::PayloadArchive,
+    connectors::load_connectors,
+    gazetteer::Gazetteer,
     repositories::{InMemoryFacilityRepository, PostgresFacilityRepository},
     scheduler,
+    webhooks::WebhookClient,
 };
 use presentation::http::{AppState, rate_limit::VoteRateLimiter, routes::build_router};
 use tokio::net::TcpListener;
@@ -25,25 +31,92 @@ async fn main() -> anyhow::Result<()> {
     init_tracing();
 
     let settings = Settings::from_env();
+    // Fail before touching the database when the connector configuration is invalid.
+    let connectors = load_connectors(settings.connectors_file.as_deref())?;
     let repository = build_repository(&settings).await?;
 
-    let trust_score_service = Arc::new(TrustScoreService::default());
-    let ingestion_service = Arc::new(IngestionService::new(
+    let webhook_service = Arc::new(WebhookService::new(
         repository.clone(),
-        trust_score_service,
-        default_connectors(),
+        WebhookClient::new(
+            Duration::from_secs(settings.webhook_timeout_secs),
+            settings.webhook_allow_private_targets,
+        ),
+        WebhookDeliveryPolicy {
+            max_attempts: settings.webhook_max_attempts,
+            ..WebhookDeliveryPolicy::default()
+        },
     ));
+    let trust_score_service = Arc::new(TrustScoreService);
+    let mut ingestion_service = IngestionService::new(
+        repository.clone(),
+        trust_score_service,
+        connectors,
+        PayloadArchive::from_env().map(Arc::new),
+        ConnectorFetchLimits {
+            parallelism: settings.connector_parallelism,
+            connector_budget: Duration::from_secs(settings.connector_budget_secs),
+        },
+        CircuitBreakerPolicy {
+            failure_threshold: settings.circuit_failure_threshold,
+            cooldown: i64::try_from(settings.circuit_cooldown_hours)
+                .ok()
+                .and_then(chrono::Duration::try_hours)
+                .unwrap_or(chrono::Duration::MAX),
+        },
+        Some(webhook_service.clone()),
+    );
+    if let Some(gazetteer) = load_gazetteer(&settings).await {
+        ingestion_service = ingestion_service.with_geocoding(Arc::new(GeocodingService::new(
+            repository.clone(),
+            Arc::new(gazetteer),
+        )));
+    }
+    let ingestion_service = Arc::new(ingestion_service);
+    ingestion_service.abandon_interrupted_runs().await;
 
     if settings.run_mode == RunMode::RefreshOnce {
         info!("Running one-shot ingestion refresh");
-        ingestion_service.refresh().await?;
+        ingestion_service
+            .refresh(IngestionTrigger::RefreshOnce)
+            .await?;
         info!("One-shot ingestion refresh completed");
+        // Jobs have no dispatcher loop; push what is due now and leave retries to
+        // the next run or a worker.
+        if let Err(error) = webhook_service.dispatch_due().await {
+            error!(%error, "Webhook dispatch failed");
+        }
+        return Ok(());
+    }
+
+    if settings.run_mode == RunMode::Replay {
+        let run_id = settings
+            .replay_run_id
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("replay mode requires CLEANPLATED_REPLAY_RUN_ID"))?;
+        info!(run_id, "Replaying archived ingestion run");
+        ingestion_service.replay(run_id).await?;
+        info!(run_id, "Archived ingestion replay completed");
+        return Ok(());
+    }
+
+    if settings.run_mode == RunMode::ExportLives {
+        let body = DirectoryService::new(repository).lives_export().await?;
+        tokio::fs::write(&settings.export_path, &body).await?;
+        info!(
+            path = %settings.export_path.display(),
+            bytes = body.len(),
+            "Wrote LIVES export"
+        );
         return Ok(());
     }
 
     if settings.run_mode == RunMode::Worker {
         info!("Running ingestion worker mode");
-        if let Err(error) = ingestion_service.refresh().await {
+        tokio::spawn(scheduler::run_webhook_dispatcher(
+            webhook_service.clone(),
+            settings.webhook_dispatch_interval_secs,
+        ));
+        if let Err(error) = ingestion_service.refresh(IngestionTrigger::Startup).await {
             error!(%error, "Initial worker refresh failed");
         } else {
             info!("Initial worker refresh completed");
@@ -55,7 +128,10 @@ async fn main() -> anyhow::Result<()> {
     if settings.enable_background_ingestion {
         let initial_ingestion_service = ingestion_service.clone();
         tokio::spawn(async move {
-            if let Err(err) = initial_ingestion_service.refresh().await {
+            if let Err(err) = initial_ingestion_service
+                .refresh(IngestionTrigger::Startup)
+                .await
+            {
                 error!(error = %err, "Initial ingestion failed; API will still start");
             } else {
                 info!("Initial ingestion completed");
@@ -68,11 +144,18 @@ async fn main() -> anyhow::Result<()> {
         ));
     }
 
+    tokio::spawn(scheduler::run_webhook_dispatcher(
+        webhook_service.clone(),
+        settings.webhook_dispatch_interval_secs,
+    ));
+
     let app_state = AppState {
         directory_service: Arc::new(DirectoryService::new(repository.clone())),
         ingestion_service: ingestion_service.clone(),
         vote_service: Arc::new(VoteService::new(repository)),
+        webhook_service,
         vote_rate_limiter: VoteRateLimiter::new(20, Duration::from_secs(60)),
+        admin_token: settings.admin_token.clone().map(Arc::from),
     };
 
     let app = app_router(app_state, &settings);
@@ -128,6 +211,33 @@ async fn shutdown_signal() {
     info!("Shutdown signal received");
 }
 
+/// Loads the configured gazetteer files; ingestion runs without geocoding when none
+/// are configured or they cannot be read.
+async fn load_gazetteer(settings: &Settings) -> Option<Gazetteer> {
+    if settings.gazetteer_paths.is_empty() {
+        return None;
+    }
+
+    let paths = settings.gazetteer_paths.clone();
+    match tokio::task::spawn_blocking(move || Gazetteer::load(&paths)).await {
+        Ok(Ok(gazetteer)) => {
+            info!(
+                addresses = gazetteer.address_count(),
+                "Loaded geocoding gazetteer"
+            );
+            Some(gazetteer)
+        }
+        Ok(Err(error)) => {
+            warn!(error = %format!("{error:#}"), "Unable to load gazetteer; geocoding disabled");
+            None
+        }
+        Err(error) => {
+            warn!(%error, "Gazetteer loading panicked; geocoding disabled");
+            None
+        }
+    }
+}
+
 async fn build_repository(settings: &Settings) -> anyhow::Result<Arc<dyn FacilityRepository>> {
     if let Some(database_url) = settings.database_url.as_deref() {
         let repository = PostgresFacilityRepository::connect(database_url)
@@ -2,15 +2,23 @@ use axum::{
     Json,
     extract::{Path, Query, State},
     http::HeaderMap,
-    http::StatusCode,
+    http::{StatusCode, header},
+    response::IntoResponse,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::error;
 
 use crate::{
-    application::dto::FacilitySearchQuery,
-    domain::entities::VoteValue,
+    application::{dto::FacilitySearchQuery, services::NewWebhookSubscription},
+    domain::{
+        entities::{
+            FacilityEventKind, IngestionTrigger, Jurisdiction, RecordDisposition, ValidationReason,
+            VoteValue, WebhookDeliveryStatus,
+        },
+        repositories::{DeadLetterQuery, FacilityEventQuery, WebhookDeliveryQuery},
+    },
     presentation::http::AppState,
 };
 
@@ -34,6 +42,50 @@ pub struct TopPicksParams {
     pub limit: Option<usize>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct IngestionRunsParams {
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLettersParams {
+    pub source: Option<String>,
+    pub run_id: Option<String>,
+    pub disposition: Option<String>,
+    pub reason: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FacilityEventsParams {
+    pub facility_id: Option<String>,
+    pub jurisdiction: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub since: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookSubscriptionRequest {
+    pub url: String,
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub jurisdictions: Vec<String>,
+    #[serde(default)]
+    pub facility_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveriesParams {
+    pub subscription_id: Option<String>,
+    pub event_id: Option<String>,
+    pub status: Option<String>,
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct HealthPayload {
     pub status: &'static str,
@@ -93,6 +145,25 @@ pub async fn get_facility(
     }
 }
 
+pub async fn facility_sources(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let sources = state
+        .directory_service
+        .sources(&id)
+        .await
+        .map_err(internal_error)?;
+
+    match sources {
+        Some(data) => Ok(Json(serde_json::json!({
+            "data": data,
+            "count": data.len(),
+        }))),
+        None => Err((StatusCode::NOT_FOUND, "Facility not found".to_owned())),
+    }
+}
+
 pub async fn top_picks(
     State(state): State<AppState>,
     Query(params): Query<TopPicksParams>,
@@ -110,6 +181,31 @@ pub async fn top_picks(
     })))
 }
 
+/// The export dumps every published facility and is built per request, so it needs
+/// the admin token.
+pub async fn export_lives(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let body = state
+        .directory_service
+        .lives_export()
+        .await
+        .map_err(|error| internal_error(format!("{error:#}")))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"lives.zip\"",
+            ),
+        ],
+        body,
+    ))
+}
+
 pub async fn ingestion_status(
     State(state): State<AppState>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
@@ -118,12 +214,246 @@ pub async fn ingestion_status(
     Ok(Json(serde_json::json!({ "data": stats })))
 }
 
+pub async fn ingestion_runs(
+    State(state): State<AppState>,
+    Query(params): Query<IngestionRunsParams>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 200);
+    let data = state
+        .ingestion_service
+        .runs(limit)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(serde_json::json!({
+        "data": data,
+        "count": data.len(),
+    })))
+}
+
+/// Dead letters carry raw source payloads, so listing them needs the admin token.
+pub async fn dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<DeadLettersParams>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let disposition = match params.disposition.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(code) => Some(RecordDisposition::from_code(code).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                "disposition must be one of: valid, repaired, rejected".to_owned(),
+            )
+        })?),
+    };
+    let reason = match params.reason.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(code) => Some(ValidationReason::from_code(code).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("unknown validation reason: {code}"),
+            )
+        })?),
+    };
+
+    let data = state
+        .ingestion_service
+        .dead_letters(&DeadLetterQuery {
+            source: params.source.filter(|value| !value.trim().is_empty()),
+            run_id: params.run_id.filter(|value| !value.trim().is_empty()),
+            disposition,
+            reason,
+            limit: params.limit.unwrap_or(100).clamp(1, 1_000),
+        })
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(serde_json::json!({
+        "data": data,
+        "count": data.len(),
+    })))
+}
+
+pub async fn facility_events(
+    State(state): State<AppState>,
+    Query(params): Query<FacilityEventsParams>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let jurisdiction = match params.jurisdiction.as_deref().map(str::trim) {
+        None | Some("") | Some("all") => None,
+        Some(value) => Some(parse_jurisdiction(value)?),
+    };
+    let kind = match params.kind.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(code) => Some(parse_event_kind(code)?),
+    };
+    let since = match params.since.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(value) => Some(
+            DateTime::parse_from_rfc3339(value)
+                .map(|since| since.with_timezone(&Utc))
+                .map_err(|_| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        "since must be an RFC 3339 timestamp".to_owned(),
+                    )
+                })?,
+        ),
+    };
+
+    let data = state
+        .ingestion_service
+        .events(&FacilityEventQuery {
+            facility_id: params.facility_id.filter(|value| !value.trim().is_empty()),
+            jurisdiction,
+            kind,
+            since,
+            limit: params.limit.unwrap_or(100).clamp(1, 1_000),
+        })
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(serde_json::json!({
+        "data": data,
+        "count": data.len(),
+    })))
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<WebhookSubscriptionRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let url = payload.url.trim();
+    state
+        .webhook_service
+        .check_target(url)
+        .await
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
+    let event_types = payload
+        .event_types
+        .iter()
+        .map(|code| parse_event_kind(code.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let jurisdictions = payload
+        .jurisdictions
+        .iter()
+        .map(|value| parse_jurisdiction(value.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let facility_ids = payload
+        .facility_ids
+        .into_iter()
+        .map(|id| id.trim().to_owned())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    let subscription = state
+        .webhook_service
+        .subscribe(NewWebhookSubscription {
+            url: url.to_owned(),
+            secret: payload.secret.map(|secret| secret.trim().to_owned()),
+            event_types,
+            jurisdictions,
+            facility_ids,
+        })
+        .await
+        .map_err(internal_error)?;
+
+    // The secret is only ever returned here.
+    let mut data = serde_json::json!(subscription);
+    data["secret"] = serde_json::json!(subscription.secret);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "data": data })),
+    ))
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let data = state
+        .webhook_service
+        .subscriptions()
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(serde_json::json!({
+        "data": data,
+        "count": data.len(),
+    })))
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let deleted = state
+        .webhook_service
+        .unsubscribe(&id)
+        .await
+        .map_err(internal_error)?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            "Webhook subscription not found".to_owned(),
+        ))
+    }
+}
+
+pub async fn webhook_deliveries(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<WebhookDeliveriesParams>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let status = match params.status.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(code) => Some(WebhookDeliveryStatus::from_code(code).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                "status must be one of: pending, delivered, failed".to_owned(),
+            )
+        })?),
+    };
+
+    let data = state
+        .webhook_service
+        .deliveries(&WebhookDeliveryQuery {
+            subscription_id: params
+                .subscription_id
+                .filter(|value| !value.trim().is_empty()),
+            event_id: params.event_id.filter(|value| !value.trim().is_empty()),
+            status,
+            limit: params.limit.unwrap_or(100).clamp(1, 1_000),
+        })
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(serde_json::json!({
+        "data": data,
+        "count": data.len(),
+    })))
+}
+
 pub async fn trigger_refresh(
     State(state): State<AppState>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
     let ingestion_service = state.ingestion_service.clone();
     tokio::spawn(async move {
-        if let Err(err) = ingestion_service.refresh().await {
+        if let Err(err) = ingestion_service
+            .refresh(IngestionTrigger::ApiRefresh)
+            .await
+        {
             error!(error = %err, "Triggered ingestion refresh failed");
         }
     });
@@ -160,7 +490,11 @@ pub async fn record_vote(
         ));
     }
 
-    let exists = state.directory_service.get(&id).await.map_err(internal_error)?;
+    let exists = state
+        .directory_service
+        .get(&id)
+        .await
+        .map_err(internal_error)?;
     if exists.is_none() {
         return Err((StatusCode::NOT_FOUND, "Facility not found".to_owned()));
     }
@@ -184,6 +518,60 @@ pub async fn record_vote(
     ))
 }
 
+/// Accepts a jurisdiction code (`lb`) or label (`Long Beach`), case-insensitively.
+fn parse_jurisdiction(value: &str) -> Result<Jurisdiction, (StatusCode, String)> {
+    Jurisdiction::ALL
+        .into_iter()
+        .find(|jurisdiction| {
+            jurisdiction.code().eq_ignore_ascii_case(value)
+                || jurisdiction.label().eq_ignore_ascii_case(value)
+        })
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("unknown jurisdiction: {value}"),
+            )
+        })
+}
+
+fn parse_event_kind(code: &str) -> Result<FacilityEventKind, (StatusCode, String)> {
+    FacilityEventKind::from_code(code).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("unknown event type: {code}"),
+        )
+    })
+}
+
+/// Admin endpoints need `Authorization: Bearer <CLEANPLATED_ADMIN_TOKEN>` and are
+/// disabled without a configured token: webhook management, since subscribers choose
+/// where the server POSTs, the dead-letter listing, since it returns raw payloads, and
+/// the LIVES export, since each request builds a full dump.
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let Some(expected) = state.admin_token.as_deref() else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "admin endpoints are disabled; set CLEANPLATED_ADMIN_TOKEN".to_owned(),
+        ));
+    };
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+
+    // Digests are compared so the check takes the same time however much matches.
+    if Sha256::digest(presented.as_bytes()) == Sha256::digest(expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::UNAUTHORIZED,
+            "a valid admin bearer token is required".to_owned(),
+        ))
+    }
+}
+
 fn internal_error(error: impl std::fmt::Display) -> (StatusCode, String) {
     (
         StatusCode::INTERNAL_SERVER_ERROR,
@@ -211,3 +599,186 @@ fn extract_voter_key(headers: &HeaderMap) -> String {
 
     from_real_ip.unwrap_or_else(|| "unknown".to_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::{
+        Json,
+        extract::State,
+        http::{HeaderMap, StatusCode, header},
+    };
+
+    use axum::extract::Query;
+
+    use super::{
+        DeadLettersParams, IngestionRunsParams, WebhookSubscriptionRequest, create_webhook,
+        dead_letters, export_lives, ingestion_runs,
+    };
+    use crate::{
+        application::services::{
+            CircuitBreakerPolicy, ConnectorFetchLimits, DirectoryService, IngestionService,
+            TrustScoreService, VoteService, WebhookDeliveryPolicy, WebhookService,
+        },
+        domain::entities::IngestionTrigger,
+        infrastructure::{repositories::InMemoryFacilityRepository, webhooks::WebhookClient},
+        presentation::http::{AppState, rate_limit::VoteRateLimiter},
+    };
+
+    fn state(admin_token: Option<&str>) -> AppState {
+        let repository = Arc::new(InMemoryFacilityRepository::new());
+        AppState {
+            directory_service: Arc::new(DirectoryService::new(repository.clone())),
+            ingestion_service: Arc::new(IngestionService::new(
+                repository.clone(),
+                Arc::new(TrustScoreService),
+                Vec::new(),
+                None,
+                ConnectorFetchLimits::default(),
+                CircuitBreakerPolicy::default(),
+                None,
+            )),
+            vote_service: Arc::new(VoteService::new(repository.clone())),
+            webhook_service: Arc::new(WebhookService::new(
+                repository,
+                WebhookClient::new(Duration::from_secs(1), false),
+                WebhookDeliveryPolicy::default(),
+            )),
+            vote_rate_limiter: VoteRateLimiter::new(20, Duration::from_secs(60)),
+            admin_token: admin_token.map(Arc::from),
+        }
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {token}").parse().unwrap(),
+        );
+        headers
+    }
+
+    async fn register(state: &AppState, headers: HeaderMap, url: &str) -> StatusCode {
+        let request = WebhookSubscriptionRequest {
+            url: url.to_owned(),
+            secret: None,
+            event_types: Vec::new(),
+            jurisdictions: Vec::new(),
+            facility_ids: Vec::new(),
+        };
+        match create_webhook(State(state.clone()), headers, Json(request)).await {
+            Ok((status, _)) => status,
+            Err((status, _)) => status,
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_registration_needs_the_admin_token_and_a_public_target() {
+        let disabled = state(None);
+        assert_eq!(
+            register(&disabled, bearer("s3cret"), "http://93.184.216.34/hook").await,
+            StatusCode::FORBIDDEN
+        );
+
+        let state = state(Some("s3cret"));
+        assert_eq!(
+            register(&state, HeaderMap::new(), "http://93.184.216.34/hook").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            register(&state, bearer("guess"), "http://93.184.216.34/hook").await,
+            StatusCode::UNAUTHORIZED
+        );
+        for internal in [
+            "http://169.254.169.254/latest/meta-data",
+            "http://127.0.0.1:8080/hook",
+            "http://10.0.0.5/hook",
+        ] {
+            assert_eq!(
+                register(&state, bearer("s3cret"), internal).await,
+                StatusCode::BAD_REQUEST,
+                "{internal}"
+            );
+        }
+        assert_eq!(
+            register(&state, bearer("s3cret"), "http://93.184.216.34/hook").await,
+            StatusCode::CREATED
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_ingestion_runs_newest_first_up_to_the_limit() {
+        let state = state(None);
+        // With no connectors every refresh fails, but each one is still recorded.
+        for trigger in [IngestionTrigger::Startup, IngestionTrigger::ApiRefresh] {
+            assert!(state.ingestion_service.refresh(trigger).await.is_err());
+        }
+
+        let Json(body) = ingestion_runs(
+            State(state.clone()),
+            Query(IngestionRunsParams { limit: None }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["data"][0]["trigger"], "api_refresh");
+        assert_eq!(body["data"][0]["outcome"], "failed");
+        assert_eq!(body["data"][1]["trigger"], "startup");
+
+        let Json(body) =
+            ingestion_runs(State(state), Query(IngestionRunsParams { limit: Some(0) }))
+                .await
+                .unwrap();
+        assert_eq!(body["count"], 1);
+    }
+
+    #[tokio::test]
+    async fn dead_letters_need_the_admin_token() {
+        let list = |state: AppState, headers: HeaderMap| async move {
+            let params = DeadLettersParams {
+                source: None,
+                run_id: None,
+                disposition: None,
+                reason: None,
+                limit: None,
+            };
+            match dead_letters(State(state), headers, Query(params)).await {
+                Ok(_) => StatusCode::OK,
+                Err((status, _)) => status,
+            }
+        };
+
+        assert_eq!(
+            list(state(None), bearer("s3cret")).await,
+            StatusCode::FORBIDDEN
+        );
+        let state = state(Some("s3cret"));
+        assert_eq!(
+            list(state.clone(), HeaderMap::new()).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(list(state, bearer("s3cret")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn lives_export_needs_the_admin_token() {
+        let export = |state: AppState, headers: HeaderMap| async move {
+            match export_lives(State(state), headers).await {
+                Ok(_) => StatusCode::OK,
+                Err((status, _)) => status,
+            }
+        };
+
+        assert_eq!(
+            export(state(None), bearer("s3cret")).await,
+            StatusCode::FORBIDDEN
+        );
+        let state = state(Some("s3cret"));
+        assert_eq!(
+            export(state.clone(), bearer("guess")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(export(state, bearer("s3cret")).await, StatusCode::OK);
+    }
+}
@@ -4,7 +4,9 @@ pub mod routes;
 
 use std::sync::Arc;
 
-use crate::application::services::{DirectoryService, IngestionService, VoteService};
+use crate::application::services::{
+    DirectoryService, IngestionService, VoteService, WebhookService,
+};
 use crate::presentation::http::rate_limit::VoteRateLimiter;
 
 #[derive(Clone)]
@@ -12,5 +14,8 @@ pub struct AppState {
     pub directory_service: Arc<DirectoryService>,
     pub ingestion_service: Arc<IngestionService>,
     pub vote_service: Arc<VoteService>,
+    pub webhook_service: Arc<WebhookService>,
     pub vote_rate_limiter: VoteRateLimiter,
+    /// Bearer token for webhook management; `None` disables it.
+    pub admin_token: Option<Arc<str>>,
 }
@@ -42,4 +42,3 @@ impl VoteRateLimiter {
         true
     }
 }
-
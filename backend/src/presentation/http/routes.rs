@@ -1,6 +1,6 @@
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 
 use crate::presentation::http::{AppState, handlers};
@@ -10,15 +10,32 @@ pub fn build_router(state: AppState) -> Router {
         .route("/health", get(handlers::health))
         .route("/api/v1/facilities", get(handlers::list_facilities))
         .route("/api/v1/facilities/top-picks", get(handlers::top_picks))
+        .route("/api/v1/facilities/{id}", get(handlers::get_facility))
         .route(
-            "/api/v1/facilities/{id}",
-            get(handlers::get_facility),
+            "/api/v1/facilities/{id}/sources",
+            get(handlers::facility_sources),
         )
+        .route("/api/v1/facilities/{id}/vote", post(handlers::record_vote))
+        .route("/api/v1/events", get(handlers::facility_events))
+        .route("/api/v1/export/lives.zip", get(handlers::export_lives))
         .route(
-            "/api/v1/facilities/{id}/vote",
-            post(handlers::record_vote),
+            "/api/v1/webhooks",
+            get(handlers::list_webhooks).post(handlers::create_webhook),
         )
+        .route(
+            "/api/v1/webhooks/deliveries",
+            get(handlers::webhook_deliveries),
+        )
+        .route("/api/v1/webhooks/{id}", delete(handlers::delete_webhook))
         .route("/api/v1/system/ingestion", get(handlers::ingestion_status))
+        .route(
+            "/api/v1/system/ingestion/runs",
+            get(handlers::ingestion_runs),
+        )
+        .route(
+            "/api/v1/system/ingestion/dead-letters",
+            get(handlers::dead_letters),
+        )
         .route("/api/v1/system/refresh", post(handlers::trigger_refresh))
         .with_state(state)
 }